@@ -200,7 +200,7 @@ impl BSPComplex {
         let constraints = Vec::from_iter(constraints_data.triangles.iter().map(|&idx| idx.into()));
         let tri_axes = Vec::from_iter(constraints_data.triangles.chunks(3).map(|tri| {
             bump.reset();
-            let tri_points = from_fn::<_, 3, _>(|i| point(tet_mesh.points, tri[i]));
+            let tri_points = from_fn::<_, 3, _>(|i| point(&tet_mesh.points, tri[i]));
             max_comp_in_tri_normal(tri_points[0], tri_points[1], tri_points[2], &bump)
         }));
 
@@ -210,7 +210,7 @@ impl BSPComplex {
                 .chunks(3)
                 .enumerate()
                 .map(|(i, tri)| {
-                    let tri_points = from_fn::<_, 3, _>(|i| point(tet_mesh.points, tri[i]));
+                    let tri_points = from_fn::<_, 3, _>(|i| point(&tet_mesh.points, tri[i]));
                     orient2d_by_axis(
                         tri_points[0],
                         tri_points[1],
@@ -575,7 +575,8 @@ impl BSPComplex {
         }
     }
 
-    pub fn complex_partition(&mut self, tri_in_shell: &[usize]) -> (Vec<f64>, Vec<usize>) {
+    /// The coordinates of the vertices, rounded for the implicit ones.
+    fn explicit_points(&self) -> Vec<f64> {
         let mut explicit_points = vec![0.0; self.points.len() * 3];
         for (p, data) in self.points.iter().zip(explicit_points.chunks_mut(3)) {
             match p {
@@ -592,6 +593,41 @@ impl BSPComplex {
                 }
             }
         }
+        explicit_points
+    }
+
+    /// Whether the constraint triangle `tid` covers the coplanar face `fid`,
+    /// `center` is the center of the face if it is inside the face.
+    fn tri_covers_face<A: Allocator + Copy>(
+        &self,
+        fid: FaceId,
+        tid: usize,
+        center: Option<&Point3D>,
+        explicit_points: &[f64],
+        bump: A,
+    ) -> bool {
+        let tri = triangle(tid, &self.constraints);
+        let pa = point(explicit_points, *tri[0]);
+        let pb = point(explicit_points, *tri[1]);
+        let pc = point(explicit_points, *tri[2]);
+
+        let axis = self.tri_axes[tid];
+
+        if let Some(center) = center {
+            let center = center.explicit().unwrap();
+            let orientations = [
+                orient2d_by_axis(pa, pb, center, axis, bump),
+                orient2d_by_axis(pb, pc, center, axis, bump),
+                orient2d_by_axis(pc, pa, center, axis, bump),
+            ];
+            vert_in_tri(&orientations)
+        } else {
+            self.face_intersects_tri(fid, tri, axis, bump)
+        }
+    }
+
+    pub fn complex_partition(&mut self, tri_in_shell: &[usize]) -> (Vec<f64>, Vec<usize>) {
+        let explicit_points = self.explicit_points();
         let mut bump = Bump::new();
         let (face_areas, face_centers) = {
             let mut areas = Vec::with_capacity(self.face_data.len());
@@ -647,26 +683,13 @@ impl BSPComplex {
                 if is_black[shell_id][fid] {
                     continue;
                 }
-                let tri = triangle(tid, &self.constraints);
-                let pa = point(&explicit_points, *tri[0]);
-                let pb = point(&explicit_points, *tri[1]);
-                let pc = point(&explicit_points, *tri[2]);
-
-                let axis = self.tri_axes[tid];
-
-                let face_intersects_tri = if center_in_face {
-                    let center = face_centers[fid].explicit().unwrap();
-                    let orientations = [
-                        orient2d_by_axis(pa, pb, &center, axis, &bump),
-                        orient2d_by_axis(pb, pc, &center, axis, &bump),
-                        orient2d_by_axis(pc, pa, &center, axis, &bump),
-                    ];
-                    vert_in_tri(&orientations)
+                let center = if center_in_face {
+                    Some(&face_centers[fid])
                 } else {
-                    self.face_intersects_tri(fid, tri, axis, &bump)
+                    None
                 };
-
-                if face_intersects_tri {
+                if self.tri_covers_face(fid, tid, center, &explicit_points, &bump) {
+                    let tri = triangle(tid, &self.constraints);
                     verts_orient_wrt_plane(
                         &self.points[tri[0]],
                         &self.points[tri[1]],
@@ -839,6 +862,225 @@ impl BSPComplex {
         (out_points, triangles)
     }
 
+    /// The winding numbers of the cells around the constraint triangles, zero
+    /// for the cells on the convex hull outside of them.
+    pub(crate) fn cell_windings(&mut self) -> Vec<i32> {
+        let explicit_points = self.explicit_points();
+        let mut bump = Bump::new();
+        // the change of the winding number from the second cell of each face
+        // to the first one
+        let mut face_windings = vec![0; self.face_data.len()];
+        for face in self.mesh.faces() {
+            let fid = *face;
+            let face_data = &self.face_data[fid];
+            let face_triangles = &face_data.triangles;
+            if face_triangles.is_empty() {
+                continue;
+            }
+
+            bump.reset();
+            let first_tid = face_triangles[0];
+            // uncoplanar vertex
+            let vert = [find_uncoplanar_verts(
+                triangle(first_tid, &self.constraints),
+                &mut self.vert_orientations[first_tid],
+                &self.vertex_data,
+                &self.points,
+                &self.cell_data[face_data.cells[0]].faces,
+                &self.mesh,
+                &bump,
+            )];
+
+            let mut face_verts = Vec::new_in(&bump);
+            face_verts.extend(face.halfedges().map(|he| *he.from()));
+            let center = face_center(&explicit_points, &face_verts);
+            let center_in_face = self.point_in_face(fid, &center, self.tri_axes[first_tid], &bump);
+
+            for &tid in face_triangles {
+                let center = if center_in_face { Some(&center) } else { None };
+                if self.tri_covers_face(fid, tid, center, &explicit_points, &bump) {
+                    let tri = triangle(tid, &self.constraints);
+                    verts_orient_wrt_plane(
+                        &self.points[tri[0]],
+                        &self.points[tri[1]],
+                        &self.points[tri[2]],
+                        &vert,
+                        &self.points,
+                        &self.vertex_data,
+                        &mut self.vert_orientations[tid],
+                        &bump,
+                    );
+                    // the first cell is inside a triangle with the same
+                    // orientation as the face
+                    if *self.vert_orientations[tid].get(&vert[0]).unwrap() == Orientation::Positive
+                    {
+                        face_windings[fid] += 1;
+                    } else {
+                        face_windings[fid] -= 1;
+                    }
+                }
+            }
+        }
+
+        let mut windings = vec![0; self.cell_data.len()];
+        let mut visited = vec![false; self.cell_data.len()];
+        let mut queue = Vec::new();
+        for face in self.mesh.faces() {
+            let [c1, c2] = self.face_data[*face].cells;
+            if c2 == INVALID_IND && !visited[c1] {
+                windings[c1] = face_windings[*face];
+                visited[c1] = true;
+                queue.push(c1);
+            }
+        }
+        let mut idx = 0;
+        while idx < queue.len() {
+            let cid = queue[idx];
+            idx += 1;
+            for &fid in &self.cell_data[cid].faces {
+                let [c1, c2] = self.face_data[fid].cells;
+                if c2 == INVALID_IND {
+                    continue;
+                }
+                let (adj_cid, winding) = if c1 == cid {
+                    (c2, windings[cid] - face_windings[fid])
+                } else {
+                    (c1, windings[cid] + face_windings[fid])
+                };
+                if !visited[adj_cid] {
+                    windings[adj_cid] = winding;
+                    visited[adj_cid] = true;
+                    queue.push(adj_cid);
+                }
+            }
+        }
+        windings
+    }
+
+    /// Split the cells into tetrahedra. The faces are split into triangles by
+    /// clipping their corners, and the cells which are not
+    /// tetrahedra into the tetrahedra joining a new vertex at their centroid
+    /// to the triangles of their faces, or a vertex of the cell if the rounded
+    /// centroid is not strictly inside the cell. Returns the coordinates of the
+    /// vertices, rounded for the implicit ones, the tetrahedra and the cell of
+    /// each of them.
+    pub(crate) fn tetrahedralize_cells(&self) -> (Vec<f64>, Vec<usize>, Vec<usize>) {
+        let mut points = self.explicit_points();
+        let mut bump = Bump::new();
+        let mut face_triangles = vec![Vec::new(); self.face_data.len()];
+        for face in self.mesh.faces() {
+            bump.reset();
+            let [pa, pb, pc] = self.face_data[*face]
+                .plane
+                .map(|vid| self.points[vid].explicit().unwrap());
+            let axis = max_comp_in_tri_normal(pa, pb, pc, &bump);
+            let mut verts = Vec::from_iter(face.halfedges().map(|he| *he.from()));
+            let ori = |a: VertexId, b: VertexId, c: VertexId| {
+                orient2d::orient2d_by_axis(
+                    &self.points[a],
+                    &self.points[b],
+                    &self.points[c],
+                    axis,
+                    &bump,
+                )
+            };
+            // clip the strict corners of the convex face, so that the vertices
+            // on its edges are kept, unless the rest of the face is flat
+            while verts.len() > 3 {
+                let n = verts.len();
+                let ear = (0..n).find(|&i| {
+                    let [a, b, c] = [verts[(i + n - 1) % n], verts[i], verts[(i + 1) % n]];
+                    ori(a, b, c) != Orientation::Zero
+                        && verts
+                            .iter()
+                            .any(|&w| w != b && ori(a, c, w) != Orientation::Zero)
+                });
+                let Some(i) = ear else {
+                    break;
+                };
+                face_triangles[*face].push([verts[(i + n - 1) % n], verts[i], verts[(i + 1) % n]]);
+                verts.remove(i);
+            }
+            if verts.len() == 3 && ori(verts[0], verts[1], verts[2]) != Orientation::Zero {
+                face_triangles[*face].push([verts[0], verts[1], verts[2]]);
+            }
+        }
+
+        let mut tets = Vec::new();
+        let mut tet_cells = Vec::new();
+        for (cid, cell) in self.cell_data.iter().enumerate() {
+            bump.reset();
+            let mut add_tet = |[va, vb, vc]: [VertexId; 3], vd: usize, pd: &Point3D| {
+                let [pa, pb, pc] = [va, vb, vc].map(|vid| &self.points[vid]);
+                match orient3d(pa, pb, pc, pd, &bump) {
+                    Orientation::Positive => tets.extend([*va, *vb, *vc, vd]),
+                    Orientation::Negative => tets.extend([*va, *vc, *vb, vd]),
+                    _ => return,
+                }
+                tet_cells.push(cid);
+            };
+            let is_tet = cell.faces.len() == 4
+                && cell
+                    .faces
+                    .iter()
+                    .all(|&fid| self.mesh.face(fid).halfedges().count() == 3);
+            if is_tet {
+                let tri = face_triangles[cell.faces[0]][0];
+                let apex = self
+                    .mesh
+                    .face(cell.faces[1])
+                    .halfedges()
+                    .map(|he| *he.from())
+                    .find(|vid| !tri.contains(vid))
+                    .unwrap();
+                add_tet(tri, *apex, &self.points[apex]);
+                continue;
+            }
+
+            let mut cell_verts = Vec::from_iter(
+                cell.faces
+                    .iter()
+                    .flat_map(|&fid| self.mesh.face(fid).halfedges().map(|he| *he.from())),
+            );
+            cell_verts.sort_unstable_by_key(|vid| vid.0);
+            cell_verts.dedup();
+            let center = face_center(&points[..(self.points.len() * 3)], &cell_verts);
+            // the rounded centroid of a thin cell may lie on the plane of a
+            // face or outside of the cell, the tetrahedra are then fanned from
+            // a vertex of the cell to the faces which do not contain it
+            let inside = cell.faces.iter().all(|&fid| {
+                let Some(tri) = face_triangles[fid].first() else {
+                    return true;
+                };
+                let [pa, pb, pc] = tri.map(|vid| &self.points[vid]);
+                let side = orient3d(pa, pb, pc, &center, &bump);
+                side != Orientation::Zero
+                    && cell_verts.iter().all(|&vid| {
+                        let ori = orient3d(pa, pb, pc, &self.points[vid], &bump);
+                        ori == Orientation::Zero || ori == side
+                    })
+            });
+            if inside {
+                let center_vid = points.len() / 3;
+                points.extend(center.explicit().unwrap().data);
+                for &fid in &cell.faces {
+                    for &tri in &face_triangles[fid] {
+                        add_tet(tri, center_vid, &center);
+                    }
+                }
+            } else {
+                // the faces containing the apex give flat tetrahedra, skipped
+                let apex = cell_verts[0];
+                for &fid in &cell.faces {
+                    for &tri in &face_triangles[fid] {
+                        add_tet(tri, apex.0, &self.points[apex]);
+                    }
+                }
+            }
+        }
+        (points, tets, tet_cells)
+    }
+
     fn merge_faces(&self, kept_faces: Vec<i32>) -> Vec<(Vec<Vec<VertexId>>, usize)> {
         let mut edge_faces = vec![Vec::new(); self.edge_data.len()];
         let mut f_old_to_new = vec![INVALID_IND; self.face_data.len()];
@@ -53,11 +53,9 @@ pub fn remove_duplicates(points: &[f64], epsilon: f64) -> (Vec<f64>, Vec<usize>)
     )
 }
 
-fn make_mesh_for_proper_triangles(
-    points: &[f64],
-    triangles: Vec<usize>,
-    tri_in_shells: &[usize],
-) -> (Vec<f64>, Vec<usize>) {
+/// Split the Delaunay tetrahedralization of `points` into the convex cells of
+/// the arrangement of the constraint `triangles`.
+fn make_complex(points: &[f64], triangles: Vec<usize>) -> BSPComplex {
     let mut constraints = Constraints::new(triangles);
     let mut tet_mesh = tetrahedralize(points);
     constraints.place_virtual_constraints(&tet_mesh);
@@ -75,8 +73,39 @@ fn make_mesh_for_proper_triangles(
             }
         }
     }
+    complex
+}
 
-    complex.complex_partition(&tri_in_shells)
+fn make_mesh_for_proper_triangles(
+    points: &[f64],
+    triangles: Vec<usize>,
+    tri_in_shells: &[usize],
+) -> (Vec<f64>, Vec<usize>) {
+    let mut complex = make_complex(points, triangles);
+    complex.complex_partition(tri_in_shells)
+}
+
+/// Tetrahedralize the convex hull of the distinct `points` so that the faces
+/// of the tetrahedra conform to the constraint `triangles`, which may cross
+/// each other. The intersections of the constraints are new vertices, and the
+/// cells they cut out of the Delaunay tetrahedralization which are not
+/// tetrahedra are split around a new vertex at their centroid, or from one of
+/// their vertices if the rounded centroid is not strictly inside them. The
+/// intersections are computed exactly, only the returned coordinates of the
+/// new vertices are rounded.
+///
+/// Returns the points, starting with the input points, the tetrahedra, and
+/// whether each of them is inside the closed surfaces made of the triangles,
+/// where their winding number is not zero.
+pub fn conforming_tetrahedralize(
+    points: &[f64],
+    triangles: &[usize],
+) -> (Vec<f64>, Vec<usize>, Vec<bool>) {
+    let mut complex = make_complex(points, triangles.to_vec());
+    let windings = complex.cell_windings();
+    let (points, tets, tet_cells) = complex.tetrahedralize_cells();
+    let inside = Vec::from_iter(tet_cells.into_iter().map(|cid| windings[cid] != 0));
+    (points, tets, inside)
 }
 
 pub fn make_polyhedral_mesh(
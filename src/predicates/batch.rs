@@ -0,0 +1,276 @@
+use std::alloc::Allocator;
+use std::simd::{f64x4, num::SimdFloat};
+
+use super::predicates::{insphere, orient3d, O3D_ERR_BOUND_A};
+
+const LANES: usize = 4;
+
+type Lanes3 = [f64x4; 3];
+
+/// Gather the coordinates of one point per lane.
+#[inline(always)]
+fn gather(points: &[&[f64]; LANES]) -> Lanes3 {
+    [0, 1, 2].map(|axis| f64x4::from_array(points.map(|p| p[axis])))
+}
+
+/// Static filter of `orient3d` on every lane, returns the determinants and
+/// their error bounds.
+#[inline(always)]
+fn orient3d_lanes([pa, pb, pc, pd]: [Lanes3; 4]) -> (f64x4, f64x4) {
+    let adx = pa[0] - pd[0];
+    let ady = pa[1] - pd[1];
+    let adz = pa[2] - pd[2];
+    let bdx = pb[0] - pd[0];
+    let bdy = pb[1] - pd[1];
+    let bdz = pb[2] - pd[2];
+    let cdx = pc[0] - pd[0];
+    let cdy = pc[1] - pd[1];
+    let cdz = pc[2] - pd[2];
+
+    let bdxcdy = bdx * cdy;
+    let cdxbdy = cdx * bdy;
+
+    let cdxady = cdx * ady;
+    let adxcdy = adx * cdy;
+
+    let adxbdy = adx * bdy;
+    let bdxady = bdx * ady;
+
+    let det = adz * (bdxcdy - cdxbdy) + bdz * (cdxady - adxcdy) + cdz * (adxbdy - bdxady);
+
+    let permanent = (bdxcdy.abs() + cdxbdy.abs()) * adz.abs()
+        + (cdxady.abs() + adxcdy.abs()) * bdz.abs()
+        + (adxbdy.abs() + bdxady.abs()) * cdz.abs();
+    (det, f64x4::splat(O3D_ERR_BOUND_A) * permanent)
+}
+
+/// Static filter of `insphere` on every lane, returns the determinants and
+/// their error bounds.
+#[inline(always)]
+fn insphere_lanes([pa, pb, pc, pd, pe]: [Lanes3; 5]) -> (f64x4, f64x4) {
+    let aex = pa[0] - pe[0];
+    let bex = pb[0] - pe[0];
+    let cex = pc[0] - pe[0];
+    let dex = pd[0] - pe[0];
+    let aey = pa[1] - pe[1];
+    let bey = pb[1] - pe[1];
+    let cey = pc[1] - pe[1];
+    let dey = pd[1] - pe[1];
+    let aez = pa[2] - pe[2];
+    let bez = pb[2] - pe[2];
+    let cez = pc[2] - pe[2];
+    let dez = pd[2] - pe[2];
+
+    let ab = aex * bey - bex * aey;
+    let bc = bex * cey - cex * bey;
+    let cd = cex * dey - dex * cey;
+    let da = dex * aey - aex * dey;
+    let ac = aex * cey - cex * aey;
+    let bd = bex * dey - dex * bey;
+
+    let abc = aez * bc - bez * ac + cez * ab;
+    let bcd = bez * cd - cez * bd + dez * bc;
+    let cda = cez * da + dez * ac + aez * cd;
+    let dab = dez * ab + aez * bd + bez * da;
+
+    let alift = aex * aex + aey * aey + aez * aez;
+    let blift = bex * bex + bey * bey + bez * bez;
+    let clift = cex * cex + cey * cey + cez * cez;
+    let dlift = dex * dex + dey * dey + dez * dez;
+
+    let det = (dlift * abc - clift * dab) + (blift * cda - alift * bcd);
+
+    let max_var = [bex, cex, dex, aey, bey, cey, dey, aez, bez, cez, dez]
+        .into_iter()
+        .fold(aex.abs(), |acc, v| acc.simd_max(v.abs()));
+    let mut epsilon = max_var;
+    epsilon *= epsilon;
+    epsilon *= epsilon;
+    epsilon *= max_var;
+    epsilon *= f64x4::splat(1.145750161413163e-13);
+    (det, epsilon)
+}
+
+/// Evaluate `n` predicates `LANES` at a time. `fetch` returns the points of
+/// the `i`-th predicate, `filter` the determinants and error bounds of a
+/// batch, and `exact` is called only for the lanes the filter cannot decide.
+#[inline(always)]
+fn evaluate_batch<'b, const N: usize>(
+    n: usize,
+    fetch: impl Fn(usize) -> [&'b [f64]; N],
+    filter: impl Fn([Lanes3; N]) -> (f64x4, f64x4),
+    exact: impl Fn([&'b [f64]; N]) -> f64,
+    result: &mut [f64],
+) {
+    assert!(result.len() >= n);
+    for start in (0..n).step_by(LANES) {
+        // pad the last batch with its last predicate
+        let ids: [usize; LANES] = std::array::from_fn(|i| (start + i).min(n - 1));
+        let args = ids.map(&fetch);
+        let lanes: [Lanes3; N] = std::array::from_fn(|k| gather(&args.map(|a| a[k])));
+        let (det, err_bound) = filter(lanes);
+        let (det, err_bound) = (det.to_array(), err_bound.to_array());
+        for lane in 0..LANES.min(n - start) {
+            result[start + lane] = if det[lane] > err_bound[lane] || -det[lane] > err_bound[lane] {
+                det[lane]
+            } else {
+                exact(args[lane])
+            };
+        }
+    }
+}
+
+#[inline(always)]
+fn point(points: &[f64], idx: usize) -> &[f64] {
+    &points[(idx * 3)..(idx * 3 + 3)]
+}
+
+/// Evaluate `orient3d(pa, pb, pc, pd)` for every point `pd` in `pds`, storing the
+/// results in `result`. The sign of each result is the same as `orient3d`.
+pub fn orient3d_points_batch<A: Allocator + Copy>(
+    pa: &[f64],
+    pb: &[f64],
+    pc: &[f64],
+    pds: &[f64],
+    result: &mut [f64],
+    allocator: A,
+) {
+    evaluate_batch(
+        pds.len() / 3,
+        |i| [pa, pb, pc, point(pds, i)],
+        orient3d_lanes,
+        |[pa, pb, pc, pd]| orient3d(pa, pb, pc, pd, allocator),
+        result,
+    );
+}
+
+/// Evaluate `orient3d` of the query point `pd` against every face of `faces`,
+/// a list of vertex triples indexing `points`.
+pub fn orient3d_faces_batch<A: Allocator + Copy>(
+    points: &[f64],
+    faces: &[usize],
+    pd: &[f64],
+    result: &mut [f64],
+    allocator: A,
+) {
+    evaluate_batch(
+        faces.len() / 3,
+        |i| {
+            let f = &faces[(i * 3)..(i * 3 + 3)];
+            [
+                point(points, f[0]),
+                point(points, f[1]),
+                point(points, f[2]),
+                pd,
+            ]
+        },
+        orient3d_lanes,
+        |[pa, pb, pc, pd]| orient3d(pa, pb, pc, pd, allocator),
+        result,
+    );
+}
+
+/// Evaluate `insphere(pa, pb, pc, pd, pe)` for every point `pe` in `pes`.
+pub fn insphere_points_batch<A: Allocator + Copy>(
+    pa: &[f64],
+    pb: &[f64],
+    pc: &[f64],
+    pd: &[f64],
+    pes: &[f64],
+    result: &mut [f64],
+    allocator: A,
+) {
+    evaluate_batch(
+        pes.len() / 3,
+        |i| [pa, pb, pc, pd, point(pes, i)],
+        insphere_lanes,
+        |[pa, pb, pc, pd, pe]| insphere(pa, pb, pc, pd, pe, allocator),
+        result,
+    );
+}
+
+/// Evaluate `insphere` of the query point `pe` against every tetrahedron of
+/// `tets`, a list of vertex quadruples indexing `points`.
+pub fn insphere_tets_batch<A: Allocator + Copy>(
+    points: &[f64],
+    tets: &[usize],
+    pe: &[f64],
+    result: &mut [f64],
+    allocator: A,
+) {
+    evaluate_batch(
+        tets.len() / 4,
+        |i| {
+            let t = &tets[(i * 4)..(i * 4 + 4)];
+            [
+                point(points, t[0]),
+                point(points, t[1]),
+                point(points, t[2]),
+                point(points, t[3]),
+                pe,
+            ]
+        },
+        insphere_lanes,
+        |[pa, pb, pc, pd, pe]| insphere(pa, pb, pc, pd, pe, allocator),
+        result,
+    );
+}
+
+#[test]
+fn test_batch_predicates() {
+    use super::double_to_sign;
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    let bump = bumpalo::Bump::new();
+    let mut rng = SmallRng::seed_from_u64(17);
+    // a grid makes many degenerate configurations
+    let points = Vec::from_iter((0..30).map(|_| rng.gen_range(0..3) as f64));
+    let faces = Vec::from_iter((0..21).map(|_| rng.gen_range(0..10)));
+    let tets = Vec::from_iter((0..28).map(|_| rng.gen_range(0..10)));
+
+    let mut result = vec![0.0; 10];
+    orient3d_points_batch(
+        &points[0..3],
+        &points[3..6],
+        &points[6..9],
+        &points,
+        &mut result,
+        &bump,
+    );
+    for (p, &det) in points.chunks(3).zip(&result) {
+        let expected = orient3d(&points[0..3], &points[3..6], &points[6..9], p, &bump);
+        assert_eq!(double_to_sign(det), double_to_sign(expected));
+    }
+
+    orient3d_faces_batch(&points, &faces, &points[9..12], &mut result, &bump);
+    for (i, f) in faces.chunks(3).enumerate() {
+        let expected = orient3d(
+            point(&points, f[0]),
+            point(&points, f[1]),
+            point(&points, f[2]),
+            &points[9..12],
+            &bump,
+        );
+        assert_eq!(double_to_sign(result[i]), double_to_sign(expected));
+    }
+
+    let (pa, pb, pc, pd) = (&points[0..3], &points[3..6], &points[6..9], &points[9..12]);
+    insphere_points_batch(pa, pb, pc, pd, &points, &mut result, &bump);
+    for (p, &det) in points.chunks(3).zip(&result) {
+        let expected = insphere(pa, pb, pc, pd, p, &bump);
+        assert_eq!(double_to_sign(det), double_to_sign(expected));
+    }
+
+    insphere_tets_batch(&points, &tets, &points[12..15], &mut result, &bump);
+    for (i, t) in tets.chunks(4).enumerate() {
+        let expected = insphere(
+            point(&points, t[0]),
+            point(&points, t[1]),
+            point(&points, t[2]),
+            point(&points, t[3]),
+            &points[12..15],
+            &bump,
+        );
+        assert_eq!(double_to_sign(result[i]), double_to_sign(expected));
+    }
+}
@@ -0,0 +1,291 @@
+use std::ops::{Add, Mul, Sub};
+
+const HALF_EPSILON: f64 = f64::EPSILON * 0.5;
+
+/// Forward error analysis of a homogeneous polynomial evaluated in floating
+/// point. `magnitude` bounds the computed value and `error` bounds its distance
+/// to the exact value, both in units of `max_var^degree`, where `max_var` is the
+/// largest magnitude among the explicit inputs.
+#[derive(Clone, Copy, Debug)]
+pub struct ErrorBound {
+    degree: i32,
+    magnitude: f64,
+    error: f64,
+}
+
+impl ErrorBound {
+    /// An exact input value.
+    pub const INPUT: ErrorBound = ErrorBound {
+        degree: 1,
+        magnitude: 1.0,
+        error: 0.0,
+    };
+
+    #[inline(always)]
+    pub fn degree(&self) -> i32 {
+        self.degree
+    }
+
+    /// The static filter threshold for inputs whose magnitude is at most `max_var`:
+    /// if the floating point result is larger than it in magnitude, its sign is exact.
+    #[inline(always)]
+    pub fn epsilon(&self, max_var: f64) -> f64 {
+        // account for the rounding of the power and of the product itself
+        let safety = 1.0 + 2.0 * (self.degree + 1) as f64 * f64::EPSILON;
+        self.error * max_var.powi(self.degree) * safety
+    }
+}
+
+#[inline(always)]
+fn add(a: &ErrorBound, b: &ErrorBound) -> ErrorBound {
+    assert_eq!(
+        a.degree, b.degree,
+        "the predicate polynomial must be homogeneous"
+    );
+    let magnitude = a.magnitude + b.magnitude;
+    ErrorBound {
+        degree: a.degree,
+        magnitude: (magnitude * (1.0 + HALF_EPSILON)).next_up(),
+        error: (a.error + b.error + HALF_EPSILON * magnitude).next_up(),
+    }
+}
+
+#[inline(always)]
+fn sub(a: &ErrorBound, b: &ErrorBound) -> ErrorBound {
+    add(a, b)
+}
+
+#[inline(always)]
+fn mul(a: &ErrorBound, b: &ErrorBound) -> ErrorBound {
+    let magnitude = a.magnitude * b.magnitude;
+    ErrorBound {
+        degree: a.degree + b.degree,
+        magnitude: (magnitude * (1.0 + HALF_EPSILON)).next_up(),
+        error: (a.magnitude * b.error
+            + b.magnitude * a.error
+            + a.error * b.error
+            + HALF_EPSILON * magnitude)
+            .next_up(),
+    }
+}
+
+macro_rules! impl_op {
+    (trait $op: ident, $func: ident) => {
+        impl $op for ErrorBound {
+            type Output = Self;
+            #[inline(always)]
+            fn $func(self, other: Self) -> Self {
+                $func(&self, &other)
+            }
+        }
+
+        impl $op<&ErrorBound> for ErrorBound {
+            type Output = Self;
+            #[inline(always)]
+            fn $func(self, other: &Self) -> Self {
+                $func(&self, other)
+            }
+        }
+
+        impl $op<ErrorBound> for &ErrorBound {
+            type Output = ErrorBound;
+            #[inline(always)]
+            fn $func(self, other: ErrorBound) -> ErrorBound {
+                $func(self, &other)
+            }
+        }
+
+        impl $op<&ErrorBound> for &ErrorBound {
+            type Output = ErrorBound;
+            #[inline(always)]
+            fn $func(self, other: &ErrorBound) -> ErrorBound {
+                $func(self, other)
+            }
+        }
+    };
+}
+
+impl_op!(trait Add, add);
+impl_op!(trait Sub, sub);
+impl_op!(trait Mul, mul);
+
+/// Generate a filtered predicate from a homogeneous polynomial.
+///
+/// The body is written once against a generic number type `T: GenericNum` and
+/// is evaluated in three stages: a floating point static filter whose error
+/// bound is derived by [`ErrorBound`], an [`IntervalNumber`] filter and an exact
+/// [`ExpansionNum`] evaluation. The generated function returns the sign of the
+/// polynomial.
+///
+/// Explicit inputs are `f64` values. Implicit points are listed after a `;` as
+/// `name: Type => (x, y, z, d)`, where the body sees their homogeneous
+/// coordinates. The static filter of a predicate on implicit points derives
+/// its bound from the bounds of their coordinates given by
+/// [`ImplicitPoint3D::error_bound`].
+///
+/// The body must be homogeneous in the degrees of the inputs, the explicit
+/// inputs being of degree 1, and may not use constants: write `a + a` rather
+/// than `2 * a`. A body which is not homogeneous panics at its first call,
+/// when its error bound is derived.
+///
+/// ```ignore
+/// gpf::predicate! {
+///     pub fn orient2d(ax, ay, bx, by, cx, cy) {
+///         let acx = ax - &cx;
+///         let bcy = by - &cy;
+///         let acy = ay - cy;
+///         let bcx = bx - cx;
+///         acx * bcy - acy * bcx
+///     }
+/// }
+/// ```
+///
+/// [`ImplicitPoint3D::error_bound`]: crate::predicates::ImplicitPoint3D::error_bound
+/// [`IntervalNumber`]: crate::predicates::IntervalNumber
+/// [`ExpansionNum`]: crate::predicates::ExpansionNum
+#[macro_export]
+macro_rules! predicate {
+    (
+        $(#[$meta: meta])*
+        $vis: vis fn $name: ident(
+            $($x: ident),* ;
+            $($p: ident: $P: ident => ($px: ident, $py: ident, $pz: ident, $pd: ident)),+ $(,)?
+        ) $body: block
+    ) => {
+        $(#[$meta])*
+        #[allow(clippy::too_many_arguments)]
+        $vis fn $name<$($P: $crate::predicates::ImplicitPoint3D + 'static,)+ A: std::alloc::Allocator + Copy>(
+            $($x: f64,)*
+            $($p: &$P,)+
+            bump: A,
+        ) -> $crate::predicates::Orientation {
+            #[allow(clippy::too_many_arguments)]
+            fn eval<T: $crate::predicates::GenericNum>(
+                $($x: T,)*
+                $($px: T, $py: T, $pz: T, $pd: T,)+
+            ) -> T $body
+
+            if let ($(Some($p),)+) = ($($p.static_filter(),)+) {
+                // the bound depends on the types of the implicit points
+                thread_local! {
+                    static BOUNDS: std::cell::RefCell<
+                        Vec<(std::any::TypeId, $crate::predicates::ErrorBound)>,
+                    > = const { std::cell::RefCell::new(Vec::new()) };
+                }
+                let key = std::any::TypeId::of::<($($P,)+)>();
+                let bound = BOUNDS.with_borrow_mut(|bounds| {
+                    if let Some(&(_, bound)) = bounds.iter().find(|(k, _)| *k == key) {
+                        return bound;
+                    }
+                    let bound = eval(
+                        $({
+                            let _ = $x;
+                            $crate::predicates::ErrorBound::INPUT
+                        },)*
+                        $(
+                            <$P>::error_bound().x,
+                            <$P>::error_bound().y,
+                            <$P>::error_bound().z,
+                            <$P>::error_bound().d,
+                        )+
+                    );
+                    bounds.push((key, bound));
+                    bound
+                });
+                let max_var = [$($x.abs(),)* $($p.1,)+].into_iter().fold(0.0, f64::max);
+                let epsilon = bound.epsilon(max_var);
+                let det = eval::<f64>($($x,)* $($p.0.x, $p.0.y, $p.0.z, $p.0.d,)+);
+                if det > epsilon {
+                    return $crate::predicates::Orientation::Positive;
+                } else if det < -epsilon {
+                    return $crate::predicates::Orientation::Negative;
+                }
+            }
+
+            if let ($(Some($p),)+) = ($($p.dynamic_filter(),)+) {
+                let det = eval::<$crate::predicates::IntervalNumber>(
+                    $($x.into(),)*
+                    $($p.x.clone(), $p.y.clone(), $p.z.clone(), $p.d.clone(),)+
+                );
+                if det.positive() {
+                    return $crate::predicates::Orientation::Positive;
+                } else if det.negative() {
+                    return $crate::predicates::Orientation::Negative;
+                }
+            }
+
+            if let ($(Some($p),)+) = ($($p.exact(bump),)+) {
+                let det = eval::<$crate::predicates::ExpansionNum<A>>(
+                    $([$x].to_vec_in(bump).into(),)*
+                    $($p.x, $p.y, $p.z, $p.d,)+
+                );
+                return $crate::predicates::double_to_sign(*det.last().unwrap());
+            }
+            $crate::predicates::Orientation::Undefined
+        }
+    };
+
+    (
+        $(#[$meta: meta])*
+        $vis: vis fn $name: ident($($x: ident),+ $(,)?) $body: block
+    ) => {
+        $(#[$meta])*
+        #[allow(clippy::too_many_arguments)]
+        $vis fn $name<A: std::alloc::Allocator + Copy>(
+            $($x: f64,)+
+            bump: A,
+        ) -> $crate::predicates::Orientation {
+            #[allow(clippy::too_many_arguments)]
+            fn eval<T: $crate::predicates::GenericNum>($($x: T),+) -> T $body
+
+            static BOUND: std::sync::OnceLock<$crate::predicates::ErrorBound> =
+                std::sync::OnceLock::new();
+            let bound = BOUND.get_or_init(|| {
+                eval($({
+                    let _ = $x;
+                    $crate::predicates::ErrorBound::INPUT
+                }),+)
+            });
+            let max_var = [$($x.abs()),+].into_iter().fold(0.0, f64::max);
+            let epsilon = bound.epsilon(max_var);
+            let det = eval::<f64>($($x),+);
+            if det > epsilon {
+                return $crate::predicates::Orientation::Positive;
+            } else if det < -epsilon {
+                return $crate::predicates::Orientation::Negative;
+            }
+
+            let det = eval::<$crate::predicates::IntervalNumber>($($x.into()),+);
+            if det.positive() {
+                return $crate::predicates::Orientation::Positive;
+            } else if det.negative() {
+                return $crate::predicates::Orientation::Negative;
+            }
+
+            let det = eval::<$crate::predicates::ExpansionNum<A>>(
+                $([$x].to_vec_in(bump).into()),+
+            );
+            $crate::predicates::double_to_sign(*det.last().unwrap())
+        }
+    };
+}
+
+#[test]
+fn test_error_bound() {
+    // (ax - cx) * (by - cy) - (ay - cy) * (bx - cx)
+    let i = ErrorBound::INPUT;
+    let d = i - i;
+    let det = d * d - d * d;
+    assert_eq!(det.degree(), 2);
+    assert!(det.epsilon(1.0) > 0.0 && det.epsilon(1.0) < 1e-14);
+}
+
+#[test]
+fn test_implicit_error_bound() {
+    use super::{ImplicitPoint3D, ImplicitPointLPI, ImplicitPointTPI};
+    // the coordinates are homogeneous, their ratios are of degree 1
+    let lpi = ImplicitPointLPI::error_bound();
+    assert_eq!((lpi.x.degree(), lpi.d.degree()), (4, 3));
+    let tpi = ImplicitPointTPI::error_bound();
+    assert_eq!((tpi.x.degree(), tpi.d.degree()), (7, 6));
+}
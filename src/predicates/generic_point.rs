@@ -2,10 +2,12 @@ use std::{
     alloc::Allocator,
     cell::{Ref, RefCell},
     ops::Deref,
+    sync::OnceLock,
 };
 
 use super::{
-    abs_max, dummy_abs_max, estimate, get_exponent, ExpansionNum, GenericNum, IntervalNumber,
+    abs_max, dummy_abs_max, estimate, get_exponent, ErrorBound, ExpansionNum, GenericNum,
+    IntervalNumber,
 };
 
 #[derive(Clone, PartialEq, Debug)]
@@ -37,7 +39,10 @@ fn normalize_lambda3d(x: &mut [f64], y: &mut [f64], z: &mut [f64], d: &mut [f64]
         .map(|arr| estimate(arr))
         .max_by(|x, y| x.abs().total_cmp(&y.abs()))
         .unwrap();
-    let e = get_exponent(max_val);
+    // Scale the largest component up to 2^150 rather than 1: the smallest
+    // components of intersection points are far below it, and the products
+    // taken by the predicates would underflow otherwise.
+    let e = get_exponent(max_val) - 150;
     if e != 0 {
         let s = 2.0f64.powi(-e);
         for arr in data {
@@ -73,6 +78,11 @@ pub trait ImplicitPoint3D {
     fn static_filter(&self) -> Option<&(Implicit3DCache<f64>, f64)>;
     fn dynamic_filter(&self) -> Option<&Implicit3DCache<IntervalNumber>>;
     fn exact<A: Allocator + Copy>(&self, allocator: A) -> Option<Implicit3DCache<ExpansionNum<A>>>;
+    /// The error bounds of the coordinates of the static filter, in units of
+    /// the powers of its maximum magnitude.
+    fn error_bound() -> &'static Implicit3DCache<ErrorBound>
+    where
+        Self: Sized;
     fn to_explicit(&self, data: &mut [f64]) {
         let exact = self.exact(std::alloc::Global).unwrap();
         let x = estimate(&exact.x);
@@ -196,6 +206,14 @@ where
 }
 
 impl ImplicitPoint3D for ImplicitPointLPI {
+    fn error_bound() -> &'static Implicit3DCache<ErrorBound> {
+        static BOUND: OnceLock<Implicit3DCache<ErrorBound>> = OnceLock::new();
+        BOUND.get_or_init(|| {
+            let i = ErrorBound::INPUT;
+            lpi_lambda::<false, _, _>(i, i, i, i, i, i, i, i, i, i, i, i, i, i, i, dummy_abs_max).0
+        })
+    }
+
     fn static_filter(&self) -> Option<&(Implicit3DCache<f64>, f64)> {
         if self.ss_filter.borrow().is_some() {
             let filter = self.ss_filter.borrow();
@@ -546,6 +564,44 @@ where
 }
 
 impl ImplicitPoint3D for ImplicitPointTPI {
+    fn error_bound() -> &'static Implicit3DCache<ErrorBound> {
+        static BOUND: OnceLock<Implicit3DCache<ErrorBound>> = OnceLock::new();
+        BOUND.get_or_init(|| {
+            let i = ErrorBound::INPUT;
+            tpi_lambda::<false, _, _>(
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                i,
+                dummy_abs_max,
+            )
+            .0
+        })
+    }
+
     fn static_filter(&self) -> Option<&(Implicit3DCache<f64>, f64)> {
         if self.ss_filter.borrow().is_some() {
             let filter = self.ss_filter.borrow();
@@ -1,14 +1,16 @@
 use core::f64;
-use std::ops::{Add, Mul, Sub};
+use std::ops::{Add, Div, Mul, Sub};
 use std::simd::num::SimdFloat;
-use std::simd::{f64x2, u64x2};
+use std::simd::{f64x2, u64x2, StdFloat};
+
+use super::Orientation;
 
 const SIGN_LOW_MASK: u64x2 = u64x2::from_array([0_u64, (1_u64 << 63)]);
 const SIGN_HIGH_MASK: u64x2 = u64x2::from_array([(1_u64 << 63), 0u64]);
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub struct IntervalNumber {
-    /// [high; low]
+    /// [high; -low]
     data: f64x2,
 }
 
@@ -16,9 +18,19 @@ impl IntervalNumber {
     #[inline(always)]
     pub fn new(low: f64, high: f64) -> Self {
         Self {
-            data: f64x2::from_array([high, low]),
+            data: f64x2::from_array([high, -low]),
         }
     }
+
+    #[inline(always)]
+    pub fn low(&self) -> f64 {
+        -self.data[1]
+    }
+
+    #[inline(always)]
+    pub fn high(&self) -> f64 {
+        self.data[0]
+    }
 }
 
 impl Default for IntervalNumber {
@@ -47,12 +59,26 @@ impl IntervalNumber {
 
     #[inline(always)]
     pub fn positive(&self) -> bool {
-        self.data[0] < 0.0
+        self.data[1] < 0.0
     }
 
     #[inline(always)]
     pub fn negative(&self) -> bool {
-        self.data[1] < 0.0
+        self.data[0] < 0.0
+    }
+
+    /// The sign of the number, or `Undefined` if the interval contains zero.
+    #[inline(always)]
+    pub fn sign(&self) -> Orientation {
+        if self.positive() {
+            Orientation::Positive
+        } else if self.negative() {
+            Orientation::Negative
+        } else if self.data[0] == 0.0 && self.data[1] == 0.0 {
+            Orientation::Zero
+        } else {
+            Orientation::Undefined
+        }
     }
 
     #[inline(always)]
@@ -63,6 +89,80 @@ impl IntervalNumber {
     pub fn from_f64x2(data: f64x2) -> Self {
         Self { data }
     }
+
+    pub fn abs(&self) -> Self {
+        if self.data[1] <= 0.0 {
+            self.clone()
+        } else if self.data[0] <= 0.0 {
+            Self {
+                data: self.data.reverse(),
+            }
+        } else {
+            Self {
+                data: f64x2::from_array([self.data.reduce_max(), 0.0]),
+            }
+        }
+    }
+
+    /// The square root of the non-negative part of the interval. The result is
+    /// `NaN` if the whole interval is negative.
+    pub fn sqrt(&self) -> Self {
+        if self.data[0] < 0.0 {
+            return Self::default();
+        }
+        let root = f64x2::from_array([self.data[0], (-self.data[1]).max(0.0)]).sqrt();
+        let mut data = f64x2::from_array([root[0], -root[1]]);
+        next_up(&mut data);
+        Self { data }
+    }
+
+    #[inline(always)]
+    pub fn min(&self, other: &Self) -> Self {
+        Self {
+            data: f64x2::from_array([
+                self.data[0].min(other.data[0]),
+                self.data[1].max(other.data[1]),
+            ]),
+        }
+    }
+
+    #[inline(always)]
+    pub fn max(&self, other: &Self) -> Self {
+        Self {
+            data: f64x2::from_array([
+                self.data[0].max(other.data[0]),
+                self.data[1].min(other.data[1]),
+            ]),
+        }
+    }
+
+    /// `Some(true)` if every value in `self` is less than every value in `other`,
+    /// `Some(false)` if none is, and `None` if it cannot be decided.
+    #[inline(always)]
+    pub fn lt(&self, other: &Self) -> Option<bool> {
+        if self.data[0] < -other.data[1] {
+            Some(true)
+        } else if -self.data[1] >= other.data[0] {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    pub fn le(&self, other: &Self) -> Option<bool> {
+        other.lt(self).map(|res| !res)
+    }
+
+    #[inline(always)]
+    pub fn gt(&self, other: &Self) -> Option<bool> {
+        other.lt(self)
+    }
+
+    #[inline(always)]
+    pub fn ge(&self, other: &Self) -> Option<bool> {
+        self.lt(other).map(|res| !res)
+    }
 }
 
 impl PartialEq for IntervalNumber {
@@ -141,6 +241,34 @@ fn mul(a: &IntervalNumber, b: &IntervalNumber) -> IntervalNumber {
     IntervalNumber { data }
 }
 
+fn div(a: &IntervalNumber, b: &IntervalNumber) -> IntervalNumber {
+    if b.negative() {
+        return div(
+            &IntervalNumber {
+                data: a.data.reverse(),
+            },
+            &IntervalNumber {
+                data: b.data.reverse(),
+            },
+        );
+    }
+    if !b.positive() {
+        return IntervalNumber {
+            data: f64x2::splat(f64::INFINITY),
+        };
+    }
+    // the numerator bound [high; -low] is divided by the smallest magnitude of
+    // the divisor if it is non-negative, otherwise by the largest one.
+    let (b_low, b_high) = (-b.data[1], b.data[0]);
+    let divisor = f64x2::from_array([
+        if a.data[0] >= 0.0 { b_low } else { b_high },
+        if a.data[1] >= 0.0 { b_low } else { b_high },
+    ]);
+    let mut data = a.data / divisor;
+    next_up(&mut data);
+    IntervalNumber { data }
+}
+
 macro_rules! impl_op {
     (trait $op: ident, $func: ident) => {
         impl $op for IntervalNumber {
@@ -174,16 +302,80 @@ macro_rules! impl_op {
                 $func(self, other)
             }
         }
+
+        impl $op<f64> for IntervalNumber {
+            type Output = Self;
+            #[inline(always)]
+            fn $func(self, other: f64) -> Self {
+                $func(&self, &other.into())
+            }
+        }
+
+        impl $op<f64> for &IntervalNumber {
+            type Output = IntervalNumber;
+            #[inline(always)]
+            fn $func(self, other: f64) -> IntervalNumber {
+                $func(self, &other.into())
+            }
+        }
+
+        impl $op<IntervalNumber> for f64 {
+            type Output = IntervalNumber;
+            #[inline(always)]
+            fn $func(self, other: IntervalNumber) -> IntervalNumber {
+                $func(&self.into(), &other)
+            }
+        }
+
+        impl $op<&IntervalNumber> for f64 {
+            type Output = IntervalNumber;
+            #[inline(always)]
+            fn $func(self, other: &IntervalNumber) -> IntervalNumber {
+                $func(&self.into(), other)
+            }
+        }
     };
 }
 
 impl_op!(trait Add, add);
 impl_op!(trait Sub, sub);
 impl_op!(trait Mul, mul);
+impl_op!(trait Div, div);
 
 #[test]
 fn interval_number_operation() {
     let a = IntervalNumber::from(2.0);
     let b = &a - &a - &a;
     assert!(b.data[0].round() == -2.0);
+    assert!(b.negative());
+    assert_eq!(b.sign(), Orientation::Negative);
+}
+
+#[test]
+fn interval_number_extended_operation() {
+    let a = IntervalNumber::new(1.0, 2.0);
+    let b = IntervalNumber::new(-3.0, -2.0);
+
+    let q = &a / &b;
+    assert!(q.low() <= -1.0 && q.high() >= -1.0 / 3.0);
+    assert!(q.low() > -1.0 - 1e-15 && q.high() < -1.0 / 3.0 + 1e-15);
+    let q = 1.0 / IntervalNumber::new(-1.0, 1.0);
+    assert!(q.low() == f64::NEG_INFINITY && q.high() == f64::INFINITY);
+
+    let r = IntervalNumber::from(2.0).sqrt();
+    assert!(r.low() * r.low() <= 2.0 && r.high() * r.high() >= 2.0);
+    assert!(IntervalNumber::new(-2.0, -1.0).sqrt().low().is_nan());
+
+    let c = IntervalNumber::new(-3.0, 1.0).abs();
+    assert!(c.low() == 0.0 && c.high() == 3.0);
+    assert_eq!(b.abs(), IntervalNumber::new(2.0, 3.0));
+
+    assert_eq!(a.min(&b), b);
+    assert_eq!(a.max(&b), a);
+    assert_eq!(b.lt(&a), Some(true));
+    assert_eq!(a.le(&b), Some(false));
+    assert_eq!(a.lt(&IntervalNumber::from(1.5)), None);
+
+    assert_eq!((a.clone() - 1.0).sign(), Orientation::Undefined);
+    assert_eq!((2.0 * &a).sign(), Orientation::Positive);
 }
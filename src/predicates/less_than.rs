@@ -1,8 +1,10 @@
 use std::alloc::Allocator;
 
 use super::{
-    double_to_sign, ExplicitPoint3D, GenericNum, ImplicitPoint3D, ImplicitPointLPI,
-    ImplicitPointTPI, Orientation, Point3D,
+    double_to_sign,
+    stats::{self, Stage},
+    ExplicitPoint3D, GenericNum, ImplicitPoint3D, ImplicitPointLPI, ImplicitPointTPI, Orientation,
+    Point3D,
 };
 
 pub fn less_than_on_x<A: Allocator + Copy>(pa: &Point3D, pb: &Point3D, bump: A) -> Orientation {
@@ -209,6 +211,11 @@ fn less_than_le<const AXIS: usize, A: Allocator + Copy>(
             epsilon *= epsilon;
             epsilon * 1.932297637868842e-14
         },
+        [
+            "less_than_on_x_le",
+            "less_than_on_y_le",
+            "less_than_on_z_le",
+        ][AXIS],
         bump,
     )
 }
@@ -231,6 +238,11 @@ fn less_than_te<const AXIS: usize, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 3.980270973924514e-13
         },
+        [
+            "less_than_on_x_te",
+            "less_than_on_y_te",
+            "less_than_on_z_te",
+        ][AXIS],
         bump,
     )
 }
@@ -253,6 +265,11 @@ fn less_than_ll<const AXIS: usize, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 2.922887626377607e-13
         },
+        [
+            "less_than_on_x_ll",
+            "less_than_on_y_ll",
+            "less_than_on_z_ll",
+        ][AXIS],
         bump,
     )
 }
@@ -275,6 +292,11 @@ fn less_than_lt<const AXIS: usize, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 4.321380059346694e-12
         },
+        [
+            "less_than_on_x_lt",
+            "less_than_on_y_lt",
+            "less_than_on_z_lt",
+        ][AXIS],
         bump,
     )
 }
@@ -300,6 +322,11 @@ fn less_than_tt<const AXIS: usize, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 5.504141586953918e-11
         },
+        [
+            "less_than_on_x_tt",
+            "less_than_on_y_tt",
+            "less_than_on_z_tt",
+        ][AXIS],
         bump,
     )
 }
@@ -319,6 +346,7 @@ fn less_than_ie<
     pa: &IP,
     pb: &ExplicitPoint3D,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some(pa_static) = pa.static_filter() {
@@ -340,10 +368,9 @@ fn less_than_ie<
         };
         max_var = max_var.max(pa_static.1);
         let epsilon = static_filter_func(max_var);
-        if det > epsilon {
-            return Orientation::Positive;
-        } else if det < -epsilon {
-            return Orientation::Negative;
+        if det > epsilon || det < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(det);
         }
     }
 
@@ -357,11 +384,8 @@ fn less_than_ie<
         };
 
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -385,6 +409,7 @@ fn less_than_ie<
                 [pb[2].into()].to_vec_in(bump).into(),
             )
         };
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
     Orientation::Undefined
@@ -407,6 +432,7 @@ fn less_than_ii<
     pa: &IP1,
     pb: &IP2,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some(pa_static) = pa.static_filter()
@@ -436,10 +462,9 @@ fn less_than_ii<
         };
         let max_var = pa_static.1.max(pb_static.1);
         let epsilon = static_filter_func(max_var);
-        if det > epsilon {
-            return Orientation::Positive;
-        } else if det < -epsilon {
-            return Orientation::Negative;
+        if det > epsilon || det < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(det);
         }
     }
 
@@ -455,11 +480,8 @@ fn less_than_ii<
         };
 
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -473,6 +495,7 @@ fn less_than_ii<
         } else {
             less_than_ii_impl(&pa_exact.z, &pa_exact.d, &pb_exact.z, &pb_exact.d)
         };
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
     Orientation::Undefined
@@ -1,22 +1,30 @@
+mod batch;
 mod expansion_number;
+mod generator;
 mod generic_point;
 mod interval_number;
 mod less_than;
 pub mod orient2d;
 pub mod orient3d;
 mod predicates;
+mod sos;
+mod stats;
 
 use std::{
     alloc::Allocator,
     ops::{Add, Mul, Sub},
 };
 
+pub use batch::*;
 pub use expansion_number::*;
+pub use generator::*;
 pub use generic_point::*;
 pub use interval_number::*;
 pub use less_than::*;
 pub use orient2d::*;
 pub use predicates::*;
+pub use sos::*;
+pub use stats::*;
 
 #[derive(PartialEq, Eq, Clone, Copy, Hash, Debug)]
 pub enum Orientation {
@@ -26,7 +34,7 @@ pub enum Orientation {
     Undefined,
 }
 
-trait GenericNum = Sized + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+pub trait GenericNum = Sized + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
 where
     for<'a> Self:
         Add<&'a Self, Output = Self> + Sub<&'a Self, Output = Self> + Mul<&'a Self, Output = Self>,
@@ -1,8 +1,10 @@
 use std::alloc::Allocator;
 
 use super::{
-    abs_max, double_to_sign, dummy_abs_max, predicates, sign_reverse, ExplicitPoint3D, GenericNum,
-    ImplicitPoint3D, ImplicitPointLPI, ImplicitPointTPI, Orientation, Point3D,
+    abs_max, double_to_sign, dummy_abs_max, predicates, sign_reverse,
+    stats::{self, Stage},
+    ExplicitPoint3D, GenericNum, ImplicitPoint3D, ImplicitPointLPI, ImplicitPointTPI, Orientation,
+    Point3D,
 };
 
 #[inline(always)]
@@ -562,6 +564,7 @@ fn orient2d_iee<
     p2: &ExplicitPoint3D,
     p3: &ExplicitPoint3D,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some(p1_static) = p1.static_filter() {
@@ -601,10 +604,9 @@ fn orient2d_iee<
         };
         let max_var = ret.1.unwrap().max(p1_static.1);
         let epsilon = static_filter_func(max_var);
-        if ret.0 > epsilon {
-            return Orientation::Positive;
-        } else if ret.0 < -epsilon {
-            return Orientation::Negative;
+        if ret.0 > epsilon || ret.0 < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(ret.0);
         }
     }
 
@@ -644,11 +646,8 @@ fn orient2d_iee<
             )
         };
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else if det.negative() {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -687,6 +686,7 @@ fn orient2d_iee<
                 dummy_abs_max,
             )
         };
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
 
@@ -710,6 +710,7 @@ fn orient2d_lee<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 4.752773695437811e-14
         },
+        ["orient2d_lee_yz", "orient2d_lee_zx", "orient2d_lee_xy"][AXIS as usize],
         bump,
     )
 }
@@ -731,6 +732,7 @@ fn orient2d_tee<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= epsilon;
             epsilon * 9.061883188277186e-13
         },
+        ["orient2d_tee_yz", "orient2d_tee_zx", "orient2d_tee_xy"][AXIS as usize],
         bump,
     )
 }
@@ -778,6 +780,7 @@ fn orient2d_iie<
     p2: &IP2,
     p3: &ExplicitPoint3D,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some((p1_static, p2_static)) = p1.static_filter().zip(p2.static_filter()) {
@@ -820,10 +823,9 @@ fn orient2d_iie<
         };
         let max_var = ret.1.unwrap().max(p1_static.1).max(p2_static.1);
         let epsilon = static_filter_func(max_var);
-        if ret.0 > epsilon {
-            return Orientation::Positive;
-        } else if ret.0 < -epsilon {
-            return Orientation::Negative;
+        if ret.0 > epsilon || ret.0 < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(ret.0);
         }
     }
 
@@ -866,11 +868,8 @@ fn orient2d_iie<
             )
         };
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else if det.negative() {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -912,6 +911,7 @@ fn orient2d_iie<
                 dummy_abs_max,
             )
         };
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
     Orientation::Undefined
@@ -938,6 +938,7 @@ fn orient2d_lle<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 1.699690735379461e-11
         },
+        ["orient2d_lle_yz", "orient2d_lle_zx", "orient2d_lle_xy"][AXIS as usize],
         bump,
     )
 }
@@ -966,6 +967,7 @@ fn orient2d_lte<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 2.184958117212875e-10
         },
+        ["orient2d_lte_yz", "orient2d_lte_zx", "orient2d_lte_xy"][AXIS as usize],
         bump,
     )
 }
@@ -993,6 +995,7 @@ fn orient2d_tte<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 3.307187945722514e-08
         },
+        ["orient2d_tte_yz", "orient2d_tte_zx", "orient2d_tte_xy"][AXIS as usize],
         bump,
     )
 }
@@ -1038,6 +1041,7 @@ fn orient2d_iii<
     p2: &IP2,
     p3: &IP3,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some(((p1_static, p2_static), p3_static)) = p1
@@ -1084,10 +1088,9 @@ fn orient2d_iii<
         };
         let max_var = p1_static.1.max(p2_static.1).max(p3_static.1);
         let epsilon = static_filter_func(max_var);
-        if det > epsilon {
-            return Orientation::Positive;
-        } else if det < -epsilon {
-            return Orientation::Negative;
+        if det > epsilon || det < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(det);
         }
     }
 
@@ -1134,11 +1137,8 @@ fn orient2d_iii<
             )
         };
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else if det.negative() {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -1182,6 +1182,7 @@ fn orient2d_iii<
                 &p3_exact.d,
             )
         };
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
     Orientation::Undefined
@@ -1211,6 +1212,7 @@ fn orient2d_lll<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 1.75634284893534e-10
         },
+        ["orient2d_lll_yz", "orient2d_lll_zx", "orient2d_lll_xy"][AXIS as usize],
         allocator,
     )
 }
@@ -1235,6 +1237,7 @@ fn orient2d_llt<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 2.144556754402072e-09
         },
+        ["orient2d_llt_yz", "orient2d_llt_zx", "orient2d_llt_xy"][AXIS as usize],
         allocator,
     )
 }
@@ -1262,6 +1265,7 @@ fn orient2d_ltt<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 2.535681042914479e-08
         },
+        ["orient2d_ltt_yz", "orient2d_ltt_zx", "orient2d_ltt_xy"][AXIS as usize],
         allocator,
     )
 }
@@ -1295,6 +1299,7 @@ fn orient2d_ttt<const AXIS: u32, A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 3.103174776697445e-06
         },
+        ["orient2d_ttt_yz", "orient2d_ttt_zx", "orient2d_ttt_xy"][AXIS as usize],
         allocator,
     )
 }
@@ -1,8 +1,10 @@
 use std::alloc::Allocator;
 
 use super::{
-    abs_max, double_to_sign, dummy_abs_max, predicates, ExplicitPoint3D, GenericNum,
-    ImplicitPoint3D, ImplicitPointLPI, ImplicitPointTPI, Orientation, Point3D,
+    abs_max, double_to_sign, dummy_abs_max, predicates,
+    stats::{self, Stage},
+    ExplicitPoint3D, GenericNum, ImplicitPoint3D, ImplicitPointLPI, ImplicitPointTPI, Orientation,
+    Point3D,
 };
 
 /// Computes the orientation of the 3D points `pa`, `pb`, `pc` and `pd`.
@@ -289,6 +291,7 @@ fn orient3d_leee<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 1.861039534284405e-13
         },
+        "orient3d_leee",
         bump,
     )
 }
@@ -314,6 +317,7 @@ fn orient3d_teee<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 3.070283610684406e-12
         },
+        "orient3d_teee",
         bump,
     )
 }
@@ -340,6 +344,7 @@ fn orient3d_llee<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 5.12855469897434e-12
         },
+        "orient3d_llee",
         bump,
     )
 }
@@ -367,6 +372,7 @@ fn orient3d_ltee<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 7.437036403379365e-11
         },
+        "orient3d_ltee",
         bump,
     )
 }
@@ -397,6 +403,7 @@ fn orient3d_ttee<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 1.036198238324465e-09
         },
+        "orient3d_ttee",
         bump,
     )
 }
@@ -426,6 +433,7 @@ fn orient3d_llle<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 1.270161397934349e-10
         },
+        "orient3d_llle",
         bump,
     )
 }
@@ -456,6 +464,7 @@ fn orient3d_llte<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 1.7060943907632e-09
         },
+        "orient3d_llte",
         bump,
     )
 }
@@ -482,6 +491,7 @@ fn orient3d_ltte<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 2.211968919141342e-08
         },
+        "orient3d_ltte",
         bump,
     )
 }
@@ -511,6 +521,7 @@ fn orient3d_ttte<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 2.808754828720361e-07
         },
+        "orient3d_ttte",
         bump,
     )
 }
@@ -542,6 +553,7 @@ fn orient3d_llll<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 1.164303613521164e-07
         },
+        "orient3d_llll",
         bump,
     )
 }
@@ -580,6 +592,7 @@ fn orient3d_lllt<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 0.0001675978376241023
         },
+        "orient3d_lllt",
         bump,
     )
 }
@@ -606,6 +619,7 @@ fn orient3d_lltt<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 0.001770733197190587
         },
+        "orient3d_lltt",
         bump,
     )
 }
@@ -636,6 +650,7 @@ fn orient3d_lttt<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 0.01883943108077826
         },
+        "orient3d_lttt",
         bump,
     )
 }
@@ -669,6 +684,7 @@ fn orient3d_tttt<A: Allocator + Copy>(
             epsilon *= max_var;
             epsilon * 0.1952243033447331
         },
+        "orient3d_tttt",
         bump,
     )
 }
@@ -730,6 +746,7 @@ fn orient3d_ieee<IP: ImplicitPoint3D, F: FnOnce(f64) -> f64, A: Allocator + Copy
     pc: &ExplicitPoint3D,
     pd: &ExplicitPoint3D,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some(pa_static) = pa.static_filter() {
@@ -751,10 +768,9 @@ fn orient3d_ieee<IP: ImplicitPoint3D, F: FnOnce(f64) -> f64, A: Allocator + Copy
         );
         let max_var = max_var.unwrap().max(pa_static.1);
         let epsilon = static_filter_func(max_var);
-        if det > epsilon {
-            return Orientation::Positive;
-        } else if det < -epsilon {
-            return Orientation::Negative;
+        if det > epsilon || det < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(det);
         }
     }
 
@@ -776,11 +792,8 @@ fn orient3d_ieee<IP: ImplicitPoint3D, F: FnOnce(f64) -> f64, A: Allocator + Copy
             dummy_abs_max,
         );
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else if det.negative() {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -801,6 +814,7 @@ fn orient3d_ieee<IP: ImplicitPoint3D, F: FnOnce(f64) -> f64, A: Allocator + Copy
             [pd.data[2]].to_vec_in(bump).into(),
             dummy_abs_max,
         );
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
 
@@ -873,6 +887,7 @@ fn orient3d_iiee<
     pc: &ExplicitPoint3D,
     pd: &ExplicitPoint3D,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some((pa_static, pb_static)) = pa.static_filter().zip(pb.static_filter()) {
@@ -895,10 +910,9 @@ fn orient3d_iiee<
         );
         let max_var = max_var.unwrap().max(pa_static.1).max(pb_static.1);
         let epsilon = static_filter_func(max_var);
-        if det > epsilon {
-            return Orientation::Positive;
-        } else if det < -epsilon {
-            return Orientation::Negative;
+        if det > epsilon || det < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(det);
         }
     }
 
@@ -921,11 +935,8 @@ fn orient3d_iiee<
             dummy_abs_max,
         );
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else if det.negative() {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -947,6 +958,7 @@ fn orient3d_iiee<
             [pd.data[2]].to_vec_in(bump).into(),
             dummy_abs_max,
         );
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
 
@@ -1023,6 +1035,7 @@ fn orient3d_iiie<
     pc: &IP3,
     pd: &ExplicitPoint3D,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some(((pa_static, pb_static), pc_static)) = pa
@@ -1054,10 +1067,9 @@ fn orient3d_iiie<
             .max(pb_static.1)
             .max(pc_static.1);
         let epsilon = static_filter_func(max_var);
-        if det > epsilon {
-            return Orientation::Positive;
-        } else if det < -epsilon {
-            return Orientation::Negative;
+        if det > epsilon || det < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(det);
         }
     }
 
@@ -1085,11 +1097,8 @@ fn orient3d_iiie<
             dummy_abs_max,
         );
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else if det.negative() {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -1114,6 +1123,7 @@ fn orient3d_iiie<
             [pd.data[2]].to_vec_in(bump).into(),
             dummy_abs_max,
         );
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
 
@@ -1194,6 +1204,7 @@ fn orient3d_iiii<
     pc: &IP3,
     pd: &IP4,
     static_filter_func: F,
+    name: &'static str,
     bump: A,
 ) -> Orientation {
     if let Some((((pa_static, pb_static), pc_static), pd_static)) = pa
@@ -1226,10 +1237,9 @@ fn orient3d_iiii<
             .max(pc_static.1)
             .max(pd_static.1);
         let epsilon = static_filter_func(max_var);
-        if det > epsilon {
-            return Orientation::Positive;
-        } else if det < -epsilon {
-            return Orientation::Negative;
+        if det > epsilon || det < -epsilon {
+            stats::record(name, Stage::Static);
+            return double_to_sign(det);
         }
     }
 
@@ -1258,11 +1268,8 @@ fn orient3d_iiii<
             &pd_dynamic.d,
         );
         if det.not_zero() {
-            if det.positive() {
-                return Orientation::Positive;
-            } else if det.negative() {
-                return Orientation::Negative;
-            }
+            stats::record(name, Stage::Interval);
+            return det.sign();
         }
     }
 
@@ -1290,6 +1297,7 @@ fn orient3d_iiii<
             &pd_exact.z,
             &pd_exact.d,
         );
+        stats::record(name, Stage::Exact(det.len()));
         return double_to_sign(*det.last().unwrap());
     }
 
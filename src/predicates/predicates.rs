@@ -1,5 +1,7 @@
 use std::alloc::Allocator;
 
+use super::stats::{self, Stage};
+
 struct Bound {
     splitter: f64,
     result_err_bound: f64,
@@ -77,6 +79,9 @@ const fn exact_init() -> Bound {
 
 const B: Bound = exact_init();
 
+/// Relative error bound of the static filter in `orient3d`.
+pub(super) const O3D_ERR_BOUND_A: f64 = B.o3d_err_bounda;
+
 #[inline(always)]
 pub const fn fast_two_sum(a: f64, b: f64) -> (f64, f64) {
     let x = a + b;
@@ -459,6 +464,7 @@ fn orient2d_adapt<A: Allocator + Copy>(
     let mut det = estimate(&b);
     let errbound = B.ccw_err_boundb * detsum;
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("orient2d", Stage::Interval);
         return det;
     }
 
@@ -468,12 +474,14 @@ fn orient2d_adapt<A: Allocator + Copy>(
     let bcytail = two_diff_tail(pb[1], pc[1], bcy);
 
     if (acxtail == 0.0) && (acytail == 0.0) && (bcxtail == 0.0) && (bcytail == 0.0) {
+        stats::record("orient2d", Stage::Interval);
         return det;
     }
 
     let errbound = B.ccw_err_boundc * detsum + B.result_err_bound * det.abs();
     det += (acx * bcytail + bcy * acxtail) - (acy * bcxtail + bcx * acytail);
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("orient2d", Stage::Interval);
         return det;
     }
 
@@ -493,6 +501,7 @@ fn orient2d_adapt<A: Allocator + Copy>(
     (u[3], u[2], u[1], u[0]) = two_two_diff(s1, s0, t1, t0);
     let d = fast_expansion_sum_zeroelim(&c2, &u, allocator);
 
+    stats::record("orient2d", Stage::Exact(d.len()));
     *d.last().unwrap()
 }
 
@@ -504,22 +513,26 @@ pub fn orient2d<A: Allocator + Copy>(pa: &[f64], pb: &[f64], pc: &[f64], allocat
 
     if detleft > 0.0 {
         if detright <= 0.0 {
+            stats::record("orient2d", Stage::Static);
             return det;
         } else {
             detsum = detleft + detright;
         }
     } else if detleft < 0.0 {
         if detright >= 0.0 {
+            stats::record("orient2d", Stage::Static);
             return det;
         } else {
             detsum = -detleft - detright;
         }
     } else {
+        stats::record("orient2d", Stage::Static);
         return det;
     }
 
     let errbound = B.ccw_err_bounda * detsum;
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("orient2d", Stage::Static);
         return det;
     }
 
@@ -568,6 +581,7 @@ fn orient3d_adapt<A: Allocator + Copy>(
     let mut det = estimate(&fin);
     let errbound = B.o3d_err_boundb * permanent;
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("orient3d", Stage::Interval);
         return det;
     }
 
@@ -591,6 +605,7 @@ fn orient3d_adapt<A: Allocator + Copy>(
         && (bdztail == 0.0)
         && (cdztail == 0.0)
     {
+        stats::record("orient3d", Stage::Interval);
         return det;
     }
 
@@ -602,6 +617,7 @@ fn orient3d_adapt<A: Allocator + Copy>(
         + (cdz * ((adx * bdytail + bdy * adxtail) - (ady * bdxtail + bdx * adytail))
             + cdztail * (adx * bdy - ady * bdx));
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("orient3d", Stage::Interval);
         return det;
     }
 
@@ -746,6 +762,7 @@ fn orient3d_adapt<A: Allocator + Copy>(
         let v = scale_expansion_zeroelim(&xyt[2], cdztail, allocator);
         fin = fast_expansion_sum_zeroelim(&fin, &v, allocator);
     }
+    stats::record("orient3d", Stage::Exact(fin.len()));
     *fin.last().unwrap()
 }
 
@@ -782,6 +799,7 @@ pub fn orient3d<A: Allocator + Copy>(
         + (adxbdy.abs() + bdxady.abs()) * cdz.abs();
     let err_bound = B.o3d_err_bounda * permanent;
     if (det > err_bound) || (-det > err_bound) {
+        stats::record("orient3d", Stage::Static);
         return det;
     }
 
@@ -862,6 +880,7 @@ fn incircle_adapt<A: Allocator + Copy>(
     let mut det = estimate(&fin);
     let errbound = B.icc_err_boundb * permanent;
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("incircle", Stage::Interval);
         return det;
     }
 
@@ -878,6 +897,7 @@ fn incircle_adapt<A: Allocator + Copy>(
         && (bdytail == 0.0)
         && (cdytail == 0.0)
     {
+        stats::record("incircle", Stage::Interval);
         return det;
     }
 
@@ -892,6 +912,7 @@ fn incircle_adapt<A: Allocator + Copy>(
             * ((adx * bdytail + bdy * adxtail) - (ady * bdxtail + bdx * adytail))
             + 2.0 * (cdx * cdxtail + cdy * cdytail) * (adx * bdy - ady * bdx));
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("incircle", Stage::Interval);
         return det;
     }
 
@@ -1069,6 +1090,7 @@ fn incircle_adapt<A: Allocator + Copy>(
         cdxtail, cdytail, adxtail, adytail, bdxtail, bdytail, cdx, cdy, adx, ady, bdx, bdy, &cxtab,
         &cytab, &aa, &bb, &mut fin, allocator,
     );
+    stats::record("incircle", Stage::Exact(fin.len()));
     *fin.last().unwrap()
 }
 
@@ -1105,6 +1127,7 @@ pub fn incircle<A: Allocator + Copy>(
         + (adxbdy.abs() + bdxady.abs()) * clift;
     let errbound = B.icc_err_bounda * permanent;
     if (det > errbound) || (-det > errbound) {
+        stats::record("incircle", Stage::Static);
         return det;
     }
 
@@ -1304,6 +1327,7 @@ fn insphere_exact<A: Allocator + Copy>(
     let cdedet = fast_expansion_sum_zeroelim(&cddet, &edet, allocator);
     let deter = fast_expansion_sum_zeroelim(&abdet, &cdedet, allocator);
 
+    stats::record("insphere", Stage::Exact(deter.len()));
     *deter.last().unwrap()
 }
 
@@ -1422,6 +1446,7 @@ fn insphere_adapt<A: Allocator + Copy>(
     let mut det = estimate(&fin1);
     let errbound = B.isp_err_boundb * permanent;
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("insphere", Stage::Interval);
         return det;
     }
 
@@ -1450,6 +1475,7 @@ fn insphere_adapt<A: Allocator + Copy>(
         && (deytail == 0.0)
         && (deztail == 0.0)
     {
+        stats::record("insphere", Stage::Interval);
         return det;
     }
 
@@ -1482,6 +1508,7 @@ fn insphere_adapt<A: Allocator + Copy>(
                     + (cex * cextail + cey * ceytail + cez * ceztail)
                         * (dez * ab[3] + aez * bd[3] + bez * da[3])));
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("insphere", Stage::Interval);
         return det;
     }
 
@@ -1560,6 +1587,7 @@ pub fn insphere<A: Allocator + Copy>(
     epsilon *= max_var;
     epsilon *= 1.145750161413163e-13;
     if (det > epsilon) || (-det > epsilon) {
+        stats::record("insphere", Stage::Static);
         return det;
     }
 
@@ -1761,6 +1789,7 @@ fn orient4d_exact<A: Allocator + Copy>(
     let cdedet = fast_expansion_sum_zeroelim(&cddet, &edet, allocator);
     let deter = fast_expansion_sum_zeroelim(&abdet, &cdedet, allocator);
 
+    stats::record("orient4d", Stage::Exact(deter.len()));
     *deter.last().unwrap()
 }
 
@@ -1860,6 +1889,7 @@ fn orient4d_adapt<A: Allocator + Copy>(
     let mut det = estimate(&fin1);
     let errbound = B.isp_err_boundb * permanent;
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("orient4d", Stage::Interval);
         return det;
     }
 
@@ -1896,6 +1926,7 @@ fn orient4d_adapt<A: Allocator + Copy>(
         && (ceheighttail == 0.0)
         && (deheighttail == 0.0)
     {
+        stats::record("orient4d", Stage::Interval);
         return det;
     }
 
@@ -1923,6 +1954,7 @@ fn orient4d_adapt<A: Allocator + Copy>(
             - (aeheighttail * (bez * cd[3] - cez * bd[3] + dez * bc[3])
                 + ceheighttail * (dez * ab[3] + aez * bd[3] + bez * da[3])));
     if (det >= errbound) || (-det >= errbound) {
+        stats::record("orient4d", Stage::Interval);
         return det;
     }
 
@@ -2022,6 +2054,7 @@ pub fn orient4d<A: Allocator + Copy>(
             * deheight.abs();
     let errbound = B.isp_err_bounda * permanent;
     if (det > errbound) || (-det > errbound) {
+        stats::record("orient4d", Stage::Static);
         return det;
     }
 
@@ -0,0 +1,311 @@
+use std::alloc::Allocator;
+
+use super::{double_to_sign, predicates, sign_reverse, ExpansionNum, Orientation};
+
+/// Exact determinant of the submatrix of `matrix` made of `rows` and `cols`.
+fn minor<A: Allocator + Copy>(
+    matrix: &[Vec<ExpansionNum<A>>],
+    rows: &[usize],
+    cols: &[usize],
+) -> ExpansionNum<A> {
+    let row = &matrix[rows[0]];
+    if rows.len() == 1 {
+        return row[cols[0]].clone();
+    }
+    // Laplace expansion along the first row
+    let n_sub = cols.len() - 1;
+    let mut sub_cols = [0; 4];
+    sub_cols[..n_sub].copy_from_slice(&cols[1..]);
+    let mut det = &row[cols[0]] * minor(matrix, &rows[1..], &sub_cols[..n_sub]);
+    for k in 1..cols.len() {
+        sub_cols[k - 1] = cols[k - 1];
+        let term = &row[cols[k]] * minor(matrix, &rows[1..], &sub_cols[..n_sub]);
+        det = if k & 1 == 0 { det + term } else { det - term };
+    }
+    det
+}
+
+/// Collect every set of perturbed entries with distinct rows and columns,
+/// keyed by the exponent of the corresponding monomial.
+fn perturbation_terms<F: Fn(usize, usize) -> Option<u32>>(
+    row: usize,
+    n: usize,
+    used_cols: usize,
+    key: u64,
+    entries: &mut Vec<(usize, usize)>,
+    bit: &F,
+    terms: &mut Vec<(u64, Vec<(usize, usize)>)>,
+) {
+    if row == n {
+        if !entries.is_empty() {
+            terms.push((key, entries.clone()));
+        }
+        return;
+    }
+    perturbation_terms(row + 1, n, used_cols, key, entries, bit, terms);
+    for col in 0..n {
+        if used_cols & (1 << col) != 0 {
+            continue;
+        }
+        if let Some(b) = bit(row, col) {
+            entries.push((row, col));
+            perturbation_terms(
+                row + 1,
+                n,
+                used_cols | (1 << col),
+                key | (1 << b),
+                entries,
+                bit,
+                terms,
+            );
+            entries.pop();
+        }
+    }
+}
+
+/// The sign of `det(matrix + E)` for infinitesimal perturbations `E`, where
+/// entry `(r, c)` is perturbed by `eps^(2^bit(r, c))` if `bit` returns a value.
+/// The determinant of `matrix` itself is assumed to be zero.
+fn perturbed_sign<A: Allocator + Copy, F: Fn(usize, usize) -> Option<u32>>(
+    matrix: &[Vec<ExpansionNum<A>>],
+    bit: F,
+) -> Orientation {
+    let n = matrix.len();
+    let mut terms = Vec::new();
+    perturbation_terms(0, n, 0, 0, &mut Vec::new(), &bit, &mut terms);
+    terms.sort_unstable_by_key(|(key, _)| *key);
+
+    for (_, entries) in terms {
+        // the coefficient of the monomial is the complementary minor, signed by
+        // the positions of the entries and the parity of their column order
+        let mut odd = entries.iter().map(|(r, c)| r + c).sum::<usize>() & 1 == 1;
+        for (i, (_, ci)) in entries.iter().enumerate() {
+            for (_, cj) in &entries[(i + 1)..] {
+                odd ^= ci > cj;
+            }
+        }
+        let rows = Vec::from_iter((0..n).filter(|r| entries.iter().all(|e| e.0 != *r)));
+        let cols = Vec::from_iter((0..n).filter(|c| entries.iter().all(|e| e.1 != *c)));
+        let coef = minor(matrix, &rows, &cols);
+        let sign = double_to_sign(*coef.last().unwrap());
+        if sign != Orientation::Zero {
+            return if odd { sign_reverse(sign) } else { sign };
+        }
+    }
+    unreachable!("the constant column always leaves a non-zero minor")
+}
+
+/// Sort the point indices and return the permutation with its parity.
+#[inline(always)]
+fn sort_by_index<const N: usize>(indices: [usize; N]) -> ([usize; N], bool) {
+    let mut order: [usize; N] = std::array::from_fn(|i| i);
+    let mut odd = false;
+    for i in 1..N {
+        let mut j = i;
+        while j > 0 && indices[order[j - 1]] > indices[order[j]] {
+            order.swap(j - 1, j);
+            odd = !odd;
+            j -= 1;
+        }
+    }
+    (order, odd)
+}
+
+#[inline(always)]
+fn exact<A: Allocator + Copy>(val: f64, allocator: A) -> ExpansionNum<A> {
+    [val].to_vec_in(allocator).into()
+}
+
+/// Orientation of `d + 1` points in `d` dimensions perturbed by Simulation of
+/// Simplicity: coordinate `j` of the point with the `i`-th smallest index moves
+/// by `eps^(2^(i * d - j))`.
+fn orient_sos<const N: usize, A: Allocator + Copy>(
+    points: [&[f64]; N],
+    indices: [usize; N],
+    allocator: A,
+) -> Orientation {
+    let d = N - 1;
+    let (order, odd) = sort_by_index(indices);
+    let matrix = Vec::from_iter(order.iter().map(|&i| {
+        let mut row = Vec::from_iter(points[i][..d].iter().map(|&v| exact(v, allocator)));
+        row.push(exact(1.0, allocator));
+        row
+    }));
+    let sign = perturbed_sign(&matrix, |r, c| {
+        if c < d {
+            Some((r * d + d - 1 - c) as u32)
+        } else {
+            None
+        }
+    });
+    if odd {
+        sign_reverse(sign)
+    } else {
+        sign
+    }
+}
+
+/// `orient2d` with symbolic perturbation of the points according to their
+/// indices. The result is never `Orientation::Zero` for distinct indices.
+pub fn orient2d_sos<A: Allocator + Copy>(
+    points: [&[f64]; 3],
+    indices: [usize; 3],
+    allocator: A,
+) -> Orientation {
+    let [pa, pb, pc] = points;
+    let sign = double_to_sign(predicates::orient2d(pa, pb, pc, allocator));
+    if sign != Orientation::Zero {
+        return sign;
+    }
+    orient_sos(points, indices, allocator)
+}
+
+/// `orient3d` with symbolic perturbation of the points according to their
+/// indices. The result is never `Orientation::Zero` for distinct indices.
+pub fn orient3d_sos<A: Allocator + Copy>(
+    points: [&[f64]; 4],
+    indices: [usize; 4],
+    allocator: A,
+) -> Orientation {
+    let [pa, pb, pc, pd] = points;
+    let sign = double_to_sign(predicates::orient3d(pa, pb, pc, pd, allocator));
+    if sign != Orientation::Zero {
+        return sign;
+    }
+    orient_sos(points, indices, allocator)
+}
+
+/// `insphere` with symbolic perturbation of the points according to their
+/// indices. The lifted coordinate of the point with the `i`-th smallest index
+/// is lowered by `eps^(2^i)`, as in TetGen, and this dominates a perturbation
+/// of the coordinates consistent with `orient3d_sos`. The result is never
+/// `Orientation::Zero` for distinct indices.
+pub fn insphere_sos<A: Allocator + Copy>(
+    points: [&[f64]; 5],
+    indices: [usize; 5],
+    allocator: A,
+) -> Orientation {
+    let [pa, pb, pc, pd, pe] = points;
+    let sign = double_to_sign(predicates::insphere(pa, pb, pc, pd, pe, allocator));
+    if sign != Orientation::Zero {
+        return sign;
+    }
+    let (order, odd) = sort_by_index(indices);
+    let matrix = Vec::from_iter(order.iter().map(|&i| {
+        let mut row = Vec::from_iter(points[i][..3].iter().map(|&v| exact(v, allocator)));
+        // negate the lifted column, so that raising it lowers the original one
+        let mut lift = &row[0] * &row[0] + &row[1] * &row[1] + &row[2] * &row[2];
+        lift.neg();
+        row.push(lift);
+        row.push(exact(1.0, allocator));
+        row
+    }));
+    let sign = perturbed_sign(&matrix, |r, c| {
+        if c == 3 {
+            Some(r as u32)
+        } else if c < 3 {
+            Some((5 + r * 3 + 2 - c) as u32)
+        } else {
+            None
+        }
+    });
+    if odd {
+        sign
+    } else {
+        sign_reverse(sign)
+    }
+}
+
+/// `orient4d` of the points lifted to `heights`, with the same symbolic
+/// perturbation as [`insphere_sos`] with the heights as the lifted
+/// coordinates. With `x^2 + y^2 + z^2 - w` as the height of a point of weight
+/// `w`, it is the power test of the regular triangulation. The result is never
+/// `Orientation::Zero` for distinct indices.
+pub fn orient4d_sos<A: Allocator + Copy>(
+    points: [&[f64]; 5],
+    heights: [f64; 5],
+    indices: [usize; 5],
+    allocator: A,
+) -> Orientation {
+    let [pa, pb, pc, pd, pe] = points;
+    let [ha, hb, hc, hd, he] = heights;
+    let sign = double_to_sign(predicates::orient4d(
+        pa, pb, pc, pd, pe, allocator, ha, hb, hc, hd, he,
+    ));
+    if sign != Orientation::Zero {
+        return sign;
+    }
+    let (order, odd) = sort_by_index(indices);
+    let matrix = Vec::from_iter(order.iter().map(|&i| {
+        let mut row = Vec::from_iter(points[i][..3].iter().map(|&v| exact(v, allocator)));
+        row.push(exact(-heights[i], allocator));
+        row.push(exact(1.0, allocator));
+        row
+    }));
+    let sign = perturbed_sign(&matrix, |r, c| {
+        if c == 3 {
+            Some(r as u32)
+        } else if c < 3 {
+            Some((5 + r * 3 + 2 - c) as u32)
+        } else {
+            None
+        }
+    });
+    if odd {
+        sign
+    } else {
+        sign_reverse(sign)
+    }
+}
+
+#[test]
+fn test_sos() {
+    let bump = bumpalo::Bump::new();
+    let pa = [0.0, 0.0, 0.0];
+    let pb = [1.0, 0.0, 0.0];
+    let pc = [2.0, 0.0, 0.0];
+    let pd = [0.0, 1.0, 0.0];
+    let pe = [1.0, 1.0, 0.0];
+
+    // collinear and coplanar points are never degenerate
+    let ori = orient2d_sos([&pa, &pb, &pc], [0, 1, 2], &bump);
+    assert_ne!(ori, Orientation::Zero);
+    assert_eq!(
+        orient2d_sos([&pb, &pa, &pc], [1, 0, 2], &bump),
+        sign_reverse(ori)
+    );
+    let ori = orient3d_sos([&pa, &pb, &pd, &pe], [0, 1, 3, 4], &bump);
+    assert_ne!(ori, Orientation::Zero);
+    assert_eq!(
+        orient3d_sos([&pb, &pa, &pd, &pe], [1, 0, 3, 4], &bump),
+        sign_reverse(ori)
+    );
+
+    // cospherical and coplanar points
+    let pf = [0.0, 0.0, 1.0];
+    let pg = [1.0, 1.0, 1.0];
+    for (points, ids) in [
+        ([&pa, &pb, &pd, &pf, &pg], [0, 1, 3, 5, 6]),
+        ([&pa, &pb, &pc, &pd, &pe], [0, 1, 2, 3, 4]),
+    ] {
+        let ori = insphere_sos(points.map(|p| &p[..]), ids, &bump);
+        assert_ne!(ori, Orientation::Zero);
+        let [pa, pb, pc, pd, pe] = points.map(|p| &p[..]);
+        let [ia, ib, ic, id, ie] = ids;
+        let swapped = insphere_sos([pb, pa, pc, pd, pe], [ib, ia, ic, id, ie], &bump);
+        assert_eq!(swapped, sign_reverse(ori));
+    }
+
+    // the power test with zero weights is the insphere test
+    let lift = |p: &[f64; 3]| p[0] * p[0] + p[1] * p[1] + p[2] * p[2];
+    let points = [&pa, &pb, &pd, &pf, &pg];
+    let ids = [0, 1, 3, 5, 6];
+    for (i, j) in [(0, 1), (2, 4), (3, 4)] {
+        let mut points = points;
+        let mut ids = ids;
+        points.swap(i, j);
+        ids.swap(i, j);
+        let ori = orient4d_sos(points.map(|p| &p[..]), points.map(lift), ids, &bump);
+        assert_eq!(ori, insphere_sos(points.map(|p| &p[..]), ids, &bump));
+    }
+}
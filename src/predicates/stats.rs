@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "stats")]
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    OnceLock,
+};
+
+/// How a predicate evaluation was decided, counted per predicate when the
+/// `stats` feature is enabled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PredicateStats {
+    /// Decided by the floating point static filter.
+    pub static_filter: u64,
+    /// Decided by the dynamic filter: interval arithmetic for implicit points,
+    /// the intermediate adaptive stages for Shewchuk's predicates.
+    pub interval_filter: u64,
+    /// Decided by the exact expansion stage.
+    pub exact: u64,
+    /// The length of the longest expansion computed by the exact stage.
+    pub max_expansion_len: usize,
+    /// The total length of the expansions computed by the exact stage.
+    pub total_expansion_len: u64,
+}
+
+impl PredicateStats {
+    /// The number of evaluations of the predicate.
+    #[inline(always)]
+    pub fn total(&self) -> u64 {
+        self.static_filter + self.interval_filter + self.exact
+    }
+}
+
+#[cfg_attr(not(feature = "stats"), allow(dead_code))]
+pub(crate) enum Stage {
+    Static,
+    Interval,
+    /// The exact stage, with the length of the resulting expansion.
+    Exact(usize),
+}
+
+/// The counters of a predicate, identified by the address of its name. The
+/// same name may have several addresses, whose counters are merged by
+/// [`stats`].
+#[cfg(feature = "stats")]
+struct Slot {
+    /// The address of the name, 0 for a free slot.
+    key: AtomicUsize,
+    name: OnceLock<&'static str>,
+    static_filter: AtomicU64,
+    interval_filter: AtomicU64,
+    exact: AtomicU64,
+    max_expansion_len: AtomicUsize,
+    total_expansion_len: AtomicU64,
+}
+
+#[cfg(feature = "stats")]
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            key: AtomicUsize::new(0),
+            name: OnceLock::new(),
+            static_filter: AtomicU64::new(0),
+            interval_filter: AtomicU64::new(0),
+            exact: AtomicU64::new(0),
+            max_expansion_len: AtomicUsize::new(0),
+            total_expansion_len: AtomicU64::new(0),
+        }
+    }
+
+    fn stats(&self) -> PredicateStats {
+        PredicateStats {
+            static_filter: self.static_filter.load(Ordering::Relaxed),
+            interval_filter: self.interval_filter.load(Ordering::Relaxed),
+            exact: self.exact.load(Ordering::Relaxed),
+            max_expansion_len: self.max_expansion_len.load(Ordering::Relaxed),
+            total_expansion_len: self.total_expansion_len.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The number of slots, more than the number of predicates. The evaluations
+/// of predicates finding no free slot are not counted.
+#[cfg(feature = "stats")]
+const N_SLOTS: usize = 256;
+
+/// Lock free counters, so that the predicates evaluated in parallel are not
+/// serialized.
+#[cfg(feature = "stats")]
+static SLOTS: [Slot; N_SLOTS] = [const { Slot::new() }; N_SLOTS];
+
+/// The slot of the predicate `name`, claiming a free one at its first
+/// evaluation.
+#[cfg(feature = "stats")]
+fn slot(name: &'static str) -> Option<&'static Slot> {
+    let key = name.as_ptr() as usize;
+    let start = key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (usize::BITS - N_SLOTS.ilog2());
+    for i in 0..N_SLOTS {
+        let slot = &SLOTS[(start + i) % N_SLOTS];
+        let mut current = slot.key.load(Ordering::Acquire);
+        if current == 0 {
+            match slot
+                .key
+                .compare_exchange(0, key, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => {
+                    slot.name.get_or_init(|| name);
+                    return Some(slot);
+                }
+                Err(other) => current = other,
+            }
+        }
+        if current == key {
+            return Some(slot);
+        }
+    }
+    None
+}
+
+#[cfg(feature = "stats")]
+pub(crate) fn record(name: &'static str, stage: Stage) {
+    let Some(slot) = slot(name) else {
+        return;
+    };
+    match stage {
+        Stage::Static => {
+            slot.static_filter.fetch_add(1, Ordering::Relaxed);
+        }
+        Stage::Interval => {
+            slot.interval_filter.fetch_add(1, Ordering::Relaxed);
+        }
+        Stage::Exact(len) => {
+            slot.exact.fetch_add(1, Ordering::Relaxed);
+            slot.max_expansion_len.fetch_max(len, Ordering::Relaxed);
+            slot.total_expansion_len
+                .fetch_add(len as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(not(feature = "stats"))]
+#[inline(always)]
+pub(crate) fn record(_name: &'static str, _stage: Stage) {}
+
+/// A snapshot of the counters of every predicate evaluated since the last
+/// [`reset_stats`]. Without the `stats` feature nothing is recorded and the
+/// snapshot is empty.
+pub fn stats() -> BTreeMap<&'static str, PredicateStats> {
+    #[cfg(feature = "stats")]
+    {
+        let mut stats = BTreeMap::<_, PredicateStats>::new();
+        for slot in &SLOTS {
+            // the name of a slot being claimed is not set yet
+            let Some(&name) = slot.name.get() else {
+                continue;
+            };
+            let counts = slot.stats();
+            if counts.total() == 0 {
+                continue;
+            }
+            let entry = stats.entry(name).or_default();
+            entry.static_filter += counts.static_filter;
+            entry.interval_filter += counts.interval_filter;
+            entry.exact += counts.exact;
+            entry.max_expansion_len = entry.max_expansion_len.max(counts.max_expansion_len);
+            entry.total_expansion_len += counts.total_expansion_len;
+        }
+        stats
+    }
+    #[cfg(not(feature = "stats"))]
+    {
+        BTreeMap::new()
+    }
+}
+
+/// Clear the counters of every predicate.
+pub fn reset_stats() {
+    #[cfg(feature = "stats")]
+    for slot in &SLOTS {
+        slot.static_filter.store(0, Ordering::Relaxed);
+        slot.interval_filter.store(0, Ordering::Relaxed);
+        slot.exact.store(0, Ordering::Relaxed);
+        slot.max_expansion_len.store(0, Ordering::Relaxed);
+        slot.total_expansion_len.store(0, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn test_predicate_stats() {
+    let bump = bumpalo::Bump::new();
+    let before = stats().get("orient3d").copied().unwrap_or_default();
+    let (pa, pb, pc) = ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+    super::orient3d(&pa, &pb, &pc, &[0.0, 0.0, 1.0], &bump);
+    super::orient3d(&pa, &pb, &pc, &[0.5, 0.5, 0.0], &bump);
+    // the evaluations in parallel are all counted
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                let bump = bumpalo::Bump::new();
+                for _ in 0..1000 {
+                    super::orient3d(&pa, &pb, &pc, &[0.0, 0.0, 1.0], &bump);
+                }
+            });
+        }
+    });
+
+    let after = stats().get("orient3d").copied().unwrap_or_default();
+    if cfg!(feature = "stats") {
+        // other tests may evaluate predicates concurrently
+        assert!(after.static_filter > before.static_filter + 4000);
+        assert!(after.interval_filter + after.exact > before.interval_filter + before.exact);
+    } else {
+        assert_eq!(after.total(), 0);
+    }
+}
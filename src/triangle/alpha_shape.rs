@@ -0,0 +1,558 @@
+use std::alloc::Global;
+
+use hashbrown::HashMap;
+
+use crate::{
+    disjoint_set::DisjointSet,
+    predicates::{GenericNum, Orientation},
+};
+
+use super::{tetrahedralize, TetMesh, OUTER_FACES};
+
+#[inline(always)]
+fn diff<T: GenericNum>(p: &[T; 3], q: &[T; 3]) -> [T; 3] {
+    [&q[0] - &p[0], &q[1] - &p[1], &q[2] - &p[2]]
+}
+
+#[inline(always)]
+fn dot<T: GenericNum>(u: &[T; 3], v: &[T; 3]) -> T {
+    &u[0] * &v[0] + &u[1] * &v[1] + &u[2] * &v[2]
+}
+
+#[inline(always)]
+fn cross<T: GenericNum>(u: &[T; 3], v: &[T; 3]) -> [T; 3] {
+    [
+        &u[1] * &v[2] - &u[2] * &v[1],
+        &u[2] * &v[0] - &u[0] * &v[2],
+        &u[0] * &v[1] - &u[1] * &v[0],
+    ]
+}
+
+#[inline(always)]
+fn scale<T: GenericNum>(s: &T, u: &[T; 3]) -> [T; 3] {
+    [s * &u[0], s * &u[1], s * &u[2]]
+}
+
+#[inline(always)]
+fn add<T: GenericNum>(u: &[T; 3], v: &[T; 3]) -> [T; 3] {
+    [&u[0] + &v[0], &u[1] + &v[1], &u[2] + &v[2]]
+}
+
+crate::predicate! {
+    /// The sign of the squared circumradius of the tetrahedron minus `r^2`,
+    /// scaled by the squared volume.
+    fn tet_radius_cmp(ax, ay, az, bx, by, bz, cx, cy, cz, dx, dy, dz, r) {
+        let pa = [ax, ay, az];
+        let [u, v, w] = [[bx, by, bz], [cx, cy, cz], [dx, dy, dz]].map(|p| diff(&pa, &p));
+        let (vw, wu, uv) = (cross(&v, &w), cross(&w, &u), cross(&u, &v));
+        let det = dot(&u, &vw);
+        // twice the circumcenter times the determinant
+        let center = add(
+            &add(&scale(&dot(&u, &u), &vw), &scale(&dot(&v, &v), &wu)),
+            &scale(&dot(&w, &w), &uv),
+        );
+        let diameter = &r + &r;
+        dot(&center, &center) - &diameter * &diameter * &det * det
+    }
+}
+
+crate::predicate! {
+    /// The sign of the squared circumradius of the triangle minus `r^2`,
+    /// scaled by its squared area.
+    fn face_radius_cmp(ax, ay, az, bx, by, bz, cx, cy, cz, r) {
+        let [pa, pb, pc] = [[ax, ay, az], [bx, by, bz], [cx, cy, cz]];
+        let (u, v, w) = (diff(&pa, &pb), diff(&pa, &pc), diff(&pb, &pc));
+        let n = cross(&u, &v);
+        let diameter = &r + &r;
+        dot(&u, &u) * dot(&v, &v) * dot(&w, &w) - &diameter * &diameter * dot(&n, &n)
+    }
+}
+
+crate::predicate! {
+    /// The sign of the squared half length of the edge minus `r^2`, scaled by
+    /// four.
+    fn edge_radius_cmp(ax, ay, az, bx, by, bz, r) {
+        let u = diff(&[ax, ay, az], &[bx, by, bz]);
+        let diameter = &r + &r;
+        dot(&u, &u) - &diameter * &diameter
+    }
+}
+
+crate::predicate! {
+    /// Negative if `p` lies strictly inside the smallest sphere through the
+    /// triangle.
+    fn face_ball_test(ax, ay, az, bx, by, bz, cx, cy, cz, px, py, pz) {
+        let pa = [ax, ay, az];
+        let [u, v, p] = [[bx, by, bz], [cx, cy, cz], [px, py, pz]].map(|q| diff(&pa, &q));
+        let n = cross(&u, &v);
+        // twice the circumcenter times the squared norm of the normal
+        let center = add(
+            &scale(&dot(&u, &u), &cross(&v, &n)),
+            &scale(&dot(&v, &v), &cross(&n, &u)),
+        );
+        dot(&p, &p) * dot(&n, &n) - dot(&p, &center)
+    }
+}
+
+crate::predicate! {
+    /// Negative if `p` lies strictly inside the diametral ball of the edge.
+    fn edge_ball_test(ax, ay, az, bx, by, bz, px, py, pz) {
+        let p = [px, py, pz];
+        dot(&diff(&p, &[ax, ay, az]), &diff(&p, &[bx, by, bz]))
+    }
+}
+
+/// Whether the circumsphere of the tetrahedron has a radius of at most `r`.
+fn tet_within(p: &[[f64; 3]; 4], r: f64) -> bool {
+    let [[ax, ay, az], [bx, by, bz], [cx, cy, cz], [dx, dy, dz]] = *p;
+    tet_radius_cmp(ax, ay, az, bx, by, bz, cx, cy, cz, dx, dy, dz, r, Global)
+        != Orientation::Positive
+}
+
+/// Whether the smallest sphere through the triangle has a radius of at most
+/// `r`.
+fn face_within(p: &[[f64; 3]; 3], r: f64) -> bool {
+    let [[ax, ay, az], [bx, by, bz], [cx, cy, cz]] = *p;
+    face_radius_cmp(ax, ay, az, bx, by, bz, cx, cy, cz, r, Global) != Orientation::Positive
+}
+
+/// Whether half the length of the edge is at most `r`.
+fn edge_within(p: &[[f64; 3]; 2], r: f64) -> bool {
+    let [[ax, ay, az], [bx, by, bz]] = *p;
+    edge_radius_cmp(ax, ay, az, bx, by, bz, r, Global) != Orientation::Positive
+}
+
+/// Whether `q` lies strictly inside the diametral ball of the edge.
+fn in_edge_ball(p: &[[f64; 3]; 2], q: &[f64; 3]) -> bool {
+    let [[ax, ay, az], [bx, by, bz]] = *p;
+    let [qx, qy, qz] = *q;
+    edge_ball_test(ax, ay, az, bx, by, bz, qx, qy, qz, Global) == Orientation::Negative
+}
+
+/// Whether `q` lies strictly inside the smallest sphere through the triangle.
+fn in_face_ball(p: &[[f64; 3]; 3], q: &[f64; 3]) -> bool {
+    let [[ax, ay, az], [bx, by, bz], [cx, cy, cz]] = *p;
+    let [qx, qy, qz] = *q;
+    face_ball_test(ax, ay, az, bx, by, bz, cx, cy, cz, qx, qy, qz, Global) == Orientation::Negative
+}
+
+/// The smallest radius for which `covers` holds, which is monotone in the
+/// radius, starting the search at the approximation `approx`.
+fn critical_radius<F: Fn(f64) -> bool>(approx: f64, covers: F) -> f64 {
+    if !approx.is_finite() {
+        return f64::INFINITY;
+    }
+    let approx = approx.max(0.0);
+    let (mut lo, mut hi) = if covers(approx) {
+        if approx == 0.0 || covers(0.0) {
+            return 0.0;
+        }
+        if !covers(approx.next_down()) {
+            return approx;
+        }
+        (0.0, approx)
+    } else {
+        if covers(approx.next_up()) {
+            return approx.next_up();
+        }
+        let mut hi = approx;
+        while !covers(hi) {
+            hi = (hi * 2.0).max(f64::MIN_POSITIVE);
+            if hi.is_infinite() {
+                return hi;
+            }
+        }
+        (approx, hi)
+    };
+    // the non-negative floats are ordered as their bits
+    while lo.to_bits() + 1 < hi.to_bits() {
+        let mid = f64::from_bits((lo.to_bits() + hi.to_bits()) / 2);
+        if covers(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    hi
+}
+
+/// The classification of a simplex of an alpha complex for a value of alpha.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlphaClass {
+    /// Not in the alpha complex.
+    Exterior,
+    /// In the alpha complex, but not a face of its tetrahedra.
+    Singular,
+    /// On the boundary of the alpha shape.
+    Regular,
+    /// In the interior of the alpha shape.
+    Interior,
+}
+
+impl AlphaClass {
+    /// The class of a simplex from whether it is in the complex, whether a
+    /// simplex of the next dimension around it is and whether all the
+    /// tetrahedra around it are.
+    #[inline(always)]
+    fn new(entered: bool, any_inside: bool, all_inside: bool) -> Self {
+        if !entered {
+            AlphaClass::Exterior
+        } else if !any_inside {
+            AlphaClass::Singular
+        } else if !all_inside {
+            AlphaClass::Regular
+        } else {
+            AlphaClass::Interior
+        }
+    }
+}
+
+/// A triangle of the Delaunay tetrahedralization.
+#[derive(Clone, Debug)]
+pub struct AlphaFace {
+    /// The vertices, oriented outwards of the first tetrahedron.
+    pub vertices: [usize; 3],
+    /// The tetrahedra sharing the face, the second is a hull tetrahedron on
+    /// the hull.
+    pub tets: [usize; 2],
+    /// The edges of the face.
+    pub edges: [usize; 3],
+    /// The radius of the smallest sphere through the face, rounded, or `None`
+    /// if the sphere contains the opposite vertex of a tetrahedron, so that
+    /// the face only enters the complex with it.
+    pub radius: Option<f64>,
+}
+
+/// An edge of the Delaunay tetrahedralization.
+#[derive(Clone, Debug)]
+pub struct AlphaEdge {
+    pub vertices: [usize; 2],
+    /// Half the length of the edge, rounded, or `None` if its diametral ball
+    /// contains a vertex of a tetrahedron around it.
+    pub radius: Option<f64>,
+}
+
+/// The classes of the simplices of an alpha complex for a value of alpha.
+#[derive(Clone, Debug)]
+pub struct AlphaShape {
+    pub tets: Vec<AlphaClass>,
+    pub faces: Vec<AlphaClass>,
+    pub edges: Vec<AlphaClass>,
+    /// The class of each point, exterior for the duplicated points.
+    pub vertices: Vec<AlphaClass>,
+}
+
+/// The alpha complexes of a point set, the subcomplexes of its Delaunay
+/// tetrahedralization made of the simplices with an empty circumsphere of
+/// radius at most alpha, and of their faces. The radii are rounded for
+/// sorting, while the simplices are classified for a value of alpha with
+/// exact comparisons of their squared radii.
+pub struct AlphaComplex<'a> {
+    /// The Delaunay tetrahedralization of the points.
+    pub mesh: TetMesh<'a>,
+    /// The circumradius of each tetrahedron, rounded, infinite for the hull
+    /// tetrahedra.
+    pub tet_radii: Vec<f64>,
+    pub faces: Vec<AlphaFace>,
+    pub edges: Vec<AlphaEdge>,
+}
+
+impl<'a> AlphaComplex<'a> {
+    fn new(mesh: TetMesh<'a>) -> Self {
+        let point = |vid: usize| -> [f64; 3] {
+            let p = mesh.point(vid);
+            [p[0], p[1], p[2]]
+        };
+
+        let mut tet_radii = vec![f64::INFINITY; mesh.tets.len()];
+        for (tid, radius) in tet_radii.iter_mut().enumerate() {
+            if mesh.is_hull_tet(tid) {
+                continue;
+            }
+            let [pa, pb, pc, pd] = mesh.tets[tid].data.map(point);
+            let [u, v, w] = [pb, pc, pd].map(|p| diff(&pa, &p));
+            let (vw, wu, uv) = (cross(&v, &w), cross(&w, &u), cross(&u, &v));
+            let center = add(
+                &add(&scale(&dot(&u, &u), &vw), &scale(&dot(&v, &v), &wu)),
+                &scale(&dot(&w, &w), &uv),
+            );
+            *radius = dot(&center, &center).sqrt() / (2.0 * dot(&u, &vw).abs());
+        }
+
+        let mut edge_ids: HashMap<[usize; 2], usize> = HashMap::new();
+        let mut edges = Vec::new();
+        let mut faces = Vec::new();
+        for tid in 0..mesh.tets.len() {
+            if mesh.is_hull_tet(tid) {
+                continue;
+            }
+            let tet = &mesh.tets[tid];
+            for (i, face) in OUTER_FACES.iter().enumerate() {
+                let nei = tet.nei[i].tet;
+                if !mesh.is_hull_tet(nei) && nei < tid {
+                    continue;
+                }
+                let vertices = face.map(|j| tet.data[j]);
+                let face_points = vertices.map(point);
+                // the face is attached if an opposite vertex is in its ball
+                let attached = [tid, nei].iter().any(|&t| {
+                    let opposite = mesh.tets[t]
+                        .data
+                        .into_iter()
+                        .find(|v| !vertices.contains(v));
+                    !mesh.is_hull_tet(t) && in_face_ball(&face_points, &point(opposite.unwrap()))
+                });
+                let radius = (!attached).then(|| {
+                    let [pa, pb, pc] = face_points;
+                    let (u, v, w) = (diff(&pa, &pb), diff(&pa, &pc), diff(&pb, &pc));
+                    let n = cross(&u, &v);
+                    (dot(&u, &u) * dot(&v, &v) * dot(&w, &w) / dot(&n, &n)).sqrt() / 2.0
+                });
+                let edge_ids = [0, 1, 2].map(|k| {
+                    let mut key = [vertices[k], vertices[(k + 1) % 3]];
+                    key.sort_unstable();
+                    *edge_ids.entry(key).or_insert_with(|| {
+                        edges.push(AlphaEdge {
+                            vertices: key,
+                            radius: None,
+                        });
+                        edges.len() - 1
+                    })
+                });
+                faces.push(AlphaFace {
+                    vertices,
+                    tets: [tid, nei],
+                    edges: edge_ids,
+                    radius,
+                });
+            }
+        }
+
+        // an edge is attached if a vertex of a tetrahedron around it is in
+        // its diametral ball
+        let mut attached = vec![false; edges.len()];
+        for (tid, tet) in mesh.tets.iter().enumerate() {
+            if mesh.is_hull_tet(tid) {
+                continue;
+            }
+            for i in 0..4 {
+                for j in (i + 1)..4 {
+                    let mut key = [tet.data[i], tet.data[j]];
+                    key.sort_unstable();
+                    let eid = edge_ids[&key];
+                    if attached[eid] {
+                        continue;
+                    }
+                    let [pa, pb] = key.map(point);
+                    attached[eid] = (0..4)
+                        .filter(|&k| k != i && k != j)
+                        .any(|k| in_edge_ball(&[pa, pb], &point(tet.data[k])));
+                }
+            }
+        }
+        for (edge, attached) in edges.iter_mut().zip(attached) {
+            if !attached {
+                let [pa, pb] = edge.vertices.map(point);
+                let u = diff(&pa, &pb);
+                edge.radius = Some(dot(&u, &u).sqrt() / 2.0);
+            }
+        }
+
+        Self {
+            mesh,
+            tet_radii,
+            faces,
+            edges,
+        }
+    }
+
+    #[inline(always)]
+    fn point(&self, vid: usize) -> [f64; 3] {
+        let p = self.mesh.point(vid);
+        [p[0], p[1], p[2]]
+    }
+
+    /// Whether each tetrahedron is in the alpha complex for `alpha`.
+    fn inside_tets(&self, alpha: f64) -> Vec<bool> {
+        Vec::from_iter((0..self.mesh.tets.len()).map(|tid| {
+            !self.mesh.is_hull_tet(tid)
+                && (alpha == f64::INFINITY
+                    || tet_within(&self.mesh.tets[tid].data.map(|vid| self.point(vid)), alpha))
+        }))
+    }
+
+    /// Classify the simplices of the Delaunay tetrahedralization for `alpha`,
+    /// a radius.
+    pub fn classify(&self, alpha: f64) -> AlphaShape {
+        let inside = self.inside_tets(alpha);
+        let tets = Vec::from_iter(inside.iter().map(|&inside| {
+            if inside {
+                AlphaClass::Interior
+            } else {
+                AlphaClass::Exterior
+            }
+        }));
+
+        // whether an edge is in the complex, whether a face around it is, and
+        // whether all the tetrahedra around it are
+        let mut edge_flags = Vec::from_iter(self.edges.iter().map(|edge| {
+            let entered = edge.radius.is_some() && {
+                let [pa, pb] = edge.vertices.map(|vid| self.point(vid));
+                alpha == f64::INFINITY || edge_within(&[pa, pb], alpha)
+            };
+            [entered, false, true]
+        }));
+        let faces = Vec::from_iter(self.faces.iter().map(|face| {
+            let [t1, t2] = face.tets.map(|tid| inside[tid]);
+            let entered = t1
+                || t2
+                || face.radius.is_some() && {
+                    let face_points = face.vertices.map(|vid| self.point(vid));
+                    alpha == f64::INFINITY || face_within(&face_points, alpha)
+                };
+            for &eid in &face.edges {
+                let flags = &mut edge_flags[eid];
+                flags[0] |= entered;
+                flags[1] |= entered;
+                flags[2] &= t1 && t2;
+            }
+            AlphaClass::new(entered, t1 || t2, t1 && t2)
+        }));
+
+        let mut vertex_flags = Vec::from_iter(
+            (0..self.mesh.n_points).map(|vid| [self.mesh.p2t[vid] != usize::MAX, false, true]),
+        );
+        let edges = Vec::from_iter(self.edges.iter().zip(&edge_flags).map(|(edge, flags)| {
+            for vid in edge.vertices {
+                vertex_flags[vid][1] |= flags[0];
+                vertex_flags[vid][2] &= flags[2];
+            }
+            AlphaClass::new(flags[0], flags[1], flags[2])
+        }));
+        let vertices = Vec::from_iter(
+            vertex_flags
+                .iter()
+                .map(|&[entered, any, all]| AlphaClass::new(entered && alpha >= 0.0, any, all)),
+        );
+        AlphaShape {
+            tets,
+            faces,
+            edges,
+            vertices,
+        }
+    }
+
+    /// The faces of the alpha shape for `alpha`, the regular faces, oriented
+    /// outwards of its tetrahedra.
+    pub fn boundary(&self, alpha: f64) -> Vec<usize> {
+        let inside = self.inside_tets(alpha);
+        let mut triangles = Vec::new();
+        for face in &self.faces {
+            let [a, b, c] = face.vertices;
+            match face.tets.map(|tid| inside[tid]) {
+                [true, false] => triangles.extend([a, b, c]),
+                [false, true] => triangles.extend([a, c, b]),
+                _ => {}
+            }
+        }
+        triangles
+    }
+
+    /// The number of connected components of the tetrahedra of the alpha
+    /// shape for `alpha`, connected by their faces.
+    pub fn n_components(&self, alpha: f64) -> usize {
+        self.components(&self.inside_tets(alpha))
+    }
+
+    fn components(&self, inside: &[bool]) -> usize {
+        let mut ds = DisjointSet::new(inside.len());
+        let mut n_outside = 0;
+        for (tid, tet) in self.mesh.tets.iter().enumerate() {
+            if !inside[tid] {
+                n_outside += 1;
+                continue;
+            }
+            for nei in &tet.nei {
+                if inside[nei.tet] {
+                    ds.merge(tid, nei.tet);
+                }
+            }
+        }
+        ds.n_groups - n_outside
+    }
+
+    /// Whether the tetrahedra of the alpha shape form a single component with
+    /// every vertex on it.
+    fn single_solid(&self, inside: &[bool]) -> bool {
+        let mut covered =
+            Vec::from_iter((0..self.mesh.n_points).map(|vid| self.mesh.p2t[vid] == usize::MAX));
+        for (tid, tet) in self.mesh.tets.iter().enumerate() {
+            if inside[tid] {
+                tet.data.iter().for_each(|&vid| covered[vid] = true);
+            }
+        }
+        covered.iter().all(|&c| c) && self.components(inside) == 1
+    }
+
+    /// The smallest alpha for which the alpha shape is a single connected
+    /// solid with every vertex on it, or infinity without tetrahedra. The
+    /// tetrahedra are added in the order of their rounded circumradii,
+    /// merging the components they touch, and the exact circumradius of the
+    /// last one is rounded up to the returned alpha.
+    pub fn optimal_alpha(&self) -> f64 {
+        // every vertex must be on a tetrahedron of the shape
+        let mut min_radius = vec![f64::INFINITY; self.mesh.n_points];
+        let mut order = Vec::new();
+        for (tid, tet) in self.mesh.tets.iter().enumerate() {
+            if self.mesh.is_hull_tet(tid) {
+                continue;
+            }
+            order.push(tid);
+            for vid in tet.data {
+                min_radius[vid] = min_radius[vid].min(self.tet_radii[tid]);
+            }
+        }
+        let cover = (0..self.mesh.n_points)
+            .filter(|&vid| self.mesh.p2t[vid] != usize::MAX)
+            .map(|vid| min_radius[vid])
+            .fold(0.0, f64::max);
+
+        order.sort_unstable_by(|&t1, &t2| self.tet_radii[t1].total_cmp(&self.tet_radii[t2]));
+        let mut ds = DisjointSet::new(self.mesh.tets.len());
+        let mut added = vec![false; self.mesh.tets.len()];
+        let mut n_outside = self.mesh.tets.len();
+        for (k, &tid) in order.iter().enumerate() {
+            added[tid] = true;
+            n_outside -= 1;
+            for nei in &self.mesh.tets[tid].nei {
+                if added[nei.tet] {
+                    ds.merge(tid, nei.tet);
+                }
+            }
+            let radius = self.tet_radii[tid];
+            let last_of_radius = order
+                .get(k + 1)
+                .is_none_or(|&next| self.tet_radii[next] != radius);
+            if !last_of_radius || radius < cover || ds.n_groups - n_outside != 1 {
+                continue;
+            }
+            // the exact circumradius, checked against the exact classes in
+            // case of ties up to rounding
+            let tet_points = self.mesh.tets[tid].data.map(|vid| self.point(vid));
+            let alpha = critical_radius(radius, |r| tet_within(&tet_points, r));
+            if self.single_solid(&self.inside_tets(alpha)) {
+                return alpha;
+            }
+        }
+        f64::INFINITY
+    }
+}
+
+/// The alpha complexes of `points` from their Delaunay tetrahedralization
+/// by [`tetrahedralize`]. Use [`AlphaComplex::boundary`] for the surface of
+/// the alpha shape for a value of alpha, a radius, and
+/// [`AlphaComplex::optimal_alpha`] for the smallest alpha giving a single
+/// solid.
+pub fn alpha_complex(points: &[f64]) -> AlphaComplex<'_> {
+    AlphaComplex::new(tetrahedralize(points))
+}
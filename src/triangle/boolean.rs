@@ -0,0 +1,137 @@
+use std::alloc::Allocator;
+
+use super::{delaunay_skeleton, split_intersections, winding_numbers, FillRule};
+
+/// A boolean operation on two sets of polygons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Intersection,
+    /// The first set minus the second one.
+    Difference,
+    Xor,
+}
+
+impl BooleanOp {
+    #[inline(always)]
+    pub fn contains(&self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            BooleanOp::Union => in_a || in_b,
+            BooleanOp::Intersection => in_a && in_b,
+            BooleanOp::Difference => in_a && !in_b,
+            BooleanOp::Xor => in_a != in_b,
+        }
+    }
+}
+
+/// Polygons with holes given by their boundary loops, as returned by
+/// [`polygon_boolean`].
+pub struct PolygonLoops<A: Allocator + Copy> {
+    /// coordinates of the new points, numbered after the input points
+    pub points: Vec<f64, A>,
+    /// the vertices of the loops, one loop after another
+    pub loops: Vec<usize, A>,
+    /// the first vertex of each loop, followed by the number of vertices
+    pub offsets: Vec<usize, A>,
+}
+
+/// Compute the boolean operation `op` on two sets of polygons bounded by the
+/// directed edges `a` and `b` between `points`. The inside of each set is
+/// decided by `fill_rule` from the winding numbers of its edges, so outer
+/// boundaries are counterclockwise and holes clockwise with
+/// [`FillRule::Positive`]. The edges may cross and overlap, they are overlaid
+/// in one constrained triangulation like [`triangulate_with_intersections`],
+/// whose triangles are classified for each set.
+///
+/// Returns the new points and the boundary loops of the result, the outer ones
+/// counterclockwise and the holes clockwise, or `None` if the splitting of the
+/// edges does not settle, like [`triangulate_with_intersections`].
+///
+/// [`triangulate_with_intersections`]: super::triangulate_with_intersections
+pub fn polygon_boolean<A: Allocator + Copy>(
+    points: &[f64],
+    a: &[usize],
+    b: &[usize],
+    op: BooleanOp,
+    fill_rule: FillRule,
+    bump: A,
+) -> Option<PolygonLoops<A>> {
+    let mut segments = Vec::with_capacity_in(a.len() + b.len(), bump);
+    segments.extend_from_slice(a);
+    segments.extend_from_slice(b);
+    let (all_points, sub_segments) = split_intersections(points, &segments, bump)?;
+    let mut flat_segments = Vec::with_capacity_in(sub_segments.len() * 2, bump);
+    flat_segments.extend(sub_segments.iter().flat_map(|seg| [seg[0], seg[1]]));
+
+    let (triangles, ghost) = delaunay_skeleton(&all_points, &flat_segments, bump);
+    let n_a = a.len() >> 1;
+    let winding_a = winding_numbers(
+        &triangles,
+        &ghost,
+        &flat_segments,
+        |i| sub_segments[i][2] < n_a,
+        bump,
+    );
+    let winding_b = winding_numbers(
+        &triangles,
+        &ghost,
+        &flat_segments,
+        |i| sub_segments[i][2] >= n_a,
+        bump,
+    );
+    let mut inside = Vec::with_capacity_in(triangles.len(), bump);
+    inside.extend((0..triangles.len()).map(|i| {
+        !ghost[i]
+            && op.contains(
+                fill_rule.contains(winding_a[i]),
+                fill_rule.contains(winding_b[i]),
+            )
+    }));
+
+    // walk along the edges between the inside and the outside, keeping the
+    // inside on the left and turning around the vertices through the inside
+    let mut visited = std::vec::from_elem_in(false, triangles.len() * 3, bump);
+    let mut loops = Vec::new_in(bump);
+    let mut offsets = Vec::new_in(bump);
+    offsets.push(0);
+    for start in 0..triangles.len() {
+        if !inside[start] {
+            continue;
+        }
+        for start_edge in 0..3 {
+            if visited[start * 3 + start_edge] || inside[triangles[start].nei[start_edge].tri] {
+                continue;
+            }
+            let (mut tid, mut j) = (start, start_edge);
+            loop {
+                visited[tid * 3 + j] = true;
+                let data = &triangles[tid].data;
+                loops.push(data[(j + 1) % 3]);
+                let vid = data[(j + 2) % 3];
+                let mut k = (j + 1) % 3;
+                loop {
+                    let nei = triangles[tid].nei[k].tri;
+                    if !inside[nei] {
+                        j = k;
+                        break;
+                    }
+                    tid = nei;
+                    let pos = (0..3).find(|&i| triangles[nei].data[i] == vid).unwrap();
+                    k = (pos + 2) % 3;
+                }
+                if visited[tid * 3 + j] {
+                    break;
+                }
+            }
+            offsets.push(loops.len());
+        }
+    }
+
+    let mut new_points = Vec::with_capacity_in(all_points.len() - points.len(), bump);
+    new_points.extend_from_slice(&all_points[points.len()..]);
+    Some(PolygonLoops {
+        points: new_points,
+        loops,
+        offsets,
+    })
+}
@@ -0,0 +1,272 @@
+use bumpalo::Bump;
+
+use crate::{
+    predicates::{self, Orientation},
+    INVALID_IND,
+};
+
+use super::{tetrahedralize, tetrahedralize_owned, TetMesh, OUTER_FACES};
+
+#[inline(always)]
+fn point(points: &[f64], idx: usize) -> &[f64] {
+    &points[idx * 3..idx * 3 + 3]
+}
+
+/// The convex hull of a set of points.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConvexHull {
+    /// The dimension of the affine hull of the points, from 0 for a single
+    /// point to 3.
+    pub dimension: usize,
+    /// The triangles of the hull, oriented outwards. The points lying on the
+    /// faces and edges of the hull are vertices of its triangles. A flat hull
+    /// is a convex polygon triangulated on both sides, with no volume, whose
+    /// boundary points are vertices of its triangles, and the hull of
+    /// collinear points has no triangles.
+    pub triangles: Vec<usize>,
+    /// The sorted indices of the points on the boundary of the hull: the
+    /// vertices of its triangles, or the ends of a segment, or the single
+    /// point. Of duplicate points, only the first is a vertex.
+    pub vertices: Vec<usize>,
+}
+
+/// Whether the point `p` is off the affine hull of the affinely independent
+/// points `basis`.
+fn is_independent(points: &[f64], basis: &[usize], p: &[f64], bump: &Bump) -> bool {
+    match basis {
+        [] => true,
+        &[a] => point(points, a) != p,
+        &[a, b] => (0..3).any(|axis| {
+            predicates::orient2d_by_axis(point(points, a), point(points, b), p, axis, bump)
+                != Orientation::Zero
+        }),
+        &[a, b, c, ..] => {
+            predicates::orient3d(
+                point(points, a),
+                point(points, b),
+                point(points, c),
+                p,
+                bump,
+            ) != 0.0
+        }
+    }
+}
+
+/// The hull of a tetrahedralization, the faces of its hull tetrahedra, with
+/// the inserted points following the input ones.
+fn mesh_hull(mesh: &TetMesh) -> ConvexHull {
+    let ghost = mesh.n_points;
+    let mut triangles = Vec::new();
+    for tet in mesh.tets.iter().filter(|t| t.data[3] == ghost) {
+        // the face of the real tetrahedron is oriented outwards
+        let nei = &tet.nei[3];
+        let real = &mesh.tets[nei.tet];
+        triangles.extend(OUTER_FACES[nei.ver & 3].map(|i| mesh.output_index(real.data[i])));
+    }
+    let mut vertices = triangles.clone();
+    vertices.sort_unstable();
+    vertices.dedup();
+    ConvexHull {
+        dimension: 3,
+        triangles,
+        vertices,
+    }
+}
+
+/// The hull of points whose affine hull has less than 3 dimensions.
+fn flat_hull(points: &[f64], bump: &Bump) -> ConvexHull {
+    let n_points = points.len() / 3;
+    let mut basis = Vec::with_capacity(3);
+    for i in 0..n_points {
+        if basis.len() < 3 && is_independent(points, &basis, point(points, i), bump) {
+            basis.push(i);
+        }
+    }
+    match basis[..] {
+        [] => ConvexHull::default(),
+        [a] => ConvexHull {
+            dimension: 0,
+            triangles: Vec::new(),
+            vertices: vec![a],
+        },
+        [_, _] => {
+            // the lexicographic order of collinear points is along their line
+            let cmp =
+                |&i: &usize, &j: &usize| point(points, i).partial_cmp(point(points, j)).unwrap();
+            let first = (0..n_points).min_by(cmp).unwrap();
+            // `max_by` returns the last maximum, so the points are reversed
+            let last = (0..n_points).rev().max_by(cmp).unwrap();
+            ConvexHull {
+                dimension: 1,
+                triangles: Vec::new(),
+                vertices: vec![first.min(last), first.max(last)],
+            }
+        }
+        [a, b, c, ..] => {
+            // the projection along an axis not parallel to the plane of the
+            // points keeps their orientations
+            let axis = (0..3)
+                .find(|&axis| {
+                    predicates::orient2d_by_axis(
+                        point(points, a),
+                        point(points, b),
+                        point(points, c),
+                        axis,
+                        bump,
+                    ) != Orientation::Zero
+                })
+                .unwrap();
+            let uv = |i: usize| {
+                let p = point(points, i);
+                [p[(axis + 1) % 3], p[(axis + 2) % 3]]
+            };
+            let mut ids = Vec::from_iter(0..n_points);
+            ids.sort_by(|&i, &j| uv(i).partial_cmp(&uv(j)).unwrap());
+            // the first of the duplicates is kept
+            ids.dedup_by_key(|&mut i| uv(i));
+
+            // the monotone chain, keeping the points on the edges between the
+            // corners, as only the right turns are popped
+            let right_turn = |i: usize, j: usize, k: usize| {
+                predicates::orient2d_by_axis(
+                    point(points, i),
+                    point(points, j),
+                    point(points, k),
+                    axis,
+                    bump,
+                ) == Orientation::Negative
+            };
+            let mut ring: Vec<usize> = Vec::new();
+            for &i in &ids {
+                while ring.len() >= 2 && right_turn(ring[ring.len() - 2], ring[ring.len() - 1], i) {
+                    ring.pop();
+                }
+                ring.push(i);
+            }
+            let n_lower = ring.len() + 1;
+            for &i in ids.iter().rev().skip(1) {
+                while ring.len() >= n_lower
+                    && right_turn(ring[ring.len() - 2], ring[ring.len() - 1], i)
+                {
+                    ring.pop();
+                }
+                ring.push(i);
+            }
+            // the first point closes the chain
+            ring.pop();
+
+            // the Delaunay triangles of the boundary points have no collinear
+            // corners and fill the polygon
+            let mut ring_uv = Vec::with_capacity(ring.len() * 2);
+            for &i in &ring {
+                ring_uv.extend(uv(i));
+            }
+            let local = super::triangulate(&ring_uv, &[], bump);
+            let mut triangles = Vec::with_capacity(local.len() * 2);
+            triangles.extend(local.iter().map(|&i| ring[i]));
+            for tri in local.chunks_exact(3) {
+                triangles.extend([ring[tri[0]], ring[tri[2]], ring[tri[1]]]);
+            }
+            ring.sort_unstable();
+            ConvexHull {
+                dimension: 2,
+                triangles,
+                vertices: ring,
+            }
+        }
+    }
+}
+
+/// The convex hull of `points`, from the hull tetrahedra of their Delaunay
+/// tetrahedralization, or computed in the plane or on the line of the points
+/// if they are coplanar or collinear. All the tests are exact.
+pub fn convex_hull(points: &[f64]) -> ConvexHull {
+    let mesh = tetrahedralize(points);
+    if mesh.tets.is_empty() {
+        flat_hull(points, &Bump::new())
+    } else {
+        mesh_hull(&mesh)
+    }
+}
+
+/// A convex hull growing with the insertion of points, in a Delaunay
+/// tetrahedralization once the points are not coplanar.
+pub struct IncrementalHull {
+    /// The points while they are coplanar.
+    flat: Vec<f64>,
+    /// The affinely independent points among the flat points.
+    basis: Vec<usize>,
+    mesh: Option<TetMesh<'static>>,
+    bump: Bump,
+}
+
+impl IncrementalHull {
+    /// The hull of the initial `points`.
+    pub fn new(points: &[f64]) -> Self {
+        let mut hull = Self {
+            flat: Vec::new(),
+            basis: Vec::with_capacity(4),
+            mesh: None,
+            bump: Bump::new(),
+        };
+        for p in points.chunks_exact(3) {
+            hull.push_flat(p);
+        }
+        hull.build();
+        hull
+    }
+
+    /// Append the point `p` to the flat points, recording whether it raises
+    /// their dimension.
+    fn push_flat(&mut self, p: &[f64]) {
+        if self.basis.len() < 4 && is_independent(&self.flat, &self.basis, p, &self.bump) {
+            self.basis.push(self.flat.len() / 3);
+        }
+        self.flat.extend_from_slice(p);
+    }
+
+    /// Tetrahedralize the flat points once they span the space.
+    fn build(&mut self) {
+        if self.basis.len() == 4 {
+            self.mesh = Some(tetrahedralize_owned(std::mem::take(&mut self.flat)));
+        }
+    }
+
+    /// The number of points.
+    pub fn n_points(&self) -> usize {
+        match &self.mesh {
+            Some(mesh) => mesh.p2t.len() - 1,
+            None => self.flat.len() / 3,
+        }
+    }
+
+    /// Insert the point `p` and return its index, which follows the previous
+    /// points. A point inside the hull or duplicating a vertex is kept but
+    /// does not change the hull.
+    pub fn insert(&mut self, p: &[f64]) -> usize {
+        let idx = self.n_points();
+        self.bump.reset();
+        match &mut self.mesh {
+            Some(mesh) => {
+                let last = mesh.p2t[mesh.p2t.len() - 1];
+                let hint = (last != INVALID_IND).then_some(last);
+                if mesh.insert_point(p, hint, &self.bump).is_none() {
+                    mesh.push_point(p);
+                }
+            }
+            None => {
+                self.push_flat(p);
+                self.build();
+            }
+        }
+        idx
+    }
+
+    /// The current convex hull.
+    pub fn hull(&self) -> ConvexHull {
+        match &self.mesh {
+            Some(mesh) => mesh_hull(mesh),
+            None => flat_hull(&self.flat, &self.bump),
+        }
+    }
+}
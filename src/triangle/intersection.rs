@@ -0,0 +1,295 @@
+use std::alloc::Allocator;
+
+use hashbrown::{hash_map::DefaultHashBuilder, HashMap};
+
+use crate::predicates;
+
+use super::constrained_delaunay;
+
+#[inline(always)]
+fn point(points: &[f64], idx: usize) -> &[f64] {
+    &points[(idx << 1)..((idx << 1) + 2)]
+}
+
+/// The vertices by their coordinates.
+type VertexIds<A> = HashMap<[u64; 2], usize, DefaultHashBuilder, A>;
+
+/// The points followed by the crossings, and the sub-segments.
+type SplitSegments<A> = (Vec<f64, A>, Vec<[usize; 3], A>);
+
+/// The new points, the triangles and the sub-segments of an overlay.
+pub(super) type Overlay<A> = (Vec<f64, A>, Vec<usize, A>, Vec<usize, A>);
+
+/// The key of a point in the map from coordinates to vertices.
+#[inline(always)]
+fn coord_key(p: &[f64]) -> [u64; 2] {
+    // adding zero turns -0.0 into 0.0
+    [(p[0] + 0.0).to_bits(), (p[1] + 0.0).to_bits()]
+}
+
+/// Whether `p`, collinear with the segment from `pa` to `pb`, lies strictly
+/// between its endpoints.
+#[inline(always)]
+fn strictly_between(pa: &[f64], pb: &[f64], p: &[f64]) -> bool {
+    let axis = if pa[0] != pb[0] { 0 } else { 1 };
+    (pa[axis] < p[axis] && p[axis] < pb[axis]) || (pb[axis] < p[axis] && p[axis] < pa[axis])
+}
+
+/// The split points of the segments, stored as pairs of a segment and a vertex.
+struct Splits<'a, A: Allocator + Copy> {
+    points: &'a mut Vec<f64, A>,
+    vertex_ids: &'a mut VertexIds<A>,
+    splits: Vec<(usize, usize), A>,
+}
+
+impl<A: Allocator + Copy> Splits<'_, A> {
+    /// The vertex at `p`, added if there is none.
+    fn vertex(&mut self, p: [f64; 2]) -> usize {
+        let n_points = self.points.len() >> 1;
+        let vid = *self.vertex_ids.entry(coord_key(&p)).or_insert(n_points);
+        if vid == n_points {
+            self.points.extend(p);
+        }
+        vid
+    }
+
+    /// Record the intersections of the segments `[a, b]` and `[c, d]`, where a
+    /// segment may be a single vertex.
+    fn intersect(
+        &mut self,
+        (s, [a, b]): (usize, [usize; 2]),
+        (t, [c, d]): (usize, [usize; 2]),
+        bump: A,
+    ) {
+        if a == b && c == d {
+            return;
+        }
+        let points = &*self.points;
+        let (pa, pb) = (point(points, a), point(points, b));
+        let (pc, pd) = (point(points, c), point(points, d));
+        let o1 = predicates::orient2d(pa, pb, pc, bump);
+        let o2 = predicates::orient2d(pa, pb, pd, bump);
+        let o3 = predicates::orient2d(pc, pd, pa, bump);
+        let o4 = predicates::orient2d(pc, pd, pb, bump);
+        if a != b && c != d && o1 * o2 < 0.0 && o3 * o4 < 0.0 {
+            // the segments cross, the intersection is rounded to the nearest
+            // floating point coordinates
+            let ratio = o3 / (o3 - o4);
+            let p = [
+                pa[0] + (pb[0] - pa[0]) * ratio,
+                pa[1] + (pb[1] - pa[1]) * ratio,
+            ];
+            // snap it to a nearby endpoint, otherwise the crossings of the
+            // pieces with the segments passing nearby may never end
+            let scale = [pa, pb, pc, pd]
+                .iter()
+                .flat_map(|q| [q[0].abs(), q[1].abs()])
+                .fold(0.0, f64::max);
+            let tolerance = scale * f64::EPSILON * 16.0;
+            let vid = [a, b, c, d]
+                .into_iter()
+                .find(|&v| {
+                    let q = point(points, v);
+                    (q[0] - p[0]).abs() <= tolerance && (q[1] - p[1]).abs() <= tolerance
+                })
+                .unwrap_or_else(|| self.vertex(p));
+            self.splits.extend([(s, vid), (t, vid)]);
+            return;
+        }
+        // the endpoints lying in the interior of the other segment
+        if a != b {
+            for (v, ori) in [(c, o1), (d, o2)] {
+                if ori == 0.0 && strictly_between(pa, pb, point(points, v)) {
+                    self.splits.push((s, v));
+                }
+            }
+        }
+        if c != d {
+            for (v, ori) in [(a, o3), (b, o4)] {
+                if ori == 0.0 && strictly_between(pc, pd, point(points, v)) {
+                    self.splits.push((t, v));
+                }
+            }
+        }
+    }
+}
+
+/// Split the segments, given as their endpoints followed by the index of the
+/// input segment, where they cross other segments or pass through vertices.
+/// Returns whether any segment was split.
+fn split_segments<A: Allocator + Copy>(
+    points: &mut Vec<f64, A>,
+    vertex_ids: &mut VertexIds<A>,
+    segments: &mut Vec<[usize; 3], A>,
+    bump: A,
+) -> bool {
+    // the vertices are single point segments, numbered after the segments
+    let n_points = points.len() >> 1;
+    let item = |i: usize| {
+        if i < segments.len() {
+            let [a, b, _] = segments[i];
+            [a, b]
+        } else {
+            [i - segments.len(); 2]
+        }
+    };
+    let bounds = |i: usize| {
+        let [a, b] = item(i);
+        let (pa, pb) = (point(points, a), point(points, b));
+        [
+            pa[0].min(pb[0]),
+            pa[0].max(pb[0]),
+            pa[1].min(pb[1]),
+            pa[1].max(pb[1]),
+        ]
+    };
+    let mut boxes = Vec::with_capacity_in(segments.len() + n_points, bump);
+    boxes.extend((0..(segments.len() + n_points)).map(bounds));
+    let mut order = Vec::with_capacity_in(boxes.len(), bump);
+    order.extend(0..boxes.len());
+    order.sort_unstable_by(|&i, &j| boxes[i][0].partial_cmp(&boxes[j][0]).unwrap());
+
+    let mut items = Vec::with_capacity_in(boxes.len(), bump);
+    items.extend((0..boxes.len()).map(item));
+    let mut splits = Splits {
+        points,
+        vertex_ids,
+        splits: Vec::new_in(bump),
+    };
+    // sweep the boxes along the x axis
+    for (k, &i) in order.iter().enumerate() {
+        for &j in &order[(k + 1)..] {
+            if boxes[j][0] > boxes[i][1] {
+                break;
+            }
+            if boxes[j][2] > boxes[i][3] || boxes[i][2] > boxes[j][3] {
+                continue;
+            }
+            let [a, b] = items[i];
+            let [c, d] = items[j];
+            if (a == c || a == d) && (b == c || b == d) {
+                continue;
+            }
+            splits.intersect((i, [a, b]), (j, [c, d]), bump);
+        }
+    }
+    let mut splits = splits.splits;
+    splits.retain(|&(s, _)| s < segments.len());
+    if splits.is_empty() {
+        return false;
+    }
+
+    // order the split points of each segment from its first endpoint
+    splits.sort_unstable_by(|&(s, v), &(t, w)| {
+        s.cmp(&t).then_with(|| {
+            let [a, b, _] = segments[s];
+            let (pa, pb) = (point(points, a), point(points, b));
+            let axis = if (pb[0] - pa[0]).abs() >= (pb[1] - pa[1]).abs() {
+                0
+            } else {
+                1
+            };
+            let pv = point(points, v)[axis];
+            let pw = point(points, w)[axis];
+            let cmp = pv.partial_cmp(&pw).unwrap();
+            if pa[axis] <= pb[axis] {
+                cmp
+            } else {
+                cmp.reverse()
+            }
+        })
+    });
+    splits.dedup();
+    let mut result = Vec::with_capacity_in(segments.len() + splits.len(), bump);
+    let mut splits = splits.into_iter().peekable();
+    for (s, &[a, b, parent]) in segments.iter().enumerate() {
+        let mut start = a;
+        while let Some((_, v)) = splits.next_if(|&(t, _)| t == s) {
+            if v != start {
+                result.push([start, v, parent]);
+                start = v;
+            }
+        }
+        if b != start {
+            result.push([start, b, parent]);
+        }
+    }
+    // a crossing may be rounded to an endpoint
+    let split = result.len() > segments.len();
+    *segments = result;
+    split
+}
+
+/// The number of passes splitting the segments before giving up, as the
+/// rounded crossings of a pass may create new crossings.
+const MAX_SPLIT_PASSES: usize = 32;
+
+/// Split the `segments` at their crossings and at the vertices lying on them.
+/// Returns the points followed by the crossings, and the sub-segments as their
+/// endpoints followed by the index of the input segment containing them, or
+/// `None` if rounding keeps creating new crossings after `MAX_SPLIT_PASSES`
+/// passes.
+pub(super) fn split_intersections<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    bump: A,
+) -> Option<SplitSegments<A>> {
+    let mut all_points = Vec::with_capacity_in(points.len(), bump);
+    all_points.extend_from_slice(points);
+    let mut vertex_ids = HashMap::with_capacity_in(points.len() >> 1, bump);
+    for i in 0..(points.len() >> 1) {
+        vertex_ids.entry(coord_key(point(points, i))).or_insert(i);
+    }
+    // the segments use the first of coincident vertices
+    let vertex = |v: usize| vertex_ids[&coord_key(point(points, v))];
+    let mut sub_segments = Vec::with_capacity_in(segments.len() >> 1, bump);
+    sub_segments.extend(
+        segments
+            .chunks(2)
+            .enumerate()
+            .map(|(i, seg)| [vertex(seg[0]), vertex(seg[1]), i])
+            .filter(|seg| seg[0] != seg[1]),
+    );
+    for _ in 0..MAX_SPLIT_PASSES {
+        if !split_segments(&mut all_points, &mut vertex_ids, &mut sub_segments, bump) {
+            return Some((all_points, sub_segments));
+        }
+    }
+    None
+}
+
+/// Triangulate like [`triangulate`](super::triangulate) when the segments may
+/// cross or overlap each other. The segments are split at their crossings and
+/// at the vertices lying on them, the crossings are added as new points with
+/// their coordinates rounded. The predicates deciding the intersections are
+/// exact, but rounding may create new crossings near the existing ones, which
+/// are split in turn, and crossings within a few ulps of an endpoint of the
+/// segments are snapped to it.
+///
+/// Returns the new points, numbered after the input points, the triangles and
+/// the sub-segments, as triples of their endpoints followed by the index of
+/// the input segment containing them. Overlapping segments yield a sub-segment
+/// for each of them. Returns `None` if the splitting does not settle, which
+/// takes rounded crossings creating new crossings over and over.
+pub fn triangulate_with_intersections<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    bump: A,
+) -> Option<Overlay<A>> {
+    let (all_points, sub_segments) = split_intersections(points, segments, bump)?;
+    let mut flat_segments = Vec::with_capacity_in(sub_segments.len() * 2, bump);
+    flat_segments.extend(sub_segments.iter().flat_map(|seg| [seg[0], seg[1]]));
+
+    let (triangles, ghost) = constrained_delaunay(&all_points, &flat_segments, bump);
+    let mut result = Vec::new_in(bump);
+    for (tri, is_ghost) in triangles.iter().zip(ghost) {
+        if !is_ghost {
+            result.extend_from_slice(&tri.data[0..3]);
+        }
+    }
+    let mut new_points = Vec::with_capacity_in(all_points.len() - points.len(), bump);
+    new_points.extend_from_slice(&all_points[points.len()..]);
+    let mut flat_sub_segments = Vec::with_capacity_in(sub_segments.len() * 3, bump);
+    flat_sub_segments.extend(sub_segments.into_iter().flatten());
+    Some((new_points, result, flat_sub_segments))
+}
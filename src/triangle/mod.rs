@@ -1,5 +1,25 @@
+mod alpha_shape;
+mod boolean;
+mod convex_hull;
+mod intersection;
+mod reconstruction;
+mod refine;
+mod tet_io;
+mod tet_refine;
 mod tetrahedron;
 mod triangle;
+mod triangulation;
+mod voronoi;
 
+pub use alpha_shape::*;
+pub use boolean::*;
+pub use convex_hull::*;
+pub use intersection::*;
+pub use reconstruction::*;
+pub use refine::*;
+pub use tet_io::*;
+pub use tet_refine::*;
 pub use tetrahedron::*;
 pub use triangle::*;
+pub use triangulation::*;
+pub use voronoi::*;
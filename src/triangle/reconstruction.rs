@@ -0,0 +1,233 @@
+use bumpalo::Bump;
+
+use crate::{
+    graphcut::{ArcBuilder, MaxFlow, PushRelabelFifo},
+    math::{cross, dot, sub},
+    predicates, INVALID_IND,
+};
+
+use super::{tetrahedralize, TetMesh, OUTER_FACES};
+
+#[inline(always)]
+fn point(points: &[f64], idx: usize) -> [f64; 3] {
+    let start = idx * 3;
+    [points[start], points[start + 1], points[start + 2]]
+}
+
+/// The lines of sight of the points of a reconstruction, the segments in
+/// front of the points which are known to be empty.
+#[derive(Clone, Copy, Debug)]
+pub enum LineOfSight<'a> {
+    /// The oriented normals of the points, pointing outside. The space is
+    /// assumed to be empty over `ray_length` in front of each point, which
+    /// should be less than the thickness of the object.
+    Normals { normals: &'a [f64], ray_length: f64 },
+    /// The position of the sensor which saw each point.
+    Sensors(&'a [f64]),
+}
+
+/// The tetrahedron around the vertex `vid`, from its tetrahedra `star`, whose
+/// cone at the vertex contains the direction towards `target`, or `None` if
+/// the direction leaves the convex hull.
+fn cone_tet(
+    mesh: &TetMesh,
+    star: &[usize],
+    vid: usize,
+    target: &[f64; 3],
+    bump: &Bump,
+) -> Option<usize> {
+    star.iter().copied().find(|&tid| {
+        let tet = &mesh.tets[tid];
+        let pos = tet.index(vid).unwrap();
+        (0..4).filter(|&i| i != pos).all(|i| {
+            let [pa, pb, pc] = OUTER_FACES[i].map(|j| mesh.point(tet.data[j]));
+            predicates::orient3d(pa, pb, pc, target, bump) >= 0.0
+        })
+    })
+}
+
+/// Walk from the vertex `vid` towards `target` through the tetrahedra
+/// starting at `start`, returning the pairs of tetrahedra whose shared face is
+/// crossed, and the tetrahedron containing `target`, or `None` if the walk
+/// leaves the convex hull.
+fn walk(
+    mesh: &TetMesh,
+    vid: usize,
+    target: &[f64; 3],
+    start: usize,
+    bump: &Bump,
+) -> (Vec<(usize, usize)>, Option<usize>) {
+    let p = mesh.point(vid);
+    let mut crossed = Vec::new();
+    let mut tid = start;
+    // the faces the segment may leave through, only the face opposite to the
+    // vertex in the first tetrahedron
+    let mut entry = 4;
+    let first = mesh.tets[tid].index(vid).unwrap();
+    for _ in 0..mesh.tets.len() {
+        let tet = &mesh.tets[tid];
+        let exit = (0..4)
+            .filter(|&i| if entry == 4 { i == first } else { i != entry })
+            .find(|&i| {
+                // the line leaves through the face if it turns around its
+                // edges in the same direction
+                let face = OUTER_FACES[i].map(|j| mesh.point(tet.data[j]));
+                (0..3).all(|k| {
+                    predicates::orient3d(p, target, face[k], face[(k + 1) % 3], bump) <= 0.0
+                })
+            });
+        let Some(exit) = exit else {
+            break;
+        };
+        let [pa, pb, pc] = OUTER_FACES[exit].map(|j| mesh.point(tet.data[j]));
+        if predicates::orient3d(pa, pb, pc, target, bump) >= 0.0 {
+            return (crossed, Some(tid));
+        }
+        let nei = &tet.nei[exit];
+        crossed.push((tid, nei.tet));
+        if mesh.is_hull_tet(nei.tet) {
+            break;
+        }
+        entry = nei.ver & 3;
+        tid = nei.tet;
+    }
+    (crossed, None)
+}
+
+/// The cosine of the angle between the `face` of the tetrahedron `tid` and its
+/// circumsphere, one for a hull tetrahedron, whose circumsphere is a
+/// half-space.
+fn face_sphere_cos(mesh: &TetMesh, tid: usize, face: &[[f64; 3]; 3]) -> f64 {
+    if mesh.is_hull_tet(tid) {
+        return 1.0;
+    }
+    let center = mesh.orthocenter(tid);
+    let mut r = [0.0; 3];
+    sub(mesh.point(mesh.tets[tid].data[0]), &center, &mut r);
+    let sphere_radius2 = dot(&r, &r);
+
+    let [pa, pb, pc] = face;
+    let (mut u, mut v, mut w, mut n) = ([0.0; 3], [0.0; 3], [0.0; 3], [0.0; 3]);
+    sub(pb, pa, &mut u);
+    sub(pc, pa, &mut v);
+    sub(pc, pb, &mut w);
+    cross(&u, &v, &mut n);
+    let face_radius2 = dot(&u, &u) * dot(&v, &v) * dot(&w, &w) / (4.0 * dot(&n, &n));
+    (1.0 - face_radius2 / sphere_radius2).max(0.0).sqrt()
+}
+
+/// Reconstruct a closed surface from the points sampled on it and their lines
+/// of sight, like Labatut et al. The Delaunay tetrahedra of the points are
+/// labeled inside or outside by a minimum cut. Each line of sight adds a unit
+/// penalty to the faces it crosses, to the tetrahedron containing its sensor
+/// being inside and to the tetrahedron behind its point being outside. Each
+/// face adds `quality_weight` times one minus the smaller cosine of the angles
+/// between the face and the circumspheres of its tetrahedra, so that the
+/// faces cutting the circumspheres of large tetrahedra close to their
+/// equators are avoided. The hull tetrahedra are outside.
+///
+/// Returns the faces between the inside and the outside tetrahedra, a closed
+/// surface oriented outwards, which may be non-manifold at some edges and
+/// vertices.
+pub fn reconstruct_surface(points: &[f64], sight: LineOfSight, quality_weight: f64) -> Vec<usize> {
+    let mut mesh = tetrahedralize(points);
+    let n_tets = mesh.tets.len();
+    // the evidence of the tetrahedra being inside, linked to the source, and
+    // outside, linked to the sink
+    let mut inside_caps = vec![0.0; n_tets];
+    let mut outside_caps = vec![0.0; n_tets];
+    let mut visibility = Vec::new();
+    let mut bump = Bump::new();
+    for vid in 0..mesh.n_points {
+        if mesh.p2t[vid] == INVALID_IND {
+            continue;
+        }
+        bump.reset();
+        let p = point(points, vid);
+        let sensor = match sight {
+            LineOfSight::Normals {
+                normals,
+                ray_length,
+            } => {
+                let n = point(normals, vid);
+                let norm = dot(&n, &n).sqrt();
+                if norm == 0.0 {
+                    continue;
+                }
+                [0, 1, 2].map(|i| p[i] + n[i] / norm * ray_length)
+            }
+            LineOfSight::Sensors(sensors) => point(sensors, vid),
+        };
+        let star = mesh.incident(vid, &bump);
+        let behind = [0, 1, 2].map(|i| p[i] * 2.0 - sensor[i]);
+        if let Some(tid) = cone_tet(&mesh, &star, vid, &behind, &bump) {
+            inside_caps[tid] += 1.0;
+        }
+        let Some(start) = cone_tet(&mesh, &star, vid, &sensor, &bump) else {
+            continue;
+        };
+        let (crossed, end) = walk(&mesh, vid, &sensor, start, &bump);
+        for (from, to) in crossed {
+            if mesh.is_hull_tet(to) {
+                outside_caps[from] += 1.0;
+            } else {
+                visibility.push((from, to));
+            }
+        }
+        if let Some(tid) = end {
+            outside_caps[tid] += 1.0;
+        }
+    }
+
+    // the faces on the hull add their weight to the tetrahedron being outside
+    let mut face_arcs = Vec::new();
+    for (tid, outside_cap) in outside_caps.iter_mut().enumerate() {
+        if mesh.is_hull_tet(tid) {
+            continue;
+        }
+        for (i, face) in OUTER_FACES.iter().enumerate() {
+            let nei = mesh.tets[tid].nei[i].tet;
+            if !mesh.is_hull_tet(nei) && nei < tid {
+                continue;
+            }
+            let face_points = face.map(|j| point(points, mesh.tets[tid].data[j]));
+            let cos = face_sphere_cos(&mesh, tid, &face_points).min(face_sphere_cos(
+                &mesh,
+                nei,
+                &face_points,
+            ));
+            let weight = quality_weight * (1.0 - cos);
+            if mesh.is_hull_tet(nei) {
+                *outside_cap += weight;
+            } else {
+                face_arcs.push((tid, nei, weight));
+            }
+        }
+    }
+
+    let mut builder = ArcBuilder::new(&inside_caps, &outside_caps);
+    for (from, to) in visibility {
+        builder.add_arc(from, to, 1.0, false);
+    }
+    for (tid, nei, weight) in face_arcs {
+        builder.add_arc(tid, nei, weight, true);
+    }
+
+    // add 2 to the number of tetrahedra for the terminals
+    let mut max_flow = PushRelabelFifo::from((builder.arcs, n_tets + 2));
+    max_flow.find_max_flow();
+    let inside =
+        Vec::from_iter((0..n_tets).map(|tid| !mesh.is_hull_tet(tid) && !max_flow.is_sink(tid + 1)));
+    let mut triangles = Vec::new();
+    for (tid, tet) in mesh.tets.iter().enumerate() {
+        if !inside[tid] {
+            continue;
+        }
+        for (i, face) in OUTER_FACES.iter().enumerate() {
+            if !inside[tet.nei[i].tet] {
+                triangles.extend(face.map(|j| tet.data[j]));
+            }
+        }
+    }
+    triangles
+}
@@ -0,0 +1,525 @@
+use std::{alloc::Allocator, collections::VecDeque};
+
+use crate::{predicates, INVALID_IND};
+
+use super::{constrained_delaunay_with_seeds, RegionSeed};
+
+#[inline(always)]
+fn point(points: &[f64], idx: usize) -> &[f64] {
+    &points[(idx << 1)..((idx << 1) + 2)]
+}
+
+#[inline(always)]
+fn dist2(pa: &[f64], pb: &[f64]) -> f64 {
+    let dx = pa[0] - pb[0];
+    let dy = pa[1] - pb[1];
+    dx * dx + dy * dy
+}
+
+struct Refiner<A: Allocator + Copy> {
+    points: Vec<f64, A>,
+    n_input_points: usize,
+    /// vertices of each triangle in counterclockwise order
+    triangles: Vec<[usize; 3], A>,
+    /// the triangle across the edge opposite to each vertex
+    neighbors: Vec<[usize; 3], A>,
+    /// the segment on the edge opposite to each vertex
+    edge_segments: Vec<[usize; 3], A>,
+    /// endpoints of the segments, including the boundary of the domain
+    segments: Vec<[usize; 2], A>,
+    /// the segment the vertex was inserted on
+    vertex_segments: Vec<usize, A>,
+    /// stamps of the triangles visited by the current cavity search
+    stamps: Vec<usize, A>,
+    stamp: usize,
+    sin2_min_angle: f64,
+    max_area: f64,
+    /// the index of the region seed of each triangle
+    region_ids: Vec<usize, A>,
+    region_seeds: Vec<RegionSeed, A>,
+    bump: A,
+}
+
+impl<A: Allocator + Copy> Refiner<A> {
+    fn new(
+        points: &[f64],
+        segments: &[usize],
+        holes: &[f64],
+        regions: &[RegionSeed],
+        min_angle: f64,
+        max_area: f64,
+        bump: A,
+    ) -> Self {
+        let (mesh, ghost, region_ids) =
+            constrained_delaunay_with_seeds(points, segments, holes, regions, bump);
+        let mut new_ids = std::vec::from_elem_in(INVALID_IND, mesh.len(), bump);
+        let mut n_triangles = 0;
+        for (i, &is_ghost) in ghost.iter().enumerate() {
+            if !is_ghost {
+                new_ids[i] = n_triangles;
+                n_triangles += 1;
+            }
+        }
+
+        let mut refiner = Self {
+            points: points.to_vec_in(bump),
+            n_input_points: points.len() >> 1,
+            triangles: Vec::with_capacity_in(n_triangles, bump),
+            neighbors: Vec::with_capacity_in(n_triangles, bump),
+            edge_segments: Vec::with_capacity_in(n_triangles, bump),
+            segments: Vec::new_in(bump),
+            vertex_segments: std::vec::from_elem_in(INVALID_IND, points.len() >> 1, bump),
+            stamps: std::vec::from_elem_in(0, n_triangles, bump),
+            stamp: 0,
+            sin2_min_angle: min_angle.to_radians().sin().powi(2),
+            max_area,
+            region_ids: Vec::with_capacity_in(n_triangles, bump),
+            region_seeds: regions.to_vec_in(bump),
+            bump,
+        };
+        refiner
+            .segments
+            .extend(segments.chunks(2).map(|seg| [seg[0], seg[1]]));
+        for (tid, tri) in mesh.iter().enumerate() {
+            if ghost[tid] {
+                continue;
+            }
+            let data = &tri.data;
+            let mut neighbors = [INVALID_IND; 3];
+            let mut edge_segments = [INVALID_IND; 3];
+            for i in 0..3 {
+                neighbors[i] = new_ids[tri.nei[i].tri];
+                if data[i + 3] != INVALID_IND {
+                    edge_segments[i] = data[i + 3] >> 1;
+                } else if neighbors[i] == INVALID_IND {
+                    // the boundary of the convex hull is refined as a segment
+                    edge_segments[i] = refiner.segments.len();
+                    refiner
+                        .segments
+                        .push([data[(i + 1) % 3], data[(i + 2) % 3]]);
+                }
+            }
+            refiner.triangles.push([data[0], data[1], data[2]]);
+            refiner.neighbors.push(neighbors);
+            refiner.edge_segments.push(edge_segments);
+            refiner.region_ids.push(region_ids[tid]);
+        }
+        refiner
+    }
+
+    #[inline(always)]
+    fn edge(&self, tid: usize, i: usize) -> [usize; 2] {
+        let tri = &self.triangles[tid];
+        [tri[(i + 1) % 3], tri[(i + 2) % 3]]
+    }
+
+    /// Whether `p` lies in the diametral circle of the edge.
+    #[inline(always)]
+    fn encroaches(&self, p: &[f64], [a, b]: [usize; 2]) -> bool {
+        let pa = point(&self.points, a);
+        let pb = point(&self.points, b);
+        (pa[0] - p[0]) * (pb[0] - p[0]) + (pa[1] - p[1]) * (pb[1] - p[1]) < 0.0
+    }
+
+    fn is_bad(&self, tid: usize) -> bool {
+        let tri = &self.triangles[tid];
+        let pa = point(&self.points, tri[0]);
+        let pb = point(&self.points, tri[1]);
+        let pc = point(&self.points, tri[2]);
+        let area2 = (pb[0] - pa[0]) * (pc[1] - pa[1]) - (pb[1] - pa[1]) * (pc[0] - pa[0]);
+        let max_area = self
+            .region_seeds
+            .get(self.region_ids[tid])
+            .map_or(self.max_area, |region| region.max_area.min(self.max_area));
+        if area2 * 0.5 > max_area {
+            return true;
+        }
+        let lens = [dist2(pb, pc), dist2(pc, pa), dist2(pa, pb)];
+        let shortest = (0..3).min_by(|&i, &j| lens[i].total_cmp(&lens[j])).unwrap();
+        // the sine of the smallest angle is the shortest edge over the diameter
+        let sin2 = lens[shortest] * area2 * area2 / (lens[0] * lens[1] * lens[2]);
+        if sin2 >= self.sin2_min_angle {
+            return false;
+        }
+        // Splitting a triangle whose shortest edge joins two segments at the
+        // same distance from their common input vertex never terminates when
+        // the segments form a small angle.
+        let [v1, v2] = self.edge(tid, shortest);
+        let (s1, s2) = (self.vertex_segments[v1], self.vertex_segments[v2]);
+        if s1 == INVALID_IND || s2 == INVALID_IND || s1 == s2 {
+            return true;
+        }
+        let [a1, b1] = self.segments[s1];
+        let [a2, b2] = self.segments[s2];
+        let apex = if a1 == a2 || a1 == b2 {
+            a1
+        } else if b1 == a2 || b1 == b2 {
+            b1
+        } else {
+            return true;
+        };
+        let papex = point(&self.points, apex);
+        let d1 = dist2(papex, point(&self.points, v1));
+        let d2 = dist2(papex, point(&self.points, v2));
+        (d1 - d2).abs() > 1e-8 * d1.max(d2)
+    }
+
+    fn circumcenter(&self, tid: usize) -> [f64; 2] {
+        let tri = &self.triangles[tid];
+        let pa = point(&self.points, tri[0]);
+        let pb = point(&self.points, tri[1]);
+        let pc = point(&self.points, tri[2]);
+        let (bx, by) = (pb[0] - pa[0], pb[1] - pa[1]);
+        let (cx, cy) = (pc[0] - pa[0], pc[1] - pa[1]);
+        let b2 = bx * bx + by * by;
+        let c2 = cx * cx + cy * cy;
+        let d = 2.0 * (bx * cy - by * cx);
+        [
+            pa[0] + (cy * b2 - by * c2) / d,
+            pa[1] + (bx * c2 - cx * b2) / d,
+        ]
+    }
+
+    /// Walk from `tid` towards `p` along the line from the centroid of `tid`.
+    /// Returns the triangle containing `p`, or the edge of the segment blocking
+    /// the way.
+    fn locate(&self, mut tid: usize, p: &[f64]) -> Result<usize, (usize, usize)> {
+        let tri = &self.triangles[tid];
+        let start = [0, 1].map(|k| {
+            (point(&self.points, tri[0])[k]
+                + point(&self.points, tri[1])[k]
+                + point(&self.points, tri[2])[k])
+                / 3.0
+        });
+        let mut prev = INVALID_IND;
+        loop {
+            let mut exit = None;
+            for i in 0..3 {
+                let [a, b] = self.edge(tid, i);
+                let pa = point(&self.points, a);
+                let pb = point(&self.points, b);
+                if predicates::orient2d(pa, pb, p, self.bump) >= 0.0 {
+                    continue;
+                }
+                if exit.is_none() {
+                    exit = Some(i);
+                }
+                if self.neighbors[tid][i] != prev
+                    && predicates::orient2d(&start, p, pa, self.bump)
+                        * predicates::orient2d(&start, p, pb, self.bump)
+                        <= 0.0
+                {
+                    exit = Some(i);
+                    break;
+                }
+            }
+            let Some(i) = exit else {
+                return Ok(tid);
+            };
+            if self.edge_segments[tid][i] != INVALID_IND {
+                return Err((tid, i));
+            }
+            prev = tid;
+            tid = self.neighbors[tid][i];
+        }
+    }
+
+    /// Collect the triangles whose circumcircles contain `p` reachable from
+    /// `seeds` without crossing a segment other than the edge being split.
+    /// Returns the cavity and its boundary edges.
+    fn cavity(
+        &mut self,
+        seeds: &[usize],
+        p: &[f64],
+        split: Option<(usize, [usize; 2])>,
+    ) -> (Vec<usize, A>, Vec<(usize, usize), A>) {
+        self.stamp += 1;
+        let mut cavity = Vec::new_in(self.bump);
+        for &tid in seeds {
+            self.stamps[tid] = self.stamp;
+            cavity.push(tid);
+        }
+        let mut boundary = Vec::new_in(self.bump);
+        let mut idx = 0;
+        while idx < cavity.len() {
+            let tid = cavity[idx];
+            idx += 1;
+            for i in 0..3 {
+                let nei = self.neighbors[tid][i];
+                if nei != INVALID_IND && self.stamps[nei] == self.stamp {
+                    continue;
+                }
+                if let Some((_, [a, b])) = split {
+                    let [va, vb] = self.edge(tid, i);
+                    if (va == a && vb == b) || (va == b && vb == a) {
+                        // the edge being split lies on the boundary of the domain
+                        continue;
+                    }
+                }
+                if nei == INVALID_IND || self.edge_segments[tid][i] != INVALID_IND {
+                    boundary.push((tid, i));
+                    continue;
+                }
+                let tri = &self.triangles[nei];
+                if predicates::incircle(
+                    point(&self.points, tri[0]),
+                    point(&self.points, tri[1]),
+                    point(&self.points, tri[2]),
+                    p,
+                    self.bump,
+                ) > 0.0
+                {
+                    self.stamps[nei] = self.stamp;
+                    cavity.push(nei);
+                } else {
+                    boundary.push((tid, i));
+                }
+            }
+        }
+        (cavity, boundary)
+    }
+
+    /// Replace the cavity by the triangles connecting `vid` to its boundary,
+    /// returns the new triangles.
+    fn fill_cavity(
+        &mut self,
+        cavity: &[usize],
+        boundary: &[(usize, usize)],
+        vid: usize,
+        split: Option<(usize, [usize; 2])>,
+    ) -> Vec<usize, A> {
+        let mut fan = Vec::with_capacity_in(boundary.len(), self.bump);
+        for &(tid, i) in boundary {
+            let [a, b] = self.edge(tid, i);
+            fan.push((
+                [vid, a, b],
+                self.neighbors[tid][i],
+                self.edge_segments[tid][i],
+                self.region_ids[tid],
+            ));
+        }
+        let mut new_triangles = Vec::with_capacity_in(fan.len(), self.bump);
+        for k in 0..fan.len() {
+            if k < cavity.len() {
+                new_triangles.push(cavity[k]);
+            } else {
+                new_triangles.push(self.triangles.len());
+                self.triangles.push([INVALID_IND; 3]);
+                self.neighbors.push([INVALID_IND; 3]);
+                self.edge_segments.push([INVALID_IND; 3]);
+                self.region_ids.push(INVALID_IND);
+                self.stamps.push(0);
+            }
+        }
+
+        for (&tid, &(tri, nei, seg, region)) in new_triangles.iter().zip(fan.iter()) {
+            self.triangles[tid] = tri;
+            self.region_ids[tid] = region;
+            self.neighbors[tid] = [nei, INVALID_IND, INVALID_IND];
+            self.edge_segments[tid] = [seg, INVALID_IND, INVALID_IND];
+            if nei != INVALID_IND {
+                let outer = &self.triangles[nei];
+                let j = (0..3).find(|&j| outer[j] != tri[1] && outer[j] != tri[2]);
+                self.neighbors[nei][j.unwrap()] = tid;
+            }
+        }
+        for (&tid, &([_, a, b], _, _, _)) in new_triangles.iter().zip(fan.iter()) {
+            // the edge from `vid` to `b` is shared with the triangle starting at `b`
+            if let Some(l) = fan.iter().position(|f| f.0[1] == b) {
+                let other = new_triangles[l];
+                self.neighbors[tid][1] = other;
+                self.neighbors[other][2] = tid;
+            }
+            if let Some((seg, ends)) = split {
+                if ends.contains(&b) {
+                    self.edge_segments[tid][1] = seg;
+                }
+                if ends.contains(&a) {
+                    self.edge_segments[tid][2] = seg;
+                }
+            }
+        }
+        new_triangles
+    }
+
+    /// Insert the point `p` into the cavity, returns the new triangles.
+    fn insert(
+        &mut self,
+        p: [f64; 2],
+        cavity: &[usize],
+        boundary: &[(usize, usize)],
+        split: Option<(usize, [usize; 2])>,
+    ) -> Vec<usize, A> {
+        let vid = self.points.len() >> 1;
+        self.points.extend(p);
+        self.vertex_segments
+            .push(split.map_or(INVALID_IND, |(seg, _)| seg));
+        self.fill_cavity(cavity, boundary, vid, split)
+    }
+
+    /// The point splitting the segment edge `[a, b]`. An edge with one input
+    /// vertex is split on a circle centered at this vertex whose radius is a
+    /// power of two, so that segments sharing the vertex are split
+    /// consistently.
+    fn split_point(&self, a: usize, b: usize) -> [f64; 2] {
+        let pa = point(&self.points, a);
+        let pb = point(&self.points, b);
+        let (a_input, b_input) = (a < self.n_input_points, b < self.n_input_points);
+        if a_input == b_input {
+            return [(pa[0] + pb[0]) * 0.5, (pa[1] + pb[1]) * 0.5];
+        }
+        let (po, pq) = if a_input { (pa, pb) } else { (pb, pa) };
+        let len = dist2(po, pq).sqrt();
+        let ratio = (len * 0.5).log2().round().exp2() / len;
+        [
+            po[0] + (pq[0] - po[0]) * ratio,
+            po[1] + (pq[1] - po[1]) * ratio,
+        ]
+    }
+
+    /// Split the segment edge `edge` of `tid` if it still exists, returns the
+    /// new triangles.
+    fn split_segment(&mut self, tid: usize, edge: [usize; 2]) -> Option<Vec<usize, A>> {
+        let i = (0..3).find(|&i| self.edge(tid, i) == edge)?;
+        let seg = self.edge_segments[tid][i];
+        if seg == INVALID_IND {
+            return None;
+        }
+        let p = self.split_point(edge[0], edge[1]);
+        if p == point(&self.points, edge[0]) || p == point(&self.points, edge[1]) {
+            // too short to be split
+            return None;
+        }
+        let nei = self.neighbors[tid][i];
+        let seeds = if nei == INVALID_IND {
+            [tid].to_vec_in(self.bump)
+        } else {
+            [tid, nei].to_vec_in(self.bump)
+        };
+        let split = Some((seg, edge));
+        let (cavity, boundary) = self.cavity(&seeds, &p, split);
+        Some(self.insert(p, &cavity, &boundary, split))
+    }
+
+    /// Queue the encroached segments and the bad quality triangle of `tid`.
+    fn check(
+        &self,
+        tid: usize,
+        encroached: &mut VecDeque<(usize, [usize; 2])>,
+        bad: &mut VecDeque<(usize, [usize; 3])>,
+    ) {
+        for i in 0..3 {
+            if self.edge_segments[tid][i] != INVALID_IND
+                && self.encroaches(
+                    point(&self.points, self.triangles[tid][i]),
+                    self.edge(tid, i),
+                )
+            {
+                encroached.push_back((tid, self.edge(tid, i)));
+            }
+        }
+        if self.is_bad(tid) {
+            bad.push_back((tid, self.triangles[tid]));
+        }
+    }
+
+    fn refine(&mut self) {
+        let mut encroached = VecDeque::new();
+        let mut bad = VecDeque::new();
+        for tid in 0..self.triangles.len() {
+            self.check(tid, &mut encroached, &mut bad);
+        }
+        loop {
+            // all encroached segments are split before any triangle
+            while let Some((tid, edge)) = encroached.pop_front() {
+                let still_encroached = (0..3).any(|i| {
+                    self.edge(tid, i) == edge
+                        && self.encroaches(point(&self.points, self.triangles[tid][i]), edge)
+                });
+                if !still_encroached {
+                    continue;
+                }
+                if let Some(new_triangles) = self.split_segment(tid, edge) {
+                    for t in new_triangles {
+                        self.check(t, &mut encroached, &mut bad);
+                    }
+                }
+            }
+
+            let Some((tid, tri)) = bad.pop_front() else {
+                break;
+            };
+            if self.triangles[tid] != tri {
+                continue;
+            }
+            let center = self.circumcenter(tid);
+            let mut to_split = Vec::new_in(self.bump);
+            match self.locate(tid, &center) {
+                Err((t, i)) => to_split.push((t, self.edge(t, i))),
+                Ok(t) => {
+                    let (cavity, boundary) = self.cavity(&[t], &center, None);
+                    // the circumcenter is rejected if it encroaches segments
+                    to_split.extend(
+                        boundary
+                            .iter()
+                            .filter(|&&(t, i)| self.edge_segments[t][i] != INVALID_IND)
+                            .map(|&(t, i)| (t, self.edge(t, i)))
+                            .filter(|&(_, edge)| self.encroaches(&center, edge)),
+                    );
+                    if to_split.is_empty() {
+                        for t in self.insert(center, &cavity, &boundary, None) {
+                            self.check(t, &mut encroached, &mut bad);
+                        }
+                        continue;
+                    }
+                }
+            }
+            let mut changed = false;
+            for (t, edge) in to_split {
+                if let Some(new_triangles) = self.split_segment(t, edge) {
+                    changed = true;
+                    for t in new_triangles {
+                        self.check(t, &mut encroached, &mut bad);
+                    }
+                }
+            }
+            if changed {
+                bad.push_back((tid, tri));
+            }
+        }
+    }
+}
+
+/// Triangulate the domain like [`triangulate_regions`](super::triangulate_regions),
+/// then insert Steiner points until no angle is smaller than `min_angle`
+/// degrees and no triangle is larger than `max_area` or than the maximum area
+/// of its region. Encroached segments are split first, and the circumcenters
+/// of bad triangles are inserted otherwise. Termination is guaranteed for
+/// `min_angle` up to about 20.7 degrees.
+///
+/// Returns the new points, numbered after the input points, the triangles and
+/// the attribute of the region containing each triangle.
+pub fn triangulate_refine<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    holes: &[f64],
+    regions: &[RegionSeed],
+    min_angle: f64,
+    max_area: f64,
+    bump: A,
+) -> (Vec<f64, A>, Vec<usize, A>, Vec<f64, A>) {
+    let mut refiner = Refiner::new(points, segments, holes, regions, min_angle, max_area, bump);
+    refiner.refine();
+    let mut new_points = Vec::new_in(bump);
+    new_points.extend_from_slice(&refiner.points[points.len()..]);
+    let mut triangles = Vec::with_capacity_in(refiner.triangles.len() * 3, bump);
+    triangles.extend(refiner.triangles.iter().flatten());
+    let mut attributes = Vec::with_capacity_in(refiner.triangles.len(), bump);
+    attributes.extend(
+        refiner
+            .region_ids
+            .iter()
+            .map(|&k| regions.get(k).map_or(0.0, |region| region.attribute)),
+    );
+    (new_points, triangles, attributes)
+}
@@ -0,0 +1,483 @@
+use std::io::{self, BufRead, Write};
+
+use hashbrown::HashMap;
+
+use super::TetMesh;
+
+/// The faces of a positively oriented tetrahedron opposite to each vertex,
+/// oriented outwards.
+pub(super) const OUTER_FACES: [[usize; 3]; 4] = [[1, 2, 3], [0, 3, 2], [0, 1, 3], [0, 2, 1]];
+
+/// The VTK cell type of a linear tetrahedron.
+const VTK_TETRA: usize = 10;
+
+#[inline(always)]
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+/// A number of values computed from the counts of a file, which must not
+/// overflow.
+#[inline(always)]
+fn checked_count(count: Option<usize>) -> io::Result<usize> {
+    count.ok_or_else(|| invalid_data("too many values"))
+}
+
+/// The whitespace separated tokens of a file, without the comments starting
+/// with `#`, read line by line.
+struct Tokens<R> {
+    lines: io::Lines<R>,
+    line: std::vec::IntoIter<String>,
+}
+
+impl<R: BufRead> Tokens<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            lines: reader.lines(),
+            line: Vec::new().into_iter(),
+        }
+    }
+
+    /// The next token, `None` at the end of the file.
+    fn next_token(&mut self) -> io::Result<Option<String>> {
+        loop {
+            if let Some(token) = self.line.next() {
+                return Ok(Some(token));
+            }
+            let Some(line) = self.lines.next() else {
+                return Ok(None);
+            };
+            let line = line?;
+            let line = line.split('#').next().unwrap();
+            self.line = Vec::from_iter(line.split_whitespace().map(str::to_string)).into_iter();
+        }
+    }
+
+    fn next_str(&mut self) -> io::Result<String> {
+        self.next_token()?
+            .ok_or_else(|| invalid_data("unexpected end of file"))
+    }
+
+    fn next_f64(&mut self) -> io::Result<f64> {
+        self.next_str()?
+            .parse()
+            .map_err(|_| invalid_data("invalid number"))
+    }
+
+    fn next_usize(&mut self) -> io::Result<usize> {
+        self.next_str()?
+            .parse()
+            .map_err(|_| invalid_data("invalid index"))
+    }
+
+    /// A mark written as a number, which must be a non-negative integer.
+    fn next_mark(&mut self) -> io::Result<usize> {
+        let mark = self.next_f64()?;
+        if mark.fract() != 0.0 || !(0.0..(usize::MAX as f64)).contains(&mark) {
+            return Err(invalid_data("invalid mark"));
+        }
+        Ok(mark as usize)
+    }
+
+    /// An index counted from `base`.
+    fn next_index(&mut self, base: usize) -> io::Result<usize> {
+        self.next_usize()?
+            .checked_sub(base)
+            .ok_or_else(|| invalid_data("invalid index"))
+    }
+
+    fn skip(&mut self, n: usize) -> io::Result<()> {
+        for _ in 0..n {
+            self.next_str()?;
+        }
+        Ok(())
+    }
+}
+
+/// A tetrahedral mesh as flat arrays, to be written to or read from the mesh
+/// files of other tools.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TetMeshData {
+    /// The coordinates of the points.
+    pub points: Vec<f64>,
+    /// The vertices of the tetrahedra, four for each.
+    pub tets: Vec<usize>,
+    /// An optional mark of each tetrahedron, e.g. the region it belongs to.
+    pub marks: Option<Vec<usize>>,
+}
+
+impl TetMeshData {
+    /// The tetrahedra of `mesh` without the hull tetrahedra, and their
+    /// `marks` if given, one for each tetrahedron of `mesh`. The points
+    /// inserted into `mesh` are numbered after the input points, without the
+    /// ghost vertex between them. Fails if the number of marks does not match.
+    pub fn new(mesh: &TetMesh, marks: Option<&[usize]>) -> io::Result<Self> {
+        if marks.is_some_and(|marks| marks.len() != mesh.tets.len()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the marks do not match the tetrahedra",
+            ));
+        }
+        let points = Vec::from_iter(mesh.vertices().flat_map(|vid| mesh.point(vid).to_vec()));
+        let kept = Vec::from_iter((0..mesh.tets.len()).filter(|&tid| !mesh.is_hull_tet(tid)));
+        let tets = Vec::from_iter(
+            kept.iter()
+                .flat_map(|&tid| mesh.tets[tid].data.map(|vid| mesh.output_index(vid))),
+        );
+        Ok(Self {
+            points,
+            tets,
+            marks: marks.map(|marks| Vec::from_iter(kept.iter().map(|&tid| marks[tid]))),
+        })
+    }
+
+    /// The faces of the tetrahedra which are not shared with another
+    /// tetrahedron, oriented outwards for positively oriented tetrahedra.
+    pub fn boundary_faces(&self) -> Vec<usize> {
+        let mut count: HashMap<[usize; 3], usize> = HashMap::new();
+        let faces = Vec::from_iter(self.tets.chunks(4).flat_map(|tet| {
+            OUTER_FACES.map(|face| {
+                let face = face.map(|i| tet[i]);
+                let mut key = face;
+                key.sort_unstable();
+                *count.entry(key).or_default() += 1;
+                (key, face)
+            })
+        }));
+        Vec::from_iter(
+            faces
+                .into_iter()
+                .filter(|(key, _)| count[key] == 1)
+                .flat_map(|(_, face)| face),
+        )
+    }
+
+    /// Write the mesh in the Medit `.mesh` format, with the marks as the
+    /// references of the tetrahedra.
+    pub fn write_medit<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.check()?;
+        writeln!(writer, "MeshVersionFormatted 2\nDimension 3\n")?;
+        writeln!(writer, "Vertices\n{}", self.points.len() / 3)?;
+        for p in self.points.chunks(3) {
+            writeln!(writer, "{} {} {} 0", p[0], p[1], p[2])?;
+        }
+        writeln!(writer, "\nTetrahedra\n{}", self.tets.len() / 4)?;
+        for (tid, tet) in self.tets.chunks(4).enumerate() {
+            let mark = self.marks.as_ref().map_or(0, |marks| marks[tid]);
+            writeln!(
+                writer,
+                "{} {} {} {} {}",
+                tet[0] + 1,
+                tet[1] + 1,
+                tet[2] + 1,
+                tet[3] + 1,
+                mark
+            )?;
+        }
+        writeln!(writer, "\nEnd")
+    }
+
+    /// Read the vertices and the tetrahedra of a Medit `.mesh` file, with
+    /// their references as the marks, skipping the other elements.
+    pub fn read_medit<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut tokens = Tokens::new(reader);
+        let mut mesh = Self::default();
+        while let Some(keyword) = tokens.next_token()? {
+            // the number of values of each element of the other sections
+            let n_values = match keyword.to_lowercase().as_str() {
+                "end" => break,
+                "meshversionformatted" => {
+                    tokens.next_usize()?;
+                    continue;
+                }
+                "dimension" => {
+                    if tokens.next_usize()? != 3 {
+                        return Err(invalid_data("only 3D meshes are supported"));
+                    }
+                    continue;
+                }
+                "vertices" => {
+                    let n = tokens.next_usize()?;
+                    for _ in 0..n {
+                        for _ in 0..3 {
+                            mesh.points.push(tokens.next_f64()?);
+                        }
+                        tokens.next_str()?;
+                    }
+                    continue;
+                }
+                "tetrahedra" => {
+                    let n = tokens.next_usize()?;
+                    let mut marks = Vec::new();
+                    for _ in 0..n {
+                        for _ in 0..4 {
+                            mesh.tets.push(tokens.next_index(1)?);
+                        }
+                        marks.push(tokens.next_mark()?);
+                    }
+                    mesh.marks = Some(marks);
+                    continue;
+                }
+                "corners" | "ridges" | "requiredvertices" | "requirededges"
+                | "requiredtriangles" => 1,
+                "edges" => 3,
+                "triangles" | "normals" | "tangents" => 4,
+                "quadrilaterals" => 5,
+                "hexahedra" => 9,
+                _ => return Err(invalid_data("unsupported Medit keyword")),
+            };
+            let n = tokens.next_usize()?;
+            tokens.skip(checked_count(n.checked_mul(n_values))?)?;
+        }
+        mesh.check()?;
+        Ok(mesh)
+    }
+
+    /// Write the mesh in the TetGen formats, the points to a `.node` file, the
+    /// tetrahedra to an `.ele` file, with the marks as their region
+    /// attributes, and the boundary faces to a `.face` file. The indices start
+    /// at zero.
+    pub fn write_tetgen<W: Write>(&self, mut node: W, mut ele: W, mut face: W) -> io::Result<()> {
+        self.check()?;
+        writeln!(node, "{} 3 0 0", self.points.len() / 3)?;
+        for (vid, p) in self.points.chunks(3).enumerate() {
+            writeln!(node, "{} {} {} {}", vid, p[0], p[1], p[2])?;
+        }
+        let n_attributes = self.marks.is_some() as usize;
+        writeln!(ele, "{} 4 {}", self.tets.len() / 4, n_attributes)?;
+        for (tid, tet) in self.tets.chunks(4).enumerate() {
+            write!(ele, "{} {} {} {} {}", tid, tet[0], tet[1], tet[2], tet[3])?;
+            match &self.marks {
+                Some(marks) => writeln!(ele, " {}", marks[tid])?,
+                None => writeln!(ele)?,
+            }
+        }
+        let faces = self.boundary_faces();
+        writeln!(face, "{} 0", faces.len() / 3)?;
+        for (fid, f) in faces.chunks(3).enumerate() {
+            writeln!(face, "{} {} {} {}", fid, f[0], f[1], f[2])?;
+        }
+        Ok(())
+    }
+
+    /// Read the points of a TetGen `.node` file and the tetrahedra of an
+    /// `.ele` file, with their first region attribute as the marks, which must
+    /// be non-negative integers. The indices start at the index of the first
+    /// point.
+    pub fn read_tetgen<R: BufRead>(node: R, ele: R) -> io::Result<Self> {
+        let mut tokens = Tokens::new(node);
+        let n_points = tokens.next_usize()?;
+        if tokens.next_usize()? != 3 {
+            return Err(invalid_data("only 3D meshes are supported"));
+        }
+        let n_attributes = tokens.next_usize()?;
+        let n_markers = tokens.next_usize()?;
+        let mut mesh = Self::default();
+        let mut base = 0;
+        for vid in 0..n_points {
+            let idx = tokens.next_usize()?;
+            if vid == 0 {
+                base = idx;
+            }
+            for _ in 0..3 {
+                mesh.points.push(tokens.next_f64()?);
+            }
+            tokens.skip(checked_count(n_attributes.checked_add(n_markers))?)?;
+        }
+
+        let mut tokens = Tokens::new(ele);
+        let n_tets = tokens.next_usize()?;
+        let n_nodes = tokens.next_usize()?;
+        if n_nodes != 4 && n_nodes != 10 {
+            return Err(invalid_data("invalid number of nodes per tetrahedron"));
+        }
+        let n_attributes = tokens.next_usize()?;
+        let mut marks = Vec::new();
+        for _ in 0..n_tets {
+            tokens.next_usize()?;
+            for _ in 0..4 {
+                mesh.tets.push(tokens.next_index(base)?);
+            }
+            // the second order nodes are dropped
+            tokens.skip(n_nodes - 4)?;
+            if n_attributes > 0 {
+                marks.push(tokens.next_mark()?);
+                tokens.skip(n_attributes - 1)?;
+            }
+        }
+        if n_attributes > 0 {
+            mesh.marks = Some(marks);
+        }
+        mesh.check()?;
+        Ok(mesh)
+    }
+
+    /// Write the mesh as an ASCII legacy VTK unstructured grid, with the marks
+    /// as the scalar `mark` of the cells.
+    pub fn write_vtk<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.check()?;
+        let n_tets = self.tets.len() / 4;
+        writeln!(
+            writer,
+            "# vtk DataFile Version 3.0\ngpf tetrahedral mesh\nASCII"
+        )?;
+        writeln!(writer, "DATASET UNSTRUCTURED_GRID")?;
+        writeln!(writer, "POINTS {} double", self.points.len() / 3)?;
+        for p in self.points.chunks(3) {
+            writeln!(writer, "{} {} {}", p[0], p[1], p[2])?;
+        }
+        writeln!(writer, "CELLS {} {}", n_tets, n_tets * 5)?;
+        for tet in self.tets.chunks(4) {
+            writeln!(writer, "4 {} {} {} {}", tet[0], tet[1], tet[2], tet[3])?;
+        }
+        writeln!(writer, "CELL_TYPES {}", n_tets)?;
+        for _ in 0..n_tets {
+            writeln!(writer, "{}", VTK_TETRA)?;
+        }
+        if let Some(marks) = &self.marks {
+            writeln!(writer, "CELL_DATA {}", n_tets)?;
+            writeln!(writer, "SCALARS mark unsigned_long 1\nLOOKUP_TABLE default")?;
+            for mark in marks {
+                writeln!(writer, "{}", mark)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the points and the tetrahedra of an ASCII legacy VTK unstructured
+    /// grid, with the first scalar of the cells as the marks, which must be
+    /// non-negative integers. The cells which are not linear tetrahedra are
+    /// skipped.
+    pub fn read_vtk<R: BufRead>(mut reader: R) -> io::Result<Self> {
+        // the version and the title are free text
+        let mut header = String::new();
+        for _ in 0..2 {
+            header.clear();
+            reader.read_line(&mut header)?;
+        }
+        let mut tokens = Tokens::new(reader);
+        if tokens.next_str()? != "ASCII" {
+            return Err(invalid_data("only ASCII VTK files are supported"));
+        }
+        let mut mesh = Self::default();
+        let mut cells = Vec::new();
+        let mut cell_types = Vec::new();
+        // the number of values of the current point or cell data
+        let mut n_data = 0;
+        let mut in_cell_data = false;
+        while let Some(keyword) = tokens.next_token()? {
+            match keyword.as_str() {
+                "DATASET" => {
+                    if tokens.next_str()? != "UNSTRUCTURED_GRID" {
+                        return Err(invalid_data("only unstructured grids are supported"));
+                    }
+                }
+                "POINTS" => {
+                    let n = tokens.next_usize()?;
+                    tokens.next_str()?;
+                    for _ in 0..n {
+                        for _ in 0..3 {
+                            mesh.points.push(tokens.next_f64()?);
+                        }
+                    }
+                }
+                "CELLS" => {
+                    tokens.next_usize()?;
+                    let size = tokens.next_usize()?;
+                    for _ in 0..size {
+                        cells.push(tokens.next_usize()?);
+                    }
+                }
+                "CELL_TYPES" => {
+                    let n = tokens.next_usize()?;
+                    for _ in 0..n {
+                        cell_types.push(tokens.next_usize()?);
+                    }
+                }
+                "POINT_DATA" | "CELL_DATA" => {
+                    n_data = tokens.next_usize()?;
+                    in_cell_data = keyword == "CELL_DATA";
+                }
+                "SCALARS" => {
+                    tokens.next_str()?;
+                    tokens.next_str()?;
+                    // the number of components is optional
+                    let mut next = tokens.next_str()?;
+                    let mut n_components = 1;
+                    if next != "LOOKUP_TABLE" {
+                        n_components = next
+                            .parse()
+                            .map_err(|_| invalid_data("invalid number of components"))?;
+                        next = tokens.next_str()?;
+                    }
+                    if next != "LOOKUP_TABLE" {
+                        return Err(invalid_data("missing lookup table"));
+                    }
+                    if n_components == 0 {
+                        return Err(invalid_data("invalid number of components"));
+                    }
+                    tokens.next_str()?;
+                    if in_cell_data && mesh.marks.is_none() {
+                        let mut marks = Vec::new();
+                        for _ in 0..n_data {
+                            marks.push(tokens.next_mark()?);
+                            tokens.skip(n_components - 1)?;
+                        }
+                        mesh.marks = Some(marks);
+                    } else {
+                        tokens.skip(checked_count(n_data.checked_mul(n_components))?)?;
+                    }
+                }
+                _ => return Err(invalid_data("unsupported VTK keyword")),
+            }
+        }
+
+        let mut marks = Vec::new();
+        let mut start = 0;
+        for (cid, &cell_type) in cell_types.iter().enumerate() {
+            let n = *cells
+                .get(start)
+                .ok_or_else(|| invalid_data("missing cells"))?;
+            let cell = cells
+                .get((start + 1)..)
+                .and_then(|rest| rest.get(..n))
+                .ok_or_else(|| invalid_data("missing cells"))?;
+            start += n + 1;
+            if cell_type != VTK_TETRA {
+                continue;
+            }
+            if n != 4 {
+                return Err(invalid_data("invalid tetrahedron"));
+            }
+            mesh.tets.extend_from_slice(cell);
+            if let Some(cell_marks) = &mesh.marks {
+                marks.push(
+                    *cell_marks
+                        .get(cid)
+                        .ok_or_else(|| invalid_data("missing cell data"))?,
+                );
+            }
+        }
+        if mesh.marks.is_some() {
+            mesh.marks = Some(marks);
+        }
+        mesh.check()?;
+        Ok(mesh)
+    }
+
+    /// Check that the tetrahedra and the marks match the points.
+    fn check(&self) -> io::Result<()> {
+        let n_points = self.points.len() / 3;
+        if self.tets.iter().any(|&vid| vid >= n_points) {
+            return Err(invalid_data("index out of range"));
+        }
+        if self
+            .marks
+            .as_ref()
+            .is_some_and(|marks| marks.len() != self.tets.len() / 4)
+        {
+            return Err(invalid_data("the marks do not match the tetrahedra"));
+        }
+        Ok(())
+    }
+}
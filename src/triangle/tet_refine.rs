@@ -0,0 +1,413 @@
+use std::collections::VecDeque;
+
+use bumpalo::Bump;
+use hashbrown::HashSet;
+
+use crate::math::{cross, dot, sub};
+
+use super::{insert_vertex_bw, locate_dt, tetrahedralize, LocateResult, TetMesh, TriFace};
+
+#[inline(always)]
+fn point(mesh: &TetMesh, idx: usize) -> [f64; 3] {
+    let p = mesh.point(idx);
+    [p[0], p[1], p[2]]
+}
+
+#[inline(always)]
+fn vector(pa: &[f64; 3], pb: &[f64; 3]) -> [f64; 3] {
+    let mut v = [0.0; 3];
+    sub(pb, pa, &mut v);
+    v
+}
+
+#[inline(always)]
+fn cross3(u: &[f64; 3], v: &[f64; 3]) -> [f64; 3] {
+    let mut n = [0.0; 3];
+    cross(u, v, &mut n);
+    n
+}
+
+/// The point to insert to improve a bad tetrahedron.
+struct SplitPoint {
+    point: [f64; 3],
+    /// Whether the tetrahedron is bad only because of its dihedral angles, or
+    /// nearly flat, so that it does not split the hull.
+    sliver: bool,
+}
+
+/// The circumcenter of the triangle `pa`, `pb`, `pc`, or its centroid if it is
+/// degenerate.
+fn circumcenter(pa: &[f64; 3], pb: &[f64; 3], pc: &[f64; 3]) -> [f64; 3] {
+    let (u, v) = (vector(pa, pb), vector(pa, pc));
+    let n = cross3(&u, &v);
+    let scale = 2.0 * dot(&n, &n);
+    if scale == 0.0 {
+        return [0, 1, 2].map(|i| (pa[i] + pb[i] + pc[i]) / 3.0);
+    }
+    let (cu, cv) = (cross3(&v, &n), cross3(&n, &u));
+    let (uu, vv) = (dot(&u, &u), dot(&v, &v));
+    [0, 1, 2].map(|i| pa[i] + (uu * cu[i] + vv * cv[i]) / scale)
+}
+
+/// Whether `p` lies in the ball of `center` through `q`.
+#[inline(always)]
+fn in_ball(center: &[f64; 3], q: &[f64; 3], p: &[f64; 3]) -> bool {
+    let (r, d) = (vector(center, q), vector(center, p));
+    dot(&d, &d) <= dot(&r, &r)
+}
+
+/// The center and the squared radius of the circumsphere of the tetrahedron
+/// `pa`, `pb`, `pc`, `pd`.
+fn circumsphere(pa: &[f64; 3], pb: &[f64; 3], pc: &[f64; 3], pd: &[f64; 3]) -> ([f64; 3], f64) {
+    let [u, v, w] = [pb, pc, pd].map(|p| vector(pa, p));
+    let det = dot(&u, &cross3(&v, &w));
+    let mut center = *pa;
+    for (e, n) in [
+        (u, cross3(&v, &w)),
+        (v, cross3(&w, &u)),
+        (w, cross3(&u, &v)),
+    ] {
+        let scale = dot(&e, &e) / (2.0 * det);
+        for i in 0..3 {
+            center[i] += n[i] * scale;
+        }
+    }
+    let r = vector(pa, &center);
+    (center, dot(&r, &r))
+}
+
+/// The hull tetrahedron whose face has `p` in its diametral ball, searching
+/// the hull faces of the tetrahedra whose circumspheres contain `p` around
+/// the tetrahedron `tid` containing it.
+fn encroached_hull_face(mesh: &TetMesh, p: &[f64; 3], tid: usize) -> Option<usize> {
+    let tet_point = |vid: usize| point(mesh, vid);
+    let mut visited: HashSet<usize> = HashSet::from([tid]);
+    let mut queue = vec![tid];
+    let mut idx = 0;
+    while idx < queue.len() {
+        let cur = queue[idx];
+        idx += 1;
+        for nei in &mesh.tets[cur].nei {
+            let nid = nei.tet;
+            if visited.contains(&nid) {
+                continue;
+            }
+            let data = &mesh.tets[nid].data;
+            if mesh.is_hull_tet(nid) {
+                let [pa, pb, pc] = [data[0], data[1], data[2]].map(tet_point);
+                if in_ball(&circumcenter(&pa, &pb, &pc), &pa, p) {
+                    return Some(nid);
+                }
+                continue;
+            }
+            let [pa, pb, pc, pd] = data.map(tet_point);
+            let (center, radius2) = circumsphere(&pa, &pb, &pc, &pd);
+            let d = vector(&center, p);
+            if dot(&d, &d) < radius2 {
+                visited.insert(nid);
+                queue.push(nid);
+            }
+        }
+    }
+    None
+}
+
+struct TetRefiner<'a> {
+    /// the mesh, whose deleted tetrahedra are kept until the end, so that the
+    /// queued tetrahedra keep their indices
+    mesh: TetMesh<'a>,
+    max_radius_edge_ratio: f64,
+    max_volume: f64,
+    cos_min_dihedral_angle: f64,
+    max_steiner_points: usize,
+    n_steiner_points: usize,
+    bump: Bump,
+}
+
+impl<'a> TetRefiner<'a> {
+    fn new(
+        points: &'a [f64],
+        max_radius_edge_ratio: f64,
+        max_volume: f64,
+        min_dihedral_angle: f64,
+        max_steiner_points: usize,
+    ) -> Self {
+        Self {
+            mesh: tetrahedralize(points),
+            max_radius_edge_ratio,
+            max_volume,
+            cos_min_dihedral_angle: min_dihedral_angle.to_radians().cos(),
+            max_steiner_points,
+            n_steiner_points: 0,
+            bump: Bump::new(),
+        }
+    }
+
+    #[inline(always)]
+    fn alive(&self, tid: usize) -> bool {
+        !self.mesh.tets[tid].deleted() && !self.mesh.is_hull_tet(tid)
+    }
+
+    /// The circumcenter of the tetrahedron, if it is bad.
+    fn split_point(&self, tid: usize) -> Option<SplitPoint> {
+        let data = &self.mesh.tets[tid].data;
+        let [pa, pb, pc, pd] = data.map(|vid| point(&self.mesh, vid));
+        let [u, v, w] = [&pb, &pc, &pd].map(|p| vector(&pa, p));
+        let volume = dot(&u, &cross3(&v, &w)) / 6.0;
+        let (center, radius2) = circumsphere(&pa, &pb, &pc, &pd);
+        if center.iter().any(|x| !x.is_finite()) {
+            return None;
+        }
+        let radius = radius2.sqrt();
+        let edges = [(pa, pb), (pa, pc), (pa, pd), (pb, pc), (pb, pd), (pc, pd)].map(|(p, q)| {
+            let e = vector(&p, &q);
+            dot(&e, &e).sqrt()
+        });
+        let min_edge = edges.into_iter().fold(f64::INFINITY, f64::min);
+        if radius > self.max_radius_edge_ratio * min_edge || volume > self.max_volume {
+            // e.g. a hull face and a split point rounded next to it, whose
+            // circumcenter would split the hull over and over
+            let max_edge = edges.into_iter().fold(0.0, f64::max);
+            return Some(SplitPoint {
+                point: center,
+                sliver: volume <= max_edge.powi(3) * 1e-12,
+            });
+        }
+
+        // the inward normals of the faces opposite to each vertex, the
+        // dihedral angle between two faces is the supplement of the angle
+        // between their normals
+        let normals = [
+            cross3(&vector(&pb, &pd), &vector(&pb, &pc)),
+            cross3(&vector(&pa, &pc), &vector(&pa, &pd)),
+            cross3(&vector(&pa, &pd), &vector(&pa, &pb)),
+            cross3(&vector(&pa, &pb), &vector(&pa, &pc)),
+        ];
+        for i in 0..4 {
+            for j in (i + 1)..4 {
+                let cos = -dot(&normals[i], &normals[j])
+                    / (dot(&normals[i], &normals[i]) * dot(&normals[j], &normals[j])).sqrt();
+                if cos > self.cos_min_dihedral_angle {
+                    return Some(SplitPoint {
+                        point: center,
+                        sliver: true,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// The vertices of the face of the hull tetrahedron `tid`.
+    #[inline(always)]
+    fn hull_face(&self, tid: usize) -> [[f64; 3]; 3] {
+        let data = &self.mesh.tets[tid].data;
+        [data[0], data[1], data[2]].map(|vid| point(&self.mesh, vid))
+    }
+
+    /// Whether the faces of the hull tetrahedra `tid` and `nid` lie in the same
+    /// plane, up to rounding.
+    fn coplanar(&self, tid: usize, nid: usize) -> bool {
+        let [m, n] = [tid, nid].map(|t| {
+            let [pa, pb, pc] = self.hull_face(t);
+            cross3(&vector(&pa, &pb), &vector(&pa, &pc))
+        });
+        let mn = dot(&m, &n);
+        mn > 0.0 && mn * mn >= dot(&m, &m) * dot(&n, &n) * (1.0 - 1e-12)
+    }
+
+    /// The point splitting the face of the hull tetrahedron `tid`, its
+    /// circumcenter if it lies in the face and the midpoint of its longest
+    /// edge otherwise. The midpoint of a ridge of the hull, an edge between
+    /// faces which are not coplanar, is taken instead if the point lies in its
+    /// diametral ball, searching the faces coplanar with `tid` whose
+    /// circumcircles contain the point.
+    fn hull_split_point(&self, tid: usize) -> [f64; 3] {
+        let [pa, pb, pc] = self.hull_face(tid);
+        let corners = [(pa, pb, pc), (pb, pc, pa), (pc, pa, pb)];
+        let acute = corners
+            .iter()
+            .all(|(p, q, r)| dot(&vector(p, q), &vector(p, r)) > 0.0);
+        let split = if acute {
+            circumcenter(&pa, &pb, &pc)
+        } else {
+            let (p, q, _) = corners
+                .into_iter()
+                .max_by(|(p, q, _), (r, s, _)| {
+                    let e = vector(p, q);
+                    let f = vector(r, s);
+                    dot(&e, &e).total_cmp(&dot(&f, &f))
+                })
+                .unwrap();
+            [0, 1, 2].map(|i| (p[i] + q[i]) * 0.5)
+        };
+
+        let mut visited: HashSet<usize> = HashSet::from([tid]);
+        let mut queue = vec![tid];
+        let mut idx = 0;
+        while idx < queue.len() {
+            let cur = queue[idx];
+            idx += 1;
+            let face = self.hull_face(cur);
+            // the neighbor across the edge opposite to each vertex
+            for i in 0..3 {
+                let nid = self.mesh.tets[cur].nei[i].tet;
+                if visited.contains(&nid) {
+                    continue;
+                }
+                if self.coplanar(cur, nid) {
+                    let [qa, qb, qc] = self.hull_face(nid);
+                    if in_ball(&circumcenter(&qa, &qb, &qc), &qa, &split) {
+                        visited.insert(nid);
+                        queue.push(nid);
+                    }
+                    continue;
+                }
+                let (p, q) = (face[(i + 1) % 3], face[(i + 2) % 3]);
+                let mid = [0, 1, 2].map(|k| (p[k] + q[k]) * 0.5);
+                if in_ball(&mid, &p, &split) {
+                    return mid;
+                }
+            }
+        }
+        split
+    }
+
+    /// Points between the circumcenter `center` of the sliver `tid` and its
+    /// centroid, which are inside its circumsphere and so remove it as well.
+    fn perturbed_split_points(&self, tid: usize, center: &[f64; 3]) -> [[f64; 3]; 3] {
+        let data = &self.mesh.tets[tid].data;
+        let corners = data.map(|vid| point(&self.mesh, vid));
+        let centroid = [0, 1, 2].map(|i| corners.iter().map(|p| p[i]).sum::<f64>() * 0.25);
+        [0.25, 0.5, 0.75].map(|t| [0, 1, 2].map(|i| center[i] + (centroid[i] - center[i]) * t))
+    }
+
+    /// Insert `p` searching from the tetrahedron `tid`. A circumcenter is
+    /// rejected, failing with the hull tetrahedron to split instead, if it is
+    /// outside the convex hull or encroaches a hull face. Fails with `None` if
+    /// `p` is a vertex already or if it is a hull split point outside the
+    /// convex hull.
+    fn insert(&mut self, p: [f64; 3], tid: usize, circumcenter: bool) -> Result<(), Option<usize>> {
+        let pid = self.mesh.push_point(&p);
+        self.bump.reset();
+        let mesh = &mut self.mesh;
+        let mut searchtet = TriFace::new(tid, 0);
+        let result = match locate_dt(mesh, pid, &mut searchtet, &self.bump) {
+            LocateResult::ONVERTEX => Err(None),
+            LocateResult::OUTSIDE => Err(circumcenter.then_some(searchtet.tet)),
+            _ => match circumcenter
+                .then(|| encroached_hull_face(mesh, &p, searchtet.tet))
+                .flatten()
+            {
+                Some(hull_tid) => Err(Some(hull_tid)),
+                None => {
+                    let mut searchtet = TriFace::new(tid, 0);
+                    insert_vertex_bw(mesh, pid, &mut searchtet, &self.bump);
+                    Ok(())
+                }
+            },
+        };
+        if result.is_ok() {
+            self.n_steiner_points += 1;
+        } else {
+            self.mesh.pop_point();
+        }
+        result
+    }
+
+    fn refine(&mut self) {
+        let mut bad = VecDeque::from_iter((0..self.mesh.tets.len()).filter(|&tid| self.alive(tid)));
+        while let Some(tid) = bad.pop_front() {
+            if self.n_steiner_points >= self.max_steiner_points {
+                break;
+            }
+            if !self.alive(tid) {
+                continue;
+            }
+            let Some(split) = self.split_point(tid) else {
+                continue;
+            };
+            let first_new = self.mesh.tets.len();
+            match self.insert(split.point, tid, true) {
+                Ok(()) => {}
+                // the circumcenter is outside the convex hull or too close to
+                // it, the hull face is split instead, unless the rounded split
+                // point is outside the hull
+                Err(Some(hull_tid)) if !split.sliver => {
+                    let point = self.hull_split_point(hull_tid);
+                    if self.insert(point, hull_tid, false).is_err() {
+                        continue;
+                    }
+                    bad.push_back(tid);
+                }
+                // a sliver is not worth splitting the hull, but any point in
+                // its circumsphere removes it
+                Err(_) if split.sliver => {
+                    let points = self.perturbed_split_points(tid, &split.point);
+                    if !points
+                        .into_iter()
+                        .any(|p| self.insert(p, tid, true).is_ok())
+                    {
+                        continue;
+                    }
+                }
+                Err(_) => continue,
+            }
+            bad.extend((first_new..self.mesh.tets.len()).filter(|&t| self.alive(t)));
+        }
+    }
+}
+
+/// Tetrahedralize the convex hull of `points` like [`tetrahedralize`], then
+/// insert Steiner points until the ratio of the circumradius to the shortest
+/// edge of every tetrahedron is at most `max_radius_edge_ratio`, no
+/// tetrahedron is larger than `max_volume` and no dihedral angle is smaller
+/// than `min_dihedral_angle` degrees, or until `max_steiner_points` are
+/// inserted. The circumcenters of the bad tetrahedra are inserted with the
+/// Bowyer-Watson algorithm. A circumcenter outside the convex hull or in the
+/// diametral ball of a hull face is rejected and the hull face is split
+/// instead, at its circumcenter, or at the midpoint of its longest edge for
+/// obtuse faces, or at the midpoint of an edge between faces which are not
+/// coplanar if the point is in its diametral ball.
+///
+/// A rejected circumcenter of a sliver is moved toward the centroid of the
+/// sliver instead of splitting the hull, and the sliver is left as it is if
+/// these points are rejected as well, so the dihedral angle bound is not
+/// guaranteed.
+///
+/// The mesh stays Delaunay without constraints, so a hull split point is
+/// inserted wherever it is rounded to, unless it is outside the hull. It lies
+/// exactly on the hull for e.g. the faces of an axis-aligned box, but it may
+/// be rounded to the inside of other hull faces, which then stay in the hull
+/// next to a nearly flat tetrahedron. Such tetrahedra are handled like slivers
+/// so that they do not split the hull over and over.
+///
+/// Returns the new points, numbered after the input points, and the
+/// tetrahedra.
+pub fn tetrahedralize_refine(
+    points: &[f64],
+    max_radius_edge_ratio: f64,
+    max_volume: f64,
+    min_dihedral_angle: f64,
+    max_steiner_points: usize,
+) -> (Vec<f64>, Vec<usize>) {
+    let mut refiner = TetRefiner::new(
+        points,
+        max_radius_edge_ratio,
+        max_volume,
+        min_dihedral_angle,
+        max_steiner_points,
+    );
+    refiner.refine();
+    let mut tets = Vec::new();
+    for tid in 0..refiner.mesh.tets.len() {
+        if refiner.alive(tid) {
+            tets.extend(
+                refiner.mesh.tets[tid]
+                    .data
+                    .map(|vid| refiner.mesh.output_index(vid)),
+            );
+        }
+    }
+    (refiner.mesh.new_points, tets)
+}
@@ -1,10 +1,10 @@
-use std::alloc::Allocator;
+use std::{alloc::Allocator, borrow::Cow};
 
 use hashbrown::HashMap;
 
 use crate::{
-    math::{cross, norm, sub},
-    predicates::{self, double_to_sign, sign_reverse, Orientation},
+    math::{cross, dot, norm, sub},
+    predicates::{self, Orientation},
     INVALID_IND,
 };
 use bumpalo::Bump;
@@ -105,26 +105,436 @@ impl Tet {
     pub fn index(&self, vid: usize) -> Option<usize> {
         self.data.iter().position(|&id| id == vid)
     }
+
+    /// Whether the tetrahedron was removed from the cavity of an insertion.
+    #[inline(always)]
+    pub(super) fn deleted(&self) -> bool {
+        self.mask == !0
+    }
 }
 
 pub struct TetMesh<'a> {
-    pub points: &'a [f64],
+    /// The coordinates of the input points, borrowed until one of them is
+    /// relocated.
+    pub points: Cow<'a, [f64]>,
+    /// The coordinates of the inserted points, which are numbered after the
+    /// input points and the ghost vertex.
+    pub new_points: Vec<f64>,
+    /// The weights of the points of a regular triangulation.
+    pub weights: Option<&'a [f64]>,
+    /// The number of input points, which is the index of the ghost vertex of
+    /// the hull tetrahedra.
     pub n_points: usize,
     pub tets: Vec<Tet>,
+    /// A tetrahedron incident to each point, `usize::MAX` for the redundant
+    /// points of a regular triangulation.
     pub p2t: Vec<usize>,
 }
 
 impl<'a> TetMesh<'a> {
-    fn new(points: &'a [f64]) -> Self {
+    fn new(points: &'a [f64], weights: Option<&'a [f64]>) -> Self {
         let n_points = points.len() / 3;
         Self {
-            points,
+            points: Cow::Borrowed(points),
+            new_points: Vec::new(),
+            weights,
             n_points,
             tets: Vec::new(),
             p2t: vec![INVALID_IND; n_points + 1],
         }
     }
 
+    /// The indices of the points, the input points followed by the inserted
+    /// ones, skipping the ghost vertex.
+    pub fn vertices(&self) -> impl Iterator<Item = usize> {
+        (0..self.n_points).chain((self.n_points + 1)..self.p2t.len())
+    }
+
+    /// The position of the vertex `vid` in [`Self::vertices`], as numbered in
+    /// the output of the tetrahedralizations.
+    #[inline(always)]
+    pub fn output_index(&self, vid: usize) -> usize {
+        if vid > self.n_points {
+            vid - 1
+        } else {
+            vid
+        }
+    }
+
+    /// Append the point `p` and return its index. It is not inserted in the
+    /// tetrahedralization.
+    pub(super) fn push_point(&mut self, p: &[f64]) -> usize {
+        self.new_points.extend_from_slice(&p[..3]);
+        self.p2t.push(INVALID_IND);
+        self.p2t.len() - 1
+    }
+
+    /// Remove the last point appended by [`Self::push_point`].
+    pub(super) fn pop_point(&mut self) {
+        self.new_points.truncate(self.new_points.len() - 3);
+        self.p2t.pop();
+    }
+
+    /// The coordinates of the point `idx`, copying the input points if they
+    /// are borrowed.
+    fn point_mut(&mut self, idx: usize) -> &mut [f64] {
+        if idx < self.n_points {
+            &mut self.points.to_mut()[(idx * 3)..(idx * 3 + 3)]
+        } else {
+            let start = (idx - self.n_points - 1) * 3;
+            &mut self.new_points[start..(start + 3)]
+        }
+    }
+
+    /// Insert the new point `p` with the Bowyer-Watson algorithm, searching
+    /// from the tetrahedron `hint`, and return its index, which follows the
+    /// input points and the ghost vertex. The convex hull grows if `p` is
+    /// outside it. Returns `None`, leaving the mesh unchanged, if `p` is a
+    /// vertex already, if there are no tetrahedra, or for a regular
+    /// triangulation, which has no weight for `p`. The indices of the
+    /// tetrahedra are only valid until the next modification.
+    pub fn insert_point(&mut self, p: &[f64], hint: Option<usize>, bump: &Bump) -> Option<usize> {
+        if self.weights.is_some() || self.tets.is_empty() {
+            return None;
+        }
+        let pid = self.push_point(p);
+        if self.insert_free_vertex(pid, hint.unwrap_or(0), bump) {
+            Some(pid)
+        } else {
+            self.pop_point();
+            None
+        }
+    }
+
+    /// The lifted coordinate of the point `idx` in a regular triangulation,
+    /// its squared norm minus its weight.
+    #[inline(always)]
+    pub fn height(&self, idx: usize) -> f64 {
+        let p = self.point(idx);
+        let weight = self.weights.map_or(0.0, |weights| weights[idx]);
+        p[0] * p[0] + p[1] * p[1] + p[2] * p[2] - weight
+    }
+
+    /// The orthocenter of the tetrahedron `idx`, the point with the same power
+    /// distance to its weighted vertices, which is a vertex of the power
+    /// diagram, or the circumcenter without weights.
+    pub fn orthocenter(&self, idx: usize) -> [f64; 3] {
+        let data = &self.tets[idx].data;
+        let pa = self.point(data[0]);
+        let weight = |vid: usize| self.weights.map_or(0.0, |weights| weights[vid]);
+        let mut u = [[0.0; 3]; 3];
+        for (i, &vid) in data[1..].iter().enumerate() {
+            sub(self.point(vid), pa, &mut u[i]);
+        }
+        let mut normals = [[0.0; 3]; 3];
+        for i in 0..3 {
+            cross(&u[(i + 1) % 3], &u[(i + 2) % 3], &mut normals[i]);
+        }
+        let det = dot(&u[0], &normals[0]);
+        let mut center = [pa[0], pa[1], pa[2]];
+        for i in 0..3 {
+            let scale = (dot(&u[i], &u[i]) - weight(data[i + 1]) + weight(data[0])) / (2.0 * det);
+            for k in 0..3 {
+                center[k] += normals[i][k] * scale;
+            }
+        }
+        center
+    }
+
+    /// Locate the point `p` by walking from the tetrahedron `hint`, e.g. the
+    /// one of a nearby point located before, or from the first tetrahedron.
+    /// The location is decided with exact predicates. Returns `None` if there
+    /// are no tetrahedra.
+    pub fn locate<A: Allocator + Copy>(
+        &self,
+        p: &[f64],
+        hint: Option<usize>,
+        bump: A,
+    ) -> Option<LocatedPoint> {
+        if self.tets.is_empty() {
+            return None;
+        }
+        let mut searchtet = TriFace::new(hint.unwrap_or(0), 0);
+        let (location, tet) = match locate_point(self, p, INVALID_IND, &mut searchtet, bump) {
+            LocateResult::OUTSIDE => (TetLocation::Outside, self.tets[searchtet.tet].nei[3].tet),
+            LocateResult::ONVERTEX => (TetLocation::Vertex(self.org(&searchtet)), searchtet.tet),
+            LocateResult::ONEDGE => (
+                TetLocation::Edge([self.org(&searchtet), self.dest(&searchtet)]),
+                searchtet.tet,
+            ),
+            LocateResult::ONFACE => (
+                TetLocation::Face([
+                    self.org(&searchtet),
+                    self.dest(&searchtet),
+                    self.apex(&searchtet),
+                ]),
+                searchtet.tet,
+            ),
+            LocateResult::INTETRAHEDRON => (TetLocation::Tet, searchtet.tet),
+        };
+        Some(LocatedPoint {
+            location,
+            tet,
+            barycentric: self.barycentric(tet, p, bump),
+        })
+    }
+
+    /// The barycentric coordinates of `p` with respect to the vertices of the
+    /// tetrahedron `idx`, which is not a hull tetrahedron. The coordinates are
+    /// zero exactly when `p` lies on the plane of the opposite face.
+    pub fn barycentric<A: Allocator + Copy>(&self, idx: usize, p: &[f64], bump: A) -> [f64; 4] {
+        let [pa, pb, pc, pd] = self.tets[idx].data.map(|vid| self.point(vid));
+        let volumes = [
+            predicates::orient3d(p, pb, pc, pd, bump),
+            predicates::orient3d(pa, p, pc, pd, bump),
+            predicates::orient3d(pa, pb, p, pd, bump),
+            predicates::orient3d(pa, pb, pc, p, bump),
+        ];
+        let total: f64 = volumes.iter().sum();
+        volumes.map(|v| v / total)
+    }
+
+    /// Remove the vertex `vid` and fill its star with the Delaunay
+    /// tetrahedra of its neighbors in conflict with it, which are the
+    /// tetrahedra of the Delaunay tetrahedralization without `vid` in its
+    /// star. The hull shrinks if `vid` is on it. Returns false, leaving the
+    /// mesh unchanged, if `vid` is not a vertex, if its neighbors are
+    /// coplanar, or for a regular triangulation, whose redundant points could
+    /// reappear. The indices of the tetrahedra are only valid until the next
+    /// modification.
+    pub fn remove_vertex(&mut self, vid: usize, bump: &Bump) -> bool {
+        self.remove(vid, bump).is_some()
+    }
+
+    /// Move the vertex `vid` to `p`, removing it and inserting it again with
+    /// the Bowyer-Watson algorithm, which repairs the Delaunay property around
+    /// its old and new positions. The input points are copied at the first
+    /// relocation of one of them in a mesh borrowing them. Returns false, leaving the vertex
+    /// where it was, if it cannot be removed or if `p` is another vertex.
+    pub fn relocate_vertex(&mut self, vid: usize, p: &[f64], bump: &Bump) -> bool {
+        let Some(hint) = self.remove(vid, bump) else {
+            return false;
+        };
+        let old = [0, 1, 2].map(|i| self.point(vid)[i]);
+        self.point_mut(vid).copy_from_slice(&p[..3]);
+        if self.insert_free_vertex(vid, hint, bump) {
+            return true;
+        }
+        // the old position is free again
+        self.point_mut(vid).copy_from_slice(&old);
+        self.insert_free_vertex(vid, hint, bump);
+        false
+    }
+
+    /// Insert the point `vid`, which is not a vertex of the mesh, searching
+    /// from the tetrahedron `hint`.
+    fn insert_free_vertex(&mut self, vid: usize, hint: usize, bump: &Bump) -> bool {
+        let first_new = self.tets.len();
+        let mut searchtet = TriFace::new(hint, 0);
+        let Some(removed) = bowyer_watson(self, vid, &mut searchtet, bump) else {
+            return false;
+        };
+        // the real vertices keep a real tetrahedron
+        for t in first_new..self.tets.len() {
+            for v in self.tets[t].data {
+                if v != self.n_points && self.is_hull_tet(self.p2t[v]) {
+                    self.p2t[v] = self.tets[self.p2t[v]].nei[3].tet;
+                }
+            }
+        }
+        self.remove_deleted(&removed);
+        true
+    }
+
+    /// Remove the vertex `vid`, returning a tetrahedron filling its star.
+    fn remove(&mut self, vid: usize, bump: &Bump) -> Option<usize> {
+        if self.weights.is_some()
+            || vid == self.n_points
+            || vid >= self.p2t.len()
+            || self.p2t[vid] == INVALID_IND
+        {
+            return None;
+        }
+        // the star of the vertex, with the hull tetrahedra
+        let mut star = Vec::new_in(bump);
+        star.push(self.p2t[vid]);
+        self.mark_test(star[0]);
+        let mut idx = 0;
+        while idx < star.len() {
+            let t = star[idx];
+            let pos = self.tets[t].index(vid).unwrap();
+            for i in (0..4).filter(|&i| i != pos) {
+                let nei = self.tets[t].nei[i].tet;
+                if !self.mark_tested(nei) {
+                    self.mark_test(nei);
+                    star.push(nei);
+                }
+            }
+            idx += 1;
+        }
+        for &t in &star {
+            self.unmark_test(t);
+        }
+
+        // the faces of the link, by their vertices, with the tetrahedra
+        // outside the star sharing them
+        let ghost = self.n_points;
+        let mut link = HashMap::new_in(bump);
+        let mut neighbors = Vec::new_in(bump);
+        for &t in &star {
+            let pos = self.tets[t].index(vid).unwrap();
+            let nei = &self.tets[t].nei[pos];
+            let mut face = self.tets[t].data;
+            face[pos] = ghost + 1;
+            face.sort_unstable();
+            link.insert([face[0], face[1], face[2]], (nei.tet, nei.ver & 3));
+            neighbors.extend(face[..3].iter().filter(|&&v| v != ghost));
+        }
+        neighbors.sort_unstable();
+        neighbors.dedup();
+
+        // the Delaunay tetrahedralization of the neighbors, numbered in the
+        // same order to keep the symbolic perturbation
+        let mut local_points = Vec::with_capacity(neighbors.len() * 3);
+        for &v in &neighbors {
+            local_points.extend_from_slice(self.point(v));
+        }
+        let local = delaunay(TetMesh::new(&local_points, None));
+        if local.tets.is_empty() {
+            return None;
+        }
+        let global = |v: usize| {
+            if v == local.n_points {
+                ghost
+            } else {
+                neighbors[v]
+            }
+        };
+        let conflict = Vec::from_iter((0..local.tets.len()).map(|t| {
+            let [a, b, c, d] = local.tets[t].data.map(global);
+            if !local.is_hull_tet(t) {
+                return insphere_s(self, a, b, c, d, vid, bump) == Orientation::Negative;
+            }
+            let ori = self.orient3d(a, b, c, vid, bump);
+            if ori != 0.0 {
+                return ori < 0.0;
+            }
+            let [a, b, c, d] = local.tets[local.tets[t].nei[3].tet].data.map(global);
+            insphere_s(self, a, b, c, d, vid, bump) == Orientation::Negative
+        }));
+
+        // the new tetrahedra take the slots of the star, their faces must
+        // match the link
+        let mut slots = Vec::new_in(bump);
+        let mut n_link_faces = 0;
+        for t in (0..local.tets.len()).filter(|&t| conflict[t]) {
+            for (i, nei) in local.tets[t].nei.iter().enumerate() {
+                if conflict[nei.tet] {
+                    continue;
+                }
+                let mut face = local.tets[t].data.map(global);
+                face[i] = ghost + 1;
+                face.sort_unstable();
+                if !link.contains_key(&[face[0], face[1], face[2]]) {
+                    return None;
+                }
+                n_link_faces += 1;
+            }
+            let slot = if slots.len() < star.len() {
+                star[slots.len()]
+            } else {
+                self.tets.len() + slots.len() - star.len()
+            };
+            slots.push((t, slot));
+        }
+        if n_link_faces != link.len() {
+            return None;
+        }
+
+        let mut new_ids = vec![INVALID_IND; local.tets.len()];
+        for &(t, slot) in &slots {
+            new_ids[t] = slot;
+            let [a, b, c, d] = local.tets[t].data.map(global);
+            if slot < self.tets.len() {
+                self.tets[slot] = Tet::new(a, b, c, d);
+            } else {
+                self.tets.push(Tet::new(a, b, c, d));
+            }
+        }
+        for &(t, slot) in &slots {
+            for i in 0..4 {
+                let t1 = TriFace::new(slot, i);
+                let nei = &local.tets[t].nei[i];
+                let (other, j) = if conflict[nei.tet] {
+                    (new_ids[nei.tet], nei.ver & 3)
+                } else {
+                    let mut face = self.tets[slot].data;
+                    face[i] = ghost + 1;
+                    face.sort_unstable();
+                    link[&[face[0], face[1], face[2]]]
+                };
+                let t2 = self.face_version(other, j, self.dest(&t1), self.org(&t1));
+                self.bond(&t1, &t2);
+            }
+        }
+
+        // the real vertices keep a real tetrahedron
+        for &(_, slot) in &slots {
+            let real = if self.is_hull_tet(slot) {
+                self.tets[slot].nei[3].tet
+            } else {
+                slot
+            };
+            for v in self.tets[slot].data {
+                if star.contains(&self.p2t[v]) {
+                    self.p2t[v] = if v == ghost { slot } else { real };
+                } else if v != ghost && real == slot {
+                    self.p2t[v] = slot;
+                }
+            }
+        }
+        self.p2t[vid] = INVALID_IND;
+        let surplus = &star[slots.len().min(star.len())..];
+        for &t in surplus {
+            self.tets[t].mask = !0;
+        }
+        self.remove_deleted(surplus);
+        Some(self.p2t[neighbors[0]])
+    }
+
+    /// The version of the tetrahedron `tid` on the face opposite to its vertex
+    /// `i`, from `org` to `dest`.
+    fn face_version(&self, tid: usize, i: usize, org: usize, dest: usize) -> TriFace {
+        (0..3)
+            .map(|k| TriFace::new(tid, i + 4 * k))
+            .find(|t| self.org(t) == org && self.dest(t) == dest)
+            .unwrap()
+    }
+
+    /// Remove the deleted tetrahedra `tids`, moving the last tetrahedra into
+    /// their slots.
+    fn remove_deleted(&mut self, tids: &[usize]) {
+        for &tid in tids {
+            while self.tets.last().is_some_and(|t| t.deleted()) {
+                self.tets.pop();
+            }
+            if tid >= self.tets.len() {
+                continue;
+            }
+            let last = self.tets.len() - 1;
+            self.tets.swap_remove(tid);
+            for i in 0..4 {
+                let nei = self.tets[tid].nei[i].clone();
+                self.tets[nei.tet].nei[nei.ver & 3].tet = tid;
+            }
+            for v in self.tets[tid].data {
+                if self.p2t[v] == last {
+                    self.p2t[v] = tid;
+                }
+            }
+        }
+    }
+
     #[inline(always)]
     pub fn org(&self, f: &TriFace) -> usize {
         self.tets[f.tet].data[ORG_PIVOT[f.ver]]
@@ -208,9 +618,14 @@ impl<'a> TetMesh<'a> {
         self.tets[idx].data[3] == self.n_points
     }
 
+    /// The coordinates of the point `idx`, an input point or an inserted one.
     #[inline(always)]
     pub fn point(&self, idx: usize) -> &[f64] {
-        point(self.points, idx)
+        if idx < self.n_points {
+            point(&self.points, idx)
+        } else {
+            point(&self.new_points, idx - self.n_points - 1)
+        }
     }
 
     #[inline(always)]
@@ -223,10 +638,10 @@ impl<'a> TetMesh<'a> {
         allocator: A,
     ) -> f64 {
         predicates::orient3d(
-            &self.points[(pa * 3)..],
-            &self.points[(pb * 3)..],
-            &self.points[(pc * 3)..],
-            &self.points[(pd * 3)..],
+            self.point(pa),
+            self.point(pb),
+            self.point(pc),
+            self.point(pd),
             allocator,
         )
     }
@@ -700,7 +1115,34 @@ fn initial_delaunay(tets: &mut TetMesh, pa: usize, pb: usize, pc: usize, pd: usi
     firsttet
 }
 
-enum LocateResult {
+/// Where a point lies in a [`TetMesh`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TetLocation {
+    /// Outside the convex hull.
+    Outside,
+    /// On a vertex.
+    Vertex(usize),
+    /// In the interior of an edge.
+    Edge([usize; 2]),
+    /// In the interior of a face.
+    Face([usize; 3]),
+    /// In the interior of a tetrahedron.
+    Tet,
+}
+
+/// The result of [`TetMesh::locate`].
+#[derive(Clone, Copy, Debug)]
+pub struct LocatedPoint {
+    pub location: TetLocation,
+    /// The tetrahedron containing the point, or the one behind the hull face
+    /// the walk left through for a point outside the convex hull.
+    pub tet: usize,
+    /// The barycentric coordinates of the point with respect to the vertices
+    /// of `tet`, some of them negative outside the convex hull.
+    pub barycentric: [f64; 4],
+}
+
+pub(super) enum LocateResult {
     OUTSIDE,
     ONVERTEX,
     ONEDGE,
@@ -708,19 +1150,37 @@ enum LocateResult {
     INTETRAHEDRON,
 }
 
-fn locate_dt(tets: &TetMesh, pid: usize, searchtet: &mut TriFace, bump: &Bump) -> LocateResult {
+pub(super) fn locate_dt(
+    tets: &TetMesh,
+    pid: usize,
+    searchtet: &mut TriFace,
+    bump: &Bump,
+) -> LocateResult {
+    locate_point(tets, tets.point(pid), pid, searchtet, bump)
+}
+
+// Walk from searchtet to the point p, which is the vertex pid if it was
+// inserted, INVALID_IND otherwise
+fn locate_point<A: Allocator + Copy>(
+    tets: &TetMesh,
+    p: &[f64],
+    pid: usize,
+    searchtet: &mut TriFace,
+    bump: A,
+) -> LocateResult {
+    let orient3d = |pa: usize, pb: usize, pc: usize| {
+        predicates::orient3d(tets.point(pa), tets.point(pb), tets.point(pc), p, bump)
+    };
     if tets.is_hull_tet(searchtet.tet) {
         searchtet.tet = tets.tets[searchtet.tet].nei[3].tet;
     }
 
     searchtet.ver = 0;
     while searchtet.ver < 4 {
-        let ori = tets.orient3d(
+        let ori = orient3d(
             tets.org(searchtet),
             tets.dest(searchtet),
             tets.apex(searchtet),
-            pid,
-            bump,
         );
         if ori < 0.0 {
             break;
@@ -741,17 +1201,15 @@ fn locate_dt(tets: &TetMesh, pid: usize, searchtet: &mut TriFace, bump: &Bump) -
             break;
         }
 
-        let oriorg = tets.orient3d(tets.dest(searchtet), tets.apex(searchtet), toppo, pid, bump);
+        let oriorg = orient3d(tets.dest(searchtet), tets.apex(searchtet), toppo);
         if oriorg < 0.0 {
             searchtet.enext_esym_self();
         } else {
-            let oridest =
-                tets.orient3d(tets.apex(searchtet), tets.org(searchtet), toppo, pid, bump);
+            let oridest = orient3d(tets.apex(searchtet), tets.org(searchtet), toppo);
             if oridest < 0.0 {
                 searchtet.eprev_esym_self();
             } else {
-                let oriapex =
-                    tets.orient3d(tets.org(searchtet), tets.dest(searchtet), toppo, pid, bump);
+                let oriapex = orient3d(tets.org(searchtet), tets.dest(searchtet), toppo);
                 if oriapex < 0.0 {
                     searchtet.esym_self();
                 } else {
@@ -814,7 +1272,8 @@ fn locate_dt(tets: &TetMesh, pid: usize, searchtet: &mut TriFace, bump: &Bump) -
     loc
 }
 
-// Insphere test with symbolic perturbation
+// Insphere test, or power test for weighted points, with symbolic perturbation
+#[inline(always)]
 fn insphere_s(
     tets: &TetMesh,
     pa: usize,
@@ -824,64 +1283,67 @@ fn insphere_s(
     pe: usize,
     bump: &Bump,
 ) -> Orientation {
-    let points = tets.points;
-    let sign = predicates::insphere(
-        &points[(pa * 3)..],
-        &points[(pb * 3)..],
-        &points[(pc * 3)..],
-        &points[(pd * 3)..],
-        &points[(pe * 3)..],
-        bump,
-    );
-    if sign != 0.0 {
-        return double_to_sign(sign);
-    }
-
-    let mut pt = [pa, pb, pc, pd, pe];
-
-    let mut swaps = 0; // Record the total number of swaps.
-    let mut n = 5;
-    while n > 0 {
-        let mut count = 0;
-        n = n - 1;
-        let mut i = 0;
-        while i < n {
-            if pt[i] > pt[i + 1] {
-                pt.swap(i, i + 1);
-                count += 1;
-            }
-            i += 1;
-        }
-        swaps += count;
-        // Continue if some points are swapped.
-        if count == 0 {
-            break;
-        }
-    }
-
-    let mut ori = tets.orient3d(pt[1], pt[2], pt[3], pt[4], bump);
-    if ori == 0.0 {
-        ori = -tets.orient3d(pt[0], pt[2], pt[3], pt[4], bump);
+    let ids = [pa, pb, pc, pd, pe];
+    if tets.weights.is_some() {
+        return predicates::orient4d_sos(
+            ids.map(|vid| tets.point(vid)),
+            ids.map(|vid| tets.height(vid)),
+            ids,
+            bump,
+        );
     }
+    predicates::insphere_sos(ids.map(|vid| tets.point(vid)), ids, bump)
+}
 
-    if (swaps & 1) != 0 {
-        sign_reverse(double_to_sign(ori))
-    } else {
-        double_to_sign(ori)
-    }
+// Insert a vertex using the Bowyer-Watson algorithm. Returns false if the
+// vertex is a duplicate, or a redundant point of a regular triangulation.
+pub(super) fn insert_vertex_bw(
+    tets: &mut TetMesh,
+    pid: usize,
+    searchtet: &mut TriFace,
+    bump: &Bump,
+) -> bool {
+    bowyer_watson(tets, pid, searchtet, bump).is_some()
 }
 
-// Insert a vertex using the Bowyer-Watson algorithm
-fn insert_vertex_bw(tets: &mut TetMesh, pid: usize, searchtet: &mut TriFace, bump: &Bump) -> bool {
+// Insert a vertex like insert_vertex_bw, returning the removed tetrahedra,
+// which are left in the mesh with the deleted mask
+fn bowyer_watson<'b>(
+    tets: &mut TetMesh,
+    pid: usize,
+    searchtet: &mut TriFace,
+    bump: &'b Bump,
+) -> Option<Vec<usize, &'b Bump>> {
     let mut cave_oldtet_list = Vec::new_in(bump);
 
-    match locate_dt(tets, pid, searchtet, bump) {
+    let loc = locate_dt(tets, pid, searchtet, bump);
+    if tets.weights.is_some() {
+        // a point inside the hull is redundant if it lies above the lifted
+        // tetrahedron containing it, and a duplicate replaces the vertex if it
+        // lies below it
+        let redundant = match loc {
+            LocateResult::OUTSIDE => false,
+            LocateResult::ONVERTEX => tets.height(pid) >= tets.height(tets.org(searchtet)),
+            _ => {
+                let d = &tets.tets[searchtet.tet].data;
+                insphere_s(tets, d[0], d[1], d[2], d[3], pid, bump) != Orientation::Negative
+            }
+        };
+        if redundant {
+            return None;
+        }
+    }
+    match loc {
         LocateResult::OUTSIDE | LocateResult::INTETRAHEDRON => {
             tets.infect(searchtet.tet);
             cave_oldtet_list.push(searchtet.tet);
         }
+        LocateResult::ONVERTEX if tets.weights.is_none() => {
+            return None;
+        }
         LocateResult::ONVERTEX => {
-            return false;
+            tets.infect(searchtet.tet);
+            cave_oldtet_list.push(searchtet.tet);
         }
         LocateResult::ONEDGE => {
             let mut spintet = searchtet.clone();
@@ -1093,14 +1555,58 @@ fn insert_vertex_bw(tets: &mut TetMesh, pid: usize, searchtet: &mut TriFace, bum
         }
     }
 
-    for tid in cave_oldtet_list {
+    for &tid in &cave_oldtet_list {
         tets.tets[tid].mask = !0;
     }
+    if tets.weights.is_some() {
+        // the vertices inside the cavity became redundant
+        for &tid in &cave_oldtet_list {
+            for vid in tets.tets[tid].data {
+                let t = tets.p2t[vid];
+                if t != INVALID_IND && tets.tets[t].deleted() {
+                    tets.p2t[vid] = INVALID_IND;
+                }
+            }
+        }
+    }
 
-    return true;
+    Some(cave_oldtet_list)
 }
 
+/// The Delaunay tetrahedralization of `points`. Only one of coincident points
+/// is a vertex of the tetrahedra, the `p2t` of the others is `usize::MAX`.
 pub fn tetrahedralize<'a>(points: &'a [f64]) -> TetMesh<'a> {
+    delaunay(TetMesh::new(points, None))
+}
+
+/// The Delaunay tetrahedralization of `points`, owning them.
+pub(super) fn tetrahedralize_owned(points: Vec<f64>) -> TetMesh<'static> {
+    let n_points = points.len() / 3;
+    delaunay(TetMesh {
+        points: Cow::Owned(points),
+        new_points: Vec::new(),
+        weights: None,
+        n_points,
+        tets: Vec::new(),
+        p2t: vec![INVALID_IND; n_points + 1],
+    })
+}
+
+/// The regular triangulation of `points` with `weights`, the weighted
+/// Delaunay tetrahedralization dual to their power diagram, using the power
+/// test of the points lifted to their squared norm minus their weight instead
+/// of the insphere test. A point lying above the lower hull of the lifted
+/// points is redundant, it has no tetrahedra and its `p2t` is `usize::MAX`,
+/// including the points hidden by the insertion of the later ones.
+pub fn tetrahedralize_weighted<'a>(points: &'a [f64], weights: &'a [f64]) -> TetMesh<'a> {
+    delaunay(TetMesh::new(points, Some(weights)))
+}
+
+fn delaunay(mut mesh: TetMesh) -> TetMesh {
+    if mesh.n_points < 4 {
+        return mesh;
+    }
+    let points = &mesh.points[..];
     let cmp = |x: &&f64, y: &&f64| x.partial_cmp(y).unwrap();
     let bbox = [
         // min_corner
@@ -1112,7 +1618,6 @@ pub fn tetrahedralize<'a>(points: &'a [f64]) -> TetMesh<'a> {
         *points[1..].iter().step_by(3).max_by(cmp).unwrap(),
         *points[2..].iter().step_by(3).max_by(cmp).unwrap(),
     ];
-    let mut mesh = TetMesh::new(points);
     let mut sorted_pt_inds = Vec::from_iter(0..mesh.n_points);
     sorted_pt_inds.shuffle(&mut rand::thread_rng());
     const SORT_OPTION: SortOption = SortOption {
@@ -1138,6 +1643,7 @@ pub fn tetrahedralize<'a>(points: &'a [f64]) -> TetMesh<'a> {
     };
     let bbox_size2 = bbox_size1 * bbox_size1;
     let bbox_size3 = bbox_size2 * bbox_size1;
+    let mut bump = Bump::new();
     let mut i = 1;
     {
         let p0 = point(points, sorted_pt_inds[0]);
@@ -1150,10 +1656,14 @@ pub fn tetrahedralize<'a>(points: &'a [f64]) -> TetMesh<'a> {
                 break;
             }
         }
-        if i > 1 {
-            if i == mesh.n_points {
-                return mesh;
+        if i == mesh.n_points {
+            // no point far enough, any distinct one
+            match (1..mesh.n_points).find(|&j| point(points, sorted_pt_inds[j]) != p0) {
+                Some(j) => i = j,
+                None => return mesh,
             }
+        }
+        if i > 1 {
             sorted_pt_inds.swap(1, i);
         }
 
@@ -1172,10 +1682,19 @@ pub fn tetrahedralize<'a>(points: &'a [f64]) -> TetMesh<'a> {
                 break;
             }
         }
+        if i == mesh.n_points {
+            // no point far enough from the line, any exactly off it
+            match (2..mesh.n_points).find(|&j| {
+                let pj = point(points, sorted_pt_inds[j]);
+                (0..3).any(|axis| {
+                    predicates::orient2d_by_axis(p0, p1, pj, axis, &bump) != Orientation::Zero
+                })
+            }) {
+                Some(j) => i = j,
+                None => return mesh,
+            }
+        }
         if i > 2 {
-            if i == mesh.n_points {
-                return mesh;
-            };
             sorted_pt_inds.swap(2, i);
         }
     }
@@ -1194,10 +1713,18 @@ pub fn tetrahedralize<'a>(points: &'a [f64]) -> TetMesh<'a> {
             break;
         }
     }
-    if i > 3 {
-        if i == mesh.n_points {
-            return mesh;
+    if i == mesh.n_points {
+        // no point far enough from the plane, any exactly off it
+        let [p0, p1, p2] = [0, 1, 2].map(|j| point(points, sorted_pt_inds[j]));
+        match (3..mesh.n_points).find(|&j| {
+            ori = predicates::orient3d(p0, p1, p2, point(points, sorted_pt_inds[j]), &bump);
+            ori != 0.0
+        }) {
+            Some(j) => i = j,
+            None => return mesh,
         }
+    }
+    if i > 3 {
         sorted_pt_inds.swap(3, i);
     }
 
@@ -1213,12 +1740,10 @@ pub fn tetrahedralize<'a>(points: &'a [f64]) -> TetMesh<'a> {
         sorted_pt_inds[2],
         sorted_pt_inds[3],
     );
-    let mut bump = Bump::new();
-    for i in 4..mesh.n_points {
+    for &pid in &sorted_pt_inds[4..] {
         bump.reset();
-        if !insert_vertex_bw(&mut mesh, sorted_pt_inds[i], &mut search_tet, &bump) {
-            break;
-        }
+        // the duplicates and the redundant points are skipped
+        insert_vertex_bw(&mut mesh, pid, &mut search_tet, &bump);
     }
 
     let mut count = 0;
@@ -1242,11 +1767,18 @@ pub fn tetrahedralize<'a>(points: &'a [f64]) -> TetMesh<'a> {
 
     // make p2t[i] not be a ghost
     for i in 0..mesh.n_points {
+        if mesh.p2t[i] == INVALID_IND {
+            continue;
+        }
         mesh.p2t[i] = tet_map[mesh.p2t[i]];
         if mesh.is_hull_tet(mesh.p2t[i]) {
             mesh.p2t[i] = mesh.tets[mesh.p2t[i]].nei[3].tet;
         }
     }
+    // and p2t of the ghost vertex be a hull tet
+    if let Some(tid) = (0..mesh.tets.len()).find(|&tid| mesh.is_hull_tet(tid)) {
+        mesh.p2t[mesh.n_points] = tid;
+    }
 
     mesh
 }
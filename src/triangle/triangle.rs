@@ -1,47 +1,173 @@
 use hashbrown::HashMap;
 use std::alloc::Allocator;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use bumpalo::Bump;
 
 use crate::math::{dot, sub};
 use crate::{predicates, INVALID_IND};
 
+use super::split_intersections;
+
 pub fn triangulate<A: Allocator + Copy>(
     points: &[f64],
     segments: &[usize],
     bump: A,
 ) -> Vec<usize, A> {
-    let n_points = points.len() >> 1;
-    let mut sorted_pt_inds = Vec::with_capacity_in(n_points, bump);
-    sorted_pt_inds.extend(0..n_points);
-    sorted_pt_inds.sort_unstable_by(|&i, &j| {
-        let i = i << 1;
-        let j = j << 1;
-        (points[i], points[i + 1])
-            .partial_cmp(&(points[j], points[j + 1]))
-            .unwrap()
-    });
-    // resort the array of points to accommodate alternating cuts
-    alternate_axes(points, &mut sorted_pt_inds, 0);
+    let (triangles, ghost) = constrained_delaunay(points, segments, bump);
+    let mut result = Vec::new_in(bump);
+    result.extend(
+        triangles
+            .iter()
+            .zip(ghost)
+            .filter_map(|(tri, is_ghost)| {
+                if is_ghost {
+                    None
+                } else {
+                    let data = &tri.data;
+                    Some([data[0], data[1], data[2]])
+                }
+            })
+            .flatten(),
+    );
+    result
+}
 
-    let mut mesh = Mesh {
-        points,
-        triangles: Vec::new_in(bump),
+/// A triangulation with its adjacency, as returned by [`triangulate_mesh`].
+pub struct TriMesh<A: Allocator + Copy> {
+    triangles: Vec<usize, A>,
+    neighbors: Vec<usize, A>,
+    segments: Vec<usize, A>,
+    hull: Vec<usize, A>,
+}
+
+impl<A: Allocator + Copy> TriMesh<A> {
+    /// The vertex triples of the triangles, in counterclockwise order.
+    #[inline(always)]
+    pub fn triangles(&self) -> &[usize] {
+        &self.triangles
+    }
+
+    #[inline(always)]
+    pub fn n_triangles(&self) -> usize {
+        self.triangles.len() / 3
+    }
+
+    #[inline(always)]
+    pub fn triangle(&self, tid: usize) -> [usize; 3] {
+        [
+            self.triangles[tid * 3],
+            self.triangles[tid * 3 + 1],
+            self.triangles[tid * 3 + 2],
+        ]
+    }
+
+    /// The triangle across the edge opposite to the vertex `i` of `tid`, if it
+    /// is in the domain.
+    #[inline(always)]
+    pub fn neighbor(&self, tid: usize, i: usize) -> Option<usize> {
+        let nei = self.neighbors[tid * 3 + i];
+        (nei != INVALID_IND).then_some(nei)
+    }
+
+    /// The index of the input segment covering the edge opposite to the
+    /// vertex `i` of `tid`, if the edge is constrained.
+    #[inline(always)]
+    pub fn segment(&self, tid: usize, i: usize) -> Option<usize> {
+        let seg = self.segments[tid * 3 + i];
+        (seg != INVALID_IND).then_some(seg)
+    }
+
+    #[inline(always)]
+    pub fn is_constrained(&self, tid: usize, i: usize) -> bool {
+        self.segments[tid * 3 + i] != INVALID_IND
+    }
+
+    /// The vertices of the convex hull of the points in counterclockwise order,
+    /// including the vertices lying on its edges.
+    #[inline(always)]
+    pub fn hull(&self) -> &[usize] {
+        &self.hull
+    }
+}
+
+/// Like [`triangulate`], but keeps the neighbors of the triangles, the input
+/// segments covering their edges and the convex hull.
+pub fn triangulate_mesh<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    bump: A,
+) -> TriMesh<A> {
+    let (triangles, mut ghost) = delaunay_skeleton(points, segments, bump);
+    let mut hull_next = HashMap::new_in(bump);
+    for (i, tri) in triangles.iter().enumerate() {
+        if ghost[i] {
+            continue;
+        }
+        for j in 0..3 {
+            if ghost[tri.nei[j].tri] {
+                hull_next.insert(tri.data[(j + 1) % 3], tri.data[(j + 2) % 3]);
+            }
+        }
+    }
+    let mut hull = Vec::with_capacity_in(hull_next.len(), bump);
+    if let Some(&start) = hull_next.keys().min() {
+        let mut vid = start;
+        loop {
+            hull.push(vid);
+            vid = hull_next[&vid];
+            if vid == start {
+                break;
+            }
+        }
+    }
+
+    remove_outside(&triangles, &mut ghost, bump);
+    let mut new_ids = std::vec::from_elem_in(INVALID_IND, triangles.len(), bump);
+    let mut n_triangles = 0;
+    for i in 0..triangles.len() {
+        if !ghost[i] {
+            new_ids[i] = n_triangles;
+            n_triangles += 1;
+        }
+    }
+    let mut mesh = TriMesh {
+        triangles: Vec::with_capacity_in(n_triangles * 3, bump),
+        neighbors: Vec::with_capacity_in(n_triangles * 3, bump),
+        segments: Vec::with_capacity_in(n_triangles * 3, bump),
+        hull,
     };
-    let mut hull_left = HEdge::default();
-    let mut hull_right = HEdge::default();
-    div_conq_recurse(
-        &mut mesh,
-        &sorted_pt_inds,
-        0,
-        &mut hull_left,
-        &mut hull_right,
-        bump,
-    );
+    for (i, tri) in triangles.iter().enumerate() {
+        if ghost[i] {
+            continue;
+        }
+        mesh.triangles.extend_from_slice(&tri.data[0..3]);
+        for j in 0..3 {
+            mesh.neighbors.push(new_ids[tri.nei[j].tri]);
+            let mark = tri.data[j + 3];
+            mesh.segments
+                .push(if mark == INVALID_IND { mark } else { mark >> 1 });
+        }
+    }
+    mesh
+}
+
+/// Build the constrained Delaunay triangulation of `points` and `segments`,
+/// returns all the triangles and whether each of them is outside the domain.
+pub(super) fn constrained_delaunay<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    bump: A,
+) -> (Vec<Triangle, A>, Vec<bool, A>) {
+    let (triangles, mut ghost) = delaunay_skeleton(points, segments, bump);
+    remove_outside(&triangles, &mut ghost, bump);
+    (triangles, ghost)
+}
 
-    let mut ghost = mark_ghost(&mesh.triangles, &mut hull_left, bump);
-    form_skeleton(&mut mesh, &ghost, segments, bump);
-    let mut visited = std::vec::from_elem_in(false, mesh.triangles.len(), bump);
+/// Mark the regions lying on the right of the first segment found on their
+/// boundary as outside the domain.
+fn remove_outside<A: Allocator + Copy>(triangles: &[Triangle], ghost: &mut [bool], bump: A) {
+    let mut visited = std::vec::from_elem_in(false, triangles.len(), bump);
     for i in 0..visited.len() {
         if visited[i] || ghost[i] {
             continue;
@@ -51,7 +177,7 @@ pub fn triangulate<A: Allocator + Copy>(
         let mut idx = 0;
         let mut flag = 2;
         while idx < queue.len() {
-            let tri = &mesh.triangles[queue[idx]];
+            let tri = &triangles[queue[idx]];
             for j in 0..3 {
                 if tri.data[j + 3] != INVALID_IND {
                     if flag > 1 {
@@ -74,22 +200,324 @@ pub fn triangulate<A: Allocator + Copy>(
             }
         }
     }
+}
+
+/// The rule deciding which regions bounded by directed segments are inside the
+/// domain from their winding numbers, like the fill rules of SVG and PostScript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside where the winding number is odd.
+    EvenOdd,
+    /// Inside where the winding number is not zero.
+    NonZero,
+    /// Inside where the winding number is positive.
+    Positive,
+}
+
+impl FillRule {
+    #[inline(always)]
+    pub fn contains(&self, winding: i32) -> bool {
+        match self {
+            FillRule::EvenOdd => (winding & 1) == 1,
+            FillRule::NonZero => winding != 0,
+            FillRule::Positive => winding > 0,
+        }
+    }
+}
+
+/// The number of times the directed `segments` for which `included` holds
+/// wind counterclockwise around each triangle, zero for the ghost triangles.
+/// Segments with the same endpoints accumulate, while collinear segments
+/// overlapping only partially are counted once.
+pub(super) fn winding_numbers<A: Allocator + Copy>(
+    triangles: &[Triangle],
+    ghost: &[bool],
+    segments: &[usize],
+    included: impl Fn(usize) -> bool,
+    bump: A,
+) -> Vec<i32, A> {
+    // the net number of segments along the edge from the smaller vertex
+    let mut net_counts = HashMap::with_capacity_in(segments.len() >> 1, bump);
+    for (i, seg) in segments.chunks(2).enumerate() {
+        if seg[0] != seg[1] && included(i) {
+            let count = net_counts
+                .entry((seg[0].min(seg[1]), seg[0].max(seg[1])))
+                .or_insert(0);
+            *count += if seg[0] < seg[1] { 1 } else { -1 };
+        }
+    }
+    // the net number of segments along the edge `j` of `tri` in its direction
+    let crossing = |tri: &Triangle, j: usize| {
+        let mark = tri.data[j + 3];
+        if mark == INVALID_IND {
+            return 0;
+        }
+        let seg = &segments[(mark & !1)..((mark & !1) + 2)];
+        let mut count = net_counts
+            .get(&(seg[0].min(seg[1]), seg[0].max(seg[1])))
+            .copied()
+            .unwrap_or(0);
+        if seg[0] > seg[1] {
+            count = -count;
+        }
+        if (mark & 1) == 0 {
+            count
+        } else {
+            -count
+        }
+    };
+
+    let mut winding = std::vec::from_elem_in(0, triangles.len(), bump);
+    let mut visited = ghost.to_vec_in(bump);
+    let mut queue = Vec::new_in(bump);
+    for (i, tri) in triangles.iter().enumerate() {
+        if ghost[i] {
+            continue;
+        }
+        if let Some(j) = (0..3).find(|&j| ghost[tri.nei[j].tri]) {
+            // the ghost triangle on the right of the edge has winding number zero
+            winding[i] = crossing(tri, j);
+            visited[i] = true;
+            queue.push(i);
+        }
+    }
+    let mut idx = 0;
+    while idx < queue.len() {
+        let cur = queue[idx];
+        let tri = &triangles[cur];
+        for j in 0..3 {
+            let nei = tri.nei[j].tri;
+            if !visited[nei] {
+                visited[nei] = true;
+                winding[nei] = winding[cur] - crossing(tri, j);
+                queue.push(nei);
+            }
+        }
+        idx += 1;
+    }
+    winding
+}
+
+/// Triangulate the regions bounded by the directed `segments` which are inside
+/// according to `fill_rule`.
+pub fn triangulate_with_fill_rule<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    fill_rule: FillRule,
+    bump: A,
+) -> Vec<usize, A> {
+    let (triangles, ghost) = delaunay_skeleton(points, segments, bump);
+    let winding = winding_numbers(&triangles, &ghost, segments, |_| true, bump);
     let mut result = Vec::new_in(bump);
-    result.extend(
-        mesh.triangles
-            .iter()
-            .zip(ghost)
-            .filter_map(|(tri, is_ghost)| {
-                if is_ghost {
-                    None
-                } else {
-                    let data = &tri.data;
-                    Some([data[0], data[1], data[2]])
-                }
+    for (i, tri) in triangles.iter().enumerate() {
+        if !ghost[i] && fill_rule.contains(winding[i]) {
+            result.extend_from_slice(&tri.data[0..3]);
+        }
+    }
+    result
+}
+
+/// A seed point of a region of the domain. The region is made of the
+/// triangles reachable from the seed without crossing segments.
+#[derive(Clone, Copy, Debug)]
+pub struct RegionSeed {
+    pub point: [f64; 2],
+    pub attribute: f64,
+    /// The maximum area of the triangles of the region when refining.
+    pub max_area: f64,
+}
+
+/// Build the constrained Delaunay triangulation of `points` and `segments`.
+/// The triangles reachable from the convex hull, unless there are no segments,
+/// or from a point of `holes` without crossing segments are outside the domain. Returns all the
+/// triangles, whether each of them is outside the domain and the index of the
+/// seed in `regions` whose region contains it.
+pub(super) fn constrained_delaunay_with_seeds<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    holes: &[f64],
+    regions: &[RegionSeed],
+    bump: A,
+) -> (Vec<Triangle, A>, Vec<bool, A>, Vec<usize, A>) {
+    let (triangles, ghost) = delaunay_skeleton(points, segments, bump);
+    let mut outside = ghost.clone();
+    if !segments.is_empty() {
+        let hull = (0..triangles.len()).filter(|&i| {
+            !ghost[i]
+                && (0..3).any(|j| {
+                    triangles[i].data[j + 3] == INVALID_IND && ghost[triangles[i].nei[j].tri]
+                })
+        });
+        flood_fill(&triangles, hull, &mut outside, bump);
+    }
+    for hole in holes.chunks(2) {
+        if let Some(tri) = locate_brute_force(points, &triangles, &ghost, hole, bump) {
+            flood_fill(&triangles, [tri], &mut outside, bump);
+        }
+    }
+
+    let mut region_ids = std::vec::from_elem_in(INVALID_IND, triangles.len(), bump);
+    let mut visited = outside.clone();
+    for (k, region) in regions.iter().enumerate() {
+        if let Some(tri) = locate_brute_force(points, &triangles, &ghost, &region.point, bump) {
+            for t in flood_fill(&triangles, [tri], &mut visited, bump) {
+                region_ids[t] = k;
+            }
+        }
+    }
+    (triangles, outside, region_ids)
+}
+
+/// Triangulate the domain bounded by `segments` minus the regions containing
+/// the points of `holes`, like the `-A` switch of Triangle. Returns the
+/// triangles and the attribute of the region containing each of them, which
+/// is zero outside the regions of `regions`.
+pub fn triangulate_regions<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    holes: &[f64],
+    regions: &[RegionSeed],
+    bump: A,
+) -> (Vec<usize, A>, Vec<f64, A>) {
+    let (triangles, outside, region_ids) =
+        constrained_delaunay_with_seeds(points, segments, holes, regions, bump);
+    let mut result = Vec::new_in(bump);
+    let mut attributes = Vec::new_in(bump);
+    for (i, tri) in triangles.iter().enumerate() {
+        if outside[i] {
+            continue;
+        }
+        result.extend_from_slice(&tri.data[0..3]);
+        attributes.push(
+            regions
+                .get(region_ids[i])
+                .map_or(0.0, |region| region.attribute),
+        );
+    }
+    (result, attributes)
+}
+
+/// Collect the triangles reachable from `starts` without crossing segments
+/// and mark them as visited.
+fn flood_fill<A: Allocator + Copy>(
+    triangles: &[Triangle],
+    starts: impl IntoIterator<Item = usize>,
+    visited: &mut [bool],
+    bump: A,
+) -> Vec<usize, A> {
+    let mut queue = Vec::new_in(bump);
+    for tri in starts {
+        if !visited[tri] {
+            visited[tri] = true;
+            queue.push(tri);
+        }
+    }
+    let mut idx = 0;
+    while idx < queue.len() {
+        let tri = &triangles[queue[idx]];
+        for j in 0..3 {
+            let nei = tri.nei[j].tri;
+            if tri.data[j + 3] == INVALID_IND && !visited[nei] {
+                visited[nei] = true;
+                queue.push(nei);
+            }
+        }
+        idx += 1;
+    }
+    queue
+}
+
+/// Find the triangle containing the point `p`.
+fn locate_brute_force<A: Allocator + Copy>(
+    points: &[f64],
+    triangles: &[Triangle],
+    ghost: &[bool],
+    p: &[f64],
+    bump: A,
+) -> Option<usize> {
+    (0..triangles.len()).find(|&i| {
+        let data = &triangles[i].data;
+        !ghost[i]
+            && (0..3).all(|j| {
+                predicates::orient2d(
+                    point(points, data[j]),
+                    point(points, data[(j + 1) % 3]),
+                    p,
+                    bump,
+                ) >= 0.0
             })
-            .flatten(),
+    })
+}
+
+/// Triangulate the convex hull of `points` and insert `segments`, returns the
+/// triangles and whether each of them is a ghost triangle outside the hull.
+/// Only the first of coincident points is triangulated, and there are no
+/// triangles if less than two distinct points are given.
+pub(super) fn delaunay_skeleton<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    bump: A,
+) -> (Vec<Triangle, A>, Vec<bool, A>) {
+    let n_points = points.len() >> 1;
+    let mut sorted_pt_inds = Vec::with_capacity_in(n_points, bump);
+    sorted_pt_inds.extend(0..n_points);
+    sorted_pt_inds.sort_unstable_by(|&i, &j| {
+        let i = i << 1;
+        let j = j << 1;
+        (points[i], points[i + 1], i)
+            .partial_cmp(&(points[j], points[j + 1], j))
+            .unwrap()
+    });
+    // like Triangle, only the first of coincident vertices is triangulated and
+    // the segments use it instead of the others
+    let mut duplicates = HashMap::new_in(bump);
+    sorted_pt_inds.dedup_by(|&mut j, &mut i| {
+        let coincident = point(points, i)[..2] == point(points, j)[..2];
+        if coincident {
+            duplicates.insert(j, i);
+        }
+        coincident
+    });
+    if sorted_pt_inds.len() < 2 {
+        return (Vec::new_in(bump), Vec::new_in(bump));
+    }
+    let mut merged_segments = Vec::new_in(bump);
+    if !duplicates.is_empty() {
+        merged_segments.extend(
+            segments
+                .iter()
+                .map(|v| duplicates.get(v).copied().unwrap_or(*v)),
+        );
+    }
+    let segments = if duplicates.is_empty() {
+        segments
+    } else {
+        &merged_segments
+    };
+    // resort the array of points to accommodate alternating cuts
+    alternate_axes(points, &mut sorted_pt_inds, 0);
+
+    let mut mesh = Mesh {
+        points,
+        triangles: Vec::new_in(bump),
+    };
+    let mut hull_left = HEdge::default();
+    let mut hull_right = HEdge::default();
+    div_conq_recurse(
+        &mut mesh,
+        &sorted_pt_inds,
+        0,
+        &mut hull_left,
+        &mut hull_right,
+        bump,
     );
-    result
+
+    let ghost = mark_ghost(&mesh.triangles, &mut hull_left, bump);
+    // collinear points have no triangles to insert the segments into
+    if ghost.contains(&false) {
+        form_skeleton(&mut mesh, &ghost, segments, bump);
+    }
+    (mesh.triangles, ghost)
 }
 
 #[inline(always)]
@@ -106,6 +534,34 @@ pub fn triangulate_polygon<A: Allocator + Copy>(
     x: &[f64],
     y: &[f64],
     bump: A,
+) -> Vec<usize, A> {
+    triangulate_projected(points, segments, [o, x, y], None, bump)
+}
+
+/// Triangulate a polygon in 3D like [`triangulate_polygon`], keeping the
+/// regions which are inside according to `fill_rule`.
+#[inline]
+pub fn triangulate_polygon_with_fill_rule<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    o: &[f64],
+    x: &[f64],
+    y: &[f64],
+    fill_rule: FillRule,
+    bump: A,
+) -> Vec<usize, A> {
+    triangulate_projected(points, segments, [o, x, y], Some(fill_rule), bump)
+}
+
+/// Triangulate the polygon projected on the plane of origin `o` and axes `x`
+/// and `y`, with `fill_rule`, or with the segment marks of [`triangulate`] if
+/// there is none.
+fn triangulate_projected<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    [o, x, y]: [&[f64]; 3],
+    fill_rule: Option<FillRule>,
+    bump: A,
 ) -> Vec<usize, A> {
     let [new_segments, new_to_ori_map] = unique_indices(segments, bump);
     let mut points_2d = Vec::new_in(bump);
@@ -120,13 +576,138 @@ pub fn triangulate_polygon<A: Allocator + Copy>(
             })
             .flatten(),
     );
+    let triangles = match fill_rule {
+        Some(fill_rule) => triangulate_with_fill_rule(&points_2d, &new_segments, fill_rule, bump),
+        None => triangulate(&points_2d, &new_segments, bump),
+    };
+    let mut result = Vec::new_in(bump);
+    result.extend(triangles.into_iter().map(|idx| new_to_ori_map[idx]));
+    result
+}
+
+/// The reasons a planar polygon cannot be triangulated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolygonError {
+    /// The outer loop has no area.
+    Degenerate,
+    /// A vertex is farther from the plane of the outer loop than the tolerance.
+    NonPlanar,
+    /// Two edges of the loops cross, overlap or touch outside their endpoints.
+    SelfIntersecting,
+    /// The offsets are fewer than two, decreasing or past the end of the loops.
+    InvalidOffsets,
+}
+
+impl std::fmt::Display for PolygonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolygonError::Degenerate => write!(f, "the polygon has no area"),
+            PolygonError::NonPlanar => write!(f, "the polygon is not planar"),
+            PolygonError::SelfIntersecting => write!(f, "the polygon intersects itself"),
+            PolygonError::InvalidOffsets => write!(f, "the offsets of the loops are invalid"),
+        }
+    }
+}
+
+impl std::error::Error for PolygonError {}
+
+/// Triangulate a planar polygon with holes in 3D. The vertices of the loops are
+/// given one after another with the offset of each loop, like the `loops` and
+/// `offsets` of [`PolygonLoops`](super::PolygonLoops), the first loop being the
+/// outer boundary and the others the holes, in any orientation. The polygon is
+/// projected along the largest component of the normal of the outer loop, so
+/// the coordinates are not rounded, and the predicates run on the projection.
+///
+/// Returns the triangles, oriented like the outer loop, or an error if the
+/// offsets do not split the loops, if a vertex is farther than `tolerance` from
+/// the plane of the outer loop or if the edges of the projection intersect
+/// each other.
+pub fn triangulate_planar_polygon<A: Allocator + Copy>(
+    points: &[f64],
+    loops: &[usize],
+    offsets: &[usize],
+    tolerance: f64,
+    bump: A,
+) -> Result<Vec<usize, A>, PolygonError> {
+    if offsets.len() < 2
+        || offsets.windows(2).any(|range| range[0] > range[1])
+        || offsets[offsets.len() - 1] > loops.len()
+    {
+        return Err(PolygonError::InvalidOffsets);
+    }
+
+    // the normal of the outer loop by Newell's method
+    let outer = &loops[offsets[0]..offsets[1]];
+    let mut normal = [0.0; 3];
+    let mut center = [0.0; 3];
+    for (i, &vid) in outer.iter().enumerate() {
+        let p = point3(points, vid);
+        let q = point3(points, outer[(i + 1) % outer.len()]);
+        for axis in 0..3 {
+            let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+            normal[axis] += (p[u] - q[u]) * (p[v] + q[v]);
+            center[axis] += p[axis] / outer.len() as f64;
+        }
+    }
+    let length = normal.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if length == 0.0 {
+        return Err(PolygonError::Degenerate);
+    }
+    let mut offset = [0.0; 3];
+    for &vid in &loops[offsets[0]..offsets[offsets.len() - 1]] {
+        sub(point3(points, vid), &center, &mut offset);
+        if dot(&offset, &normal).abs() > tolerance * length {
+            return Err(PolygonError::NonPlanar);
+        }
+    }
+
+    // keep the outer loop counterclockwise in the projection
+    let axis = (0..3)
+        .max_by(|&i, &j| normal[i].abs().total_cmp(&normal[j].abs()))
+        .unwrap();
+    let (u, v) = if normal[axis] > 0.0 {
+        ((axis + 1) % 3, (axis + 2) % 3)
+    } else {
+        ((axis + 2) % 3, (axis + 1) % 3)
+    };
+    let mut segments = Vec::with_capacity_in(loops.len() * 2, bump);
+    for range in offsets.windows(2) {
+        let ring = &loops[range[0]..range[1]];
+        for (i, &vid) in ring.iter().enumerate() {
+            segments.extend([vid, ring[(i + 1) % ring.len()]]);
+        }
+    }
+    let [new_segments, new_to_ori_map] = unique_indices(&segments, bump);
+    let mut points_2d = Vec::with_capacity_in(new_to_ori_map.len() * 2, bump);
+    points_2d.extend(new_to_ori_map.iter().flat_map(|&idx| {
+        let p = point3(points, idx);
+        [p[u], p[v]]
+    }));
+
+    // the edges must only be split at their endpoints, and not be repeated
+    let Some((all_points, sub_segments)) = split_intersections(&points_2d, &new_segments, bump)
+    else {
+        return Err(PolygonError::SelfIntersecting);
+    };
+    if all_points.len() > points_2d.len() {
+        return Err(PolygonError::SelfIntersecting);
+    }
+    let mut edges = HashMap::with_capacity_in(sub_segments.len(), bump);
+    for &[a, b, parent] in &sub_segments {
+        let [c, d] = [new_segments[parent * 2], new_segments[parent * 2 + 1]];
+        let same = |x: usize, y: usize| point(&points_2d, x)[..2] == point(&points_2d, y)[..2];
+        if !(same(a, c) && same(b, d)) || edges.insert((a.min(b), a.max(b)), ()).is_some() {
+            return Err(PolygonError::SelfIntersecting);
+        }
+    }
+
     let mut result = Vec::new_in(bump);
     result.extend(
-        triangulate(&points_2d, &new_segments, bump)
+        triangulate_with_fill_rule(&points_2d, &new_segments, FillRule::EvenOdd, bump)
             .into_iter()
             .map(|idx| new_to_ori_map[idx]),
     );
-    result
+    Ok(result)
 }
 
 #[inline]
@@ -134,30 +715,110 @@ pub fn triangulate_polygon_soup(
     points: &[f64],
     edges: &[Vec<usize>],
     axes: &[f64],
+) -> (Vec<usize>, Vec<usize>) {
+    triangulate_faces(points, edges, axes, None, 0, &mut Bump::new())
+}
+
+/// Triangulate the polygons like [`triangulate_polygon_soup`], keeping the
+/// regions of each polygon which are inside according to `fill_rule`.
+#[inline]
+pub fn triangulate_polygon_soup_with_fill_rule(
+    points: &[f64],
+    edges: &[Vec<usize>],
+    axes: &[f64],
+    fill_rule: FillRule,
+) -> (Vec<usize>, Vec<usize>) {
+    triangulate_faces(points, edges, axes, Some(fill_rule), 0, &mut Bump::new())
+}
+
+/// Triangulate the polygons like [`triangulate_polygon_soup`] on all the
+/// available threads. The polygons are handed out to the threads in batches,
+/// each thread with its own arena, and the result is the same as the one of
+/// the sequential version.
+pub fn triangulate_polygon_soup_parallel(
+    points: &[f64],
+    edges: &[Vec<usize>],
+    axes: &[f64],
+) -> (Vec<usize>, Vec<usize>) {
+    const BATCH_SIZE: usize = 256;
+    let n_batches = edges.len().div_ceil(BATCH_SIZE);
+    let n_threads = std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(n_batches);
+    let next_batch = AtomicUsize::new(0);
+    let mut batches = std::thread::scope(|scope| {
+        let handles = Vec::from_iter((0..n_threads).map(|_| {
+            scope.spawn(|| {
+                let mut bump = Bump::new();
+                let mut results = Vec::new();
+                loop {
+                    let batch = next_batch.fetch_add(1, Ordering::Relaxed);
+                    if batch >= n_batches {
+                        break;
+                    }
+                    let start = batch * BATCH_SIZE;
+                    let end = (start + BATCH_SIZE).min(edges.len());
+                    let (triangles, parents) = triangulate_faces(
+                        points,
+                        &edges[start..end],
+                        &axes[(start * 9)..(end * 9)],
+                        None,
+                        start,
+                        &mut bump,
+                    );
+                    results.push((batch, triangles, parents));
+                }
+                results
+            })
+        }));
+        Vec::from_iter(
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap()),
+        )
+    });
+    batches.sort_unstable_by_key(|(batch, _, _)| *batch);
+
+    let mut triangles = Vec::new();
+    let mut parents = Vec::new();
+    for (_, batch_triangles, batch_parents) in batches {
+        triangles.extend(batch_triangles);
+        parents.extend(batch_parents);
+    }
+    (triangles, parents)
+}
+
+/// Triangulate the polygons one after another, their parents are numbered from
+/// `first_parent`.
+fn triangulate_faces(
+    points: &[f64],
+    edges: &[Vec<usize>],
+    axes: &[f64],
+    fill_rule: Option<FillRule>,
+    first_parent: usize,
+    bump: &mut Bump,
 ) -> (Vec<usize>, Vec<usize>) {
     let mut triangles = Vec::new();
     let mut parents = Vec::new();
-    let mut bump = Bump::new();
     for (idx, (segments, axis_data)) in edges.iter().zip(axes.chunks(9)).enumerate() {
         bump.reset();
-        let face_triangles = triangulate_polygon(
+        let face_triangles = triangulate_projected(
             points,
             segments,
-            &axis_data[0..3],
-            &axis_data[3..6],
-            &axis_data[6..9],
-            &bump,
+            [&axis_data[0..3], &axis_data[3..6], &axis_data[6..9]],
+            fill_rule,
+            &*bump,
         );
-        parents.resize(parents.len() + face_triangles.len() / 3, idx);
+        parents.resize(parents.len() + face_triangles.len() / 3, first_parent + idx);
         triangles.extend(face_triangles);
     }
     (triangles, parents)
 }
 
 #[derive(Clone, PartialEq, Eq)]
-struct HEdge {
-    tri: usize,
-    ori: usize,
+pub(super) struct HEdge {
+    pub(super) tri: usize,
+    pub(super) ori: usize,
 }
 
 impl Default for HEdge {
@@ -169,9 +830,11 @@ impl Default for HEdge {
     }
 }
 
-struct Triangle {
-    data: [usize; 6],
-    nei: [HEdge; 3],
+/// The vertices of the triangle followed by the marks of the segments on its
+/// edges, the edge `i` is opposite to the vertex `i`.
+pub(super) struct Triangle {
+    pub(super) data: [usize; 6],
+    pub(super) nei: [HEdge; 3],
 }
 
 impl Default for Triangle {
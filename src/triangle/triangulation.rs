@@ -0,0 +1,939 @@
+use std::{alloc::Global, collections::VecDeque};
+
+use hashbrown::HashMap;
+
+use crate::{predicates, INVALID_IND};
+
+use super::triangulate;
+
+/// The vertex at infinity shared by the ghost triangles outside the convex hull.
+const GHOST_VERTEX: usize = INVALID_IND - 1;
+
+#[inline(always)]
+fn point(points: &[f64], idx: usize) -> &[f64] {
+    &points[(idx << 1)..((idx << 1) + 2)]
+}
+
+/// Where a point lies in a [`Triangulation2D`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Location {
+    /// On the vertex.
+    Vertex(usize),
+    /// On the edge opposite to the vertex `i` of the triangle, between its
+    /// endpoints.
+    Edge(usize, usize),
+    /// Inside the triangle.
+    Triangle(usize),
+    /// Outside the convex hull, or there are no triangles yet.
+    Outside,
+}
+
+/// A constrained Delaunay triangulation edited one point or segment at a time.
+/// The indices of the vertices and segments are never reused, the indices of
+/// the triangles are only valid until the next modification.
+pub struct Triangulation2D {
+    points: Vec<f64>,
+    removed: Vec<bool>,
+    /// a triangle incident to each vertex, invalid until the vertices span the
+    /// plane
+    vertex_triangles: Vec<usize>,
+    /// vertices of each triangle in counterclockwise order, the ghost triangles
+    /// outside the convex hull share `GHOST_VERTEX`
+    triangles: Vec<[usize; 3]>,
+    /// the triangle across the edge opposite to each vertex
+    neighbors: Vec<[usize; 3]>,
+    /// the segment on the edge opposite to each vertex
+    edge_segments: Vec<[usize; 3]>,
+    free_triangles: Vec<usize>,
+    n_solid_triangles: usize,
+    segments: Vec<Option<[usize; 2]>>,
+    /// the triangle to start the point location from
+    hint: usize,
+}
+
+impl Default for Triangulation2D {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Triangulation2D {
+    pub fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            removed: Vec::new(),
+            vertex_triangles: Vec::new(),
+            triangles: Vec::new(),
+            neighbors: Vec::new(),
+            edge_segments: Vec::new(),
+            free_triangles: Vec::new(),
+            n_solid_triangles: 0,
+            segments: Vec::new(),
+            hint: INVALID_IND,
+        }
+    }
+
+    /// The coordinates of all the vertices, including the removed ones.
+    #[inline(always)]
+    pub fn points(&self) -> &[f64] {
+        &self.points
+    }
+
+    #[inline(always)]
+    pub fn point(&self, vid: usize) -> &[f64] {
+        point(&self.points, vid)
+    }
+
+    /// The vertices which are not removed.
+    pub fn vertices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.removed.len()).filter(|&v| !self.removed[v])
+    }
+
+    /// The endpoints of the inserted segments, `None` for the removed ones.
+    #[inline(always)]
+    pub fn segments(&self) -> &[Option<[usize; 2]>] {
+        &self.segments
+    }
+
+    #[inline(always)]
+    pub fn n_triangles(&self) -> usize {
+        self.n_solid_triangles
+    }
+
+    /// The triangles inside the convex hull.
+    pub fn triangles(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.triangles.len()).filter(|&t| self.is_alive(t) && !self.is_ghost(t))
+    }
+
+    /// The vertices of the triangle in counterclockwise order.
+    #[inline(always)]
+    pub fn triangle(&self, tid: usize) -> [usize; 3] {
+        self.triangles[tid]
+    }
+
+    /// The triangle across the edge opposite to the vertex `i` of `tid`, `None`
+    /// on the convex hull.
+    #[inline(always)]
+    pub fn neighbor(&self, tid: usize, i: usize) -> Option<usize> {
+        let nei = self.neighbors[tid][i];
+        if self.is_ghost(nei) {
+            None
+        } else {
+            Some(nei)
+        }
+    }
+
+    /// The segment on the edge opposite to the vertex `i` of `tid`.
+    #[inline(always)]
+    pub fn segment(&self, tid: usize, i: usize) -> Option<usize> {
+        let seg = self.edge_segments[tid][i];
+        if seg == INVALID_IND {
+            None
+        } else {
+            Some(seg)
+        }
+    }
+
+    /// The triangles around the vertex in counterclockwise order.
+    pub fn incident_triangles(&self, vid: usize) -> Vec<usize> {
+        if self.vertex_triangles[vid] == INVALID_IND {
+            return Vec::new();
+        }
+        let mut star = self.star(vid);
+        star.retain(|&t| !self.is_ghost(t));
+        star
+    }
+
+    /// Find where the point `p` lies.
+    #[inline(always)]
+    pub fn locate(&self, p: &[f64]) -> Location {
+        self.walk(p).1
+    }
+
+    /// Insert the point `p`, returns its index or the index of the vertex at
+    /// the same position.
+    pub fn insert_point(&mut self, p: [f64; 2]) -> usize {
+        if self.n_solid_triangles == 0 {
+            if let Some(vid) = self.vertices().find(|&v| self.point(v) == p) {
+                return vid;
+            }
+            let vid = self.push_point(p);
+            self.triangulate_pending();
+            return vid;
+        }
+        let (tid, location) = self.walk(&p);
+        if let Location::Vertex(vid) = location {
+            return vid;
+        }
+        let vid = self.push_point(p);
+        self.insert_vertex(vid, tid, location);
+        vid
+    }
+
+    /// Insert the segment between the vertices `a` and `b`, splitting it at the
+    /// vertices it passes through. Returns the index of the segment, or `None`
+    /// if it crosses or overlaps an existing segment.
+    pub fn insert_segment(&mut self, a: usize, b: usize) -> Option<usize> {
+        if a == b || self.removed[a] || self.removed[b] {
+            return None;
+        }
+        let seg = self.segments.len();
+        if self.n_solid_triangles == 0 {
+            // checked when the vertices span the plane
+            self.segments.push(Some([a, b]));
+            return Some(seg);
+        }
+        let pieces = self.segment_pieces(a, b)?;
+        self.segments.push(Some([a, b]));
+        for [u, w] in pieces {
+            self.insert_piece(u, w, seg);
+        }
+        Some(seg)
+    }
+
+    /// Remove the vertex and the segments ending at it, the segment passing
+    /// through it is joined. Returns `false` and keeps the vertex if several
+    /// segments pass through it, as they would cross, or if the hole left by
+    /// the vertex cannot be triangulated.
+    pub fn remove_point(&mut self, vid: usize) -> bool {
+        if self.removed[vid] {
+            return true;
+        }
+        let mut hole = None;
+        if self.vertex_triangles[vid] != INVALID_IND {
+            let star = self.star(vid);
+            let mut link = Vec::with_capacity(star.len());
+            let mut through = Vec::new();
+            for &t in &star {
+                let k = self.vertex_index(t, vid);
+                let next = self.triangles[t][(k + 1) % 3];
+                link.push(next);
+                let seg = self.edge_segments[t][(k + 2) % 3];
+                if seg != INVALID_IND && !self.segments[seg].unwrap().contains(&vid) {
+                    through.push((seg, next));
+                }
+            }
+            through.sort_unstable();
+            if through
+                .first()
+                .is_some_and(|first| first.0 != through[through.len() - 1].0)
+            {
+                return false;
+            }
+            let new_triangles = if let Some(pos) = link.iter().position(|&v| v == GHOST_VERTEX) {
+                link.rotate_left(pos + 1);
+                link.pop();
+                self.fill_hull_notch(&link)
+            } else {
+                match self.fill_star_hole(&link) {
+                    Some(triangles) => triangles,
+                    None => return false,
+                }
+            };
+            hole = Some((star, through, new_triangles));
+        }
+        self.removed[vid] = true;
+        let mut released = Vec::new();
+        for seg in 0..self.segments.len() {
+            if self.segments[seg].is_some_and(|ends| ends.contains(&vid)) {
+                self.segments[seg] = None;
+                released.extend(self.clear_segment(seg));
+            }
+        }
+        let Some((star, through, new_triangles)) = hole else {
+            return true;
+        };
+
+        let ids = self.replace(&star, &new_triangles);
+        self.vertex_triangles[vid] = INVALID_IND;
+        if self.n_solid_triangles == 0 {
+            // the remaining vertices are collinear
+            self.clear_triangles();
+            self.triangulate_pending();
+            return true;
+        }
+        // the edges of the removed segments may not be Delaunay anymore
+        released.retain(|(_, edge)| !edge.contains(&vid));
+        let mut stack = Vec::from_iter(
+            ids.iter()
+                .flat_map(|&t| (0..3).map(move |i| (t, i)))
+                .map(|(t, i)| (t, self.edge(t, i)))
+                .chain(released),
+        );
+        self.legalize(&mut stack);
+
+        for pair in through.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                self.insert_piece(pair[0].1, pair[1].1, pair[0].0);
+            }
+        }
+        true
+    }
+
+    #[inline(always)]
+    fn is_alive(&self, tid: usize) -> bool {
+        self.triangles[tid][0] != INVALID_IND
+    }
+
+    #[inline(always)]
+    fn is_ghost(&self, tid: usize) -> bool {
+        self.triangles[tid].contains(&GHOST_VERTEX)
+    }
+
+    #[inline(always)]
+    fn edge(&self, tid: usize, i: usize) -> [usize; 2] {
+        let tri = &self.triangles[tid];
+        [tri[(i + 1) % 3], tri[(i + 2) % 3]]
+    }
+
+    #[inline(always)]
+    fn vertex_index(&self, tid: usize, vid: usize) -> usize {
+        self.triangles[tid].iter().position(|&v| v == vid).unwrap()
+    }
+
+    #[inline(always)]
+    fn orient(&self, a: usize, b: usize, c: usize) -> f64 {
+        predicates::orient2d(self.point(a), self.point(b), self.point(c), Global)
+    }
+
+    /// Whether `p` lies in the circumcircle of the triangle, or beyond the hull
+    /// edge of a ghost triangle.
+    fn in_circle(&self, tid: usize, p: &[f64]) -> bool {
+        let tri = &self.triangles[tid];
+        if let Some(k) = tri.iter().position(|&v| v == GHOST_VERTEX) {
+            let [a, b] = self.edge(tid, k);
+            predicates::orient2d(self.point(a), self.point(b), p, Global) > 0.0
+        } else {
+            predicates::incircle(
+                self.point(tri[0]),
+                self.point(tri[1]),
+                self.point(tri[2]),
+                p,
+                Global,
+            ) > 0.0
+        }
+    }
+
+    fn push_point(&mut self, p: [f64; 2]) -> usize {
+        let vid = self.removed.len();
+        self.points.extend(p);
+        self.removed.push(false);
+        self.vertex_triangles.push(INVALID_IND);
+        vid
+    }
+
+    fn new_triangle(&mut self, tri: [usize; 3]) -> usize {
+        let tid = if let Some(tid) = self.free_triangles.pop() {
+            self.triangles[tid] = tri;
+            self.neighbors[tid] = [INVALID_IND; 3];
+            self.edge_segments[tid] = [INVALID_IND; 3];
+            tid
+        } else {
+            self.triangles.push(tri);
+            self.neighbors.push([INVALID_IND; 3]);
+            self.edge_segments.push([INVALID_IND; 3]);
+            self.triangles.len() - 1
+        };
+        for v in tri {
+            if v != GHOST_VERTEX {
+                self.vertex_triangles[v] = tid;
+            }
+        }
+        if !self.is_ghost(tid) {
+            self.n_solid_triangles += 1;
+            self.hint = tid;
+        }
+        tid
+    }
+
+    fn delete_triangle(&mut self, tid: usize) {
+        if !self.is_ghost(tid) {
+            self.n_solid_triangles -= 1;
+        }
+        self.triangles[tid] = [INVALID_IND; 3];
+        self.free_triangles.push(tid);
+    }
+
+    fn clear_triangles(&mut self) {
+        self.triangles.clear();
+        self.neighbors.clear();
+        self.edge_segments.clear();
+        self.free_triangles.clear();
+        self.vertex_triangles.fill(INVALID_IND);
+        self.n_solid_triangles = 0;
+        self.hint = INVALID_IND;
+    }
+
+    /// Replace the triangles `old` by the triangles `new` covering the same
+    /// region, returns the indices of the new triangles. The segments on the
+    /// boundary of the region are kept.
+    fn replace(&mut self, old: &[usize], new: &[[usize; 3]]) -> Vec<usize> {
+        let mut boundary = HashMap::new();
+        for &t in old {
+            for i in 0..3 {
+                let nei = self.neighbors[t][i];
+                if !old.contains(&nei) {
+                    boundary.insert(self.edge(t, i), (nei, self.edge_segments[t][i]));
+                }
+            }
+        }
+        for &t in old {
+            self.delete_triangle(t);
+        }
+        let ids = Vec::from_iter(new.iter().map(|&tri| self.new_triangle(tri)));
+        let mut inner = HashMap::new();
+        for &t in &ids {
+            for i in 0..3 {
+                let [a, b] = self.edge(t, i);
+                if let Some(&(nei, seg)) = boundary.get(&[a, b]) {
+                    self.neighbors[t][i] = nei;
+                    self.edge_segments[t][i] = seg;
+                    let j = (0..3).find(|&j| self.edge(nei, j) == [b, a]).unwrap();
+                    self.neighbors[nei][j] = t;
+                    if !self.is_ghost(nei) {
+                        self.hint = nei;
+                    }
+                } else if let Some((nei, j)) = inner.remove(&[b, a]) {
+                    self.neighbors[t][i] = nei;
+                    self.neighbors[nei][j] = t;
+                } else {
+                    inner.insert([a, b], (t, i));
+                }
+            }
+        }
+        debug_assert!(inner.is_empty());
+        ids
+    }
+
+    /// The triangles around the vertex in counterclockwise order, including
+    /// the ghost triangles.
+    fn star(&self, vid: usize) -> Vec<usize> {
+        let start = self.vertex_triangles[vid];
+        let mut star = Vec::new();
+        let mut t = start;
+        loop {
+            star.push(t);
+            let k = self.vertex_index(t, vid);
+            t = self.neighbors[t][(k + 1) % 3];
+            if t == start {
+                break;
+            }
+        }
+        star
+    }
+
+    /// The triangle containing the directed edge from `a` to `b` and the index
+    /// of the edge.
+    fn find_edge(&self, a: usize, b: usize) -> Option<(usize, usize)> {
+        if a == GHOST_VERTEX {
+            let (t, i) = self.find_edge(b, a)?;
+            let nei = self.neighbors[t][i];
+            return Some((nei, (0..3).find(|&j| self.edge(nei, j) == [a, b])?));
+        }
+        self.star(a).into_iter().find_map(|t| {
+            let k = self.vertex_index(t, a);
+            if self.triangles[t][(k + 1) % 3] == b {
+                Some((t, (k + 2) % 3))
+            } else {
+                None
+            }
+        })
+    }
+
+    fn set_segment(&mut self, a: usize, b: usize, seg: usize) {
+        let (t, i) = self.find_edge(a, b).unwrap();
+        let nei = self.neighbors[t][i];
+        let j = (0..3).find(|&j| self.edge(nei, j) == [b, a]).unwrap();
+        self.edge_segments[t][i] = seg;
+        self.edge_segments[nei][j] = seg;
+    }
+
+    /// Unmark the edges of the segment, returns them.
+    fn clear_segment(&mut self, seg: usize) -> Vec<(usize, [usize; 2])> {
+        let mut edges = Vec::new();
+        for t in 0..self.triangles.len() {
+            if !self.is_alive(t) {
+                continue;
+            }
+            for i in 0..3 {
+                if self.edge_segments[t][i] == seg {
+                    self.edge_segments[t][i] = INVALID_IND;
+                    edges.push((t, self.edge(t, i)));
+                }
+            }
+        }
+        edges
+    }
+
+    fn start_triangle(&self) -> usize {
+        if self.hint < self.triangles.len() && self.is_alive(self.hint) && !self.is_ghost(self.hint)
+        {
+            self.hint
+        } else {
+            self.triangles().next().unwrap()
+        }
+    }
+
+    /// Walk from the hint towards `p`, choosing the edge to cross at random to
+    /// avoid cycles. Returns the last visited triangle, which is the ghost
+    /// triangle beyond whose hull edge `p` lies if it is outside.
+    fn walk(&self, p: &[f64]) -> (usize, Location) {
+        if self.n_solid_triangles == 0 {
+            return (INVALID_IND, Location::Outside);
+        }
+        let mut t = self.start_triangle();
+        let mut seed = 0x2545f491u32;
+        loop {
+            if self.is_ghost(t) {
+                return (t, Location::Outside);
+            }
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            let first = (seed % 3) as usize;
+            let mut orients = [0.0; 3];
+            let mut exit = None;
+            for k in 0..3 {
+                let i = (first + k) % 3;
+                let [a, b] = self.edge(t, i);
+                orients[i] = predicates::orient2d(self.point(a), self.point(b), p, Global);
+                if orients[i] < 0.0 {
+                    exit = Some(i);
+                    break;
+                }
+            }
+            if let Some(i) = exit {
+                t = self.neighbors[t][i];
+                continue;
+            }
+            if let Some(&v) = self.triangles[t].iter().find(|&&v| self.point(v) == p) {
+                return (t, Location::Vertex(v));
+            }
+            return match (0..3).find(|&i| orients[i] == 0.0) {
+                Some(i) => (t, Location::Edge(t, i)),
+                None => (t, Location::Triangle(t)),
+            };
+        }
+    }
+
+    /// Triangulate the vertices once they span the plane, then insert the
+    /// pending segments.
+    fn triangulate_pending(&mut self) {
+        let vertices = Vec::from_iter(self.vertices());
+        if vertices.len() < 3 {
+            return;
+        }
+        let (a, b) = (vertices[0], vertices[1]);
+        let Some([a, b, c]) = vertices[2..].iter().find_map(|&c| {
+            let ori = self.orient(a, b, c);
+            if ori > 0.0 {
+                Some([a, b, c])
+            } else if ori < 0.0 {
+                Some([b, a, c])
+            } else {
+                None
+            }
+        }) else {
+            return;
+        };
+        self.replace(
+            &[],
+            &[
+                [a, b, c],
+                [b, a, GHOST_VERTEX],
+                [c, b, GHOST_VERTEX],
+                [a, c, GHOST_VERTEX],
+            ],
+        );
+        for v in vertices {
+            if v != a && v != b && v != c {
+                let (tid, location) = self.walk(self.point(v));
+                self.insert_vertex(v, tid, location);
+            }
+        }
+        for seg in 0..self.segments.len() {
+            let Some([a, b]) = self.segments[seg] else {
+                continue;
+            };
+            match self.segment_pieces(a, b) {
+                Some(pieces) => {
+                    for [u, w] in pieces {
+                        self.insert_piece(u, w, seg);
+                    }
+                }
+                None => self.segments[seg] = None,
+            }
+        }
+    }
+
+    /// Connect the new vertex to the triangle or edge containing it and flip
+    /// the edges which are not locally Delaunay.
+    fn insert_vertex(&mut self, vid: usize, tid: usize, location: Location) {
+        let ids = match location {
+            Location::Triangle(_) | Location::Outside => {
+                let [a, b, c] = self.triangles[tid];
+                self.replace(&[tid], &[[vid, b, c], [vid, c, a], [vid, a, b]])
+            }
+            Location::Edge(_, i) => {
+                let c = self.triangles[tid][i];
+                let [a, b] = self.edge(tid, i);
+                let nei = self.neighbors[tid][i];
+                let d = self.triangles[nei][(self.vertex_index(nei, a) + 1) % 3];
+                let seg = self.edge_segments[tid][i];
+                let ids = self.replace(
+                    &[tid, nei],
+                    &[[vid, b, c], [vid, c, a], [vid, a, d], [vid, d, b]],
+                );
+                if seg != INVALID_IND {
+                    self.set_segment(vid, a, seg);
+                    self.set_segment(vid, b, seg);
+                }
+                ids
+            }
+            Location::Vertex(_) => unreachable!(),
+        };
+        let mut stack = Vec::from_iter(ids.into_iter().map(|t| (t, self.edge(t, 0))));
+        self.legalize(&mut stack);
+    }
+
+    /// Whether the edge `i` of `tid` is neither constrained nor locally
+    /// Delaunay.
+    fn is_illegal(&self, tid: usize, i: usize) -> bool {
+        if self.edge_segments[tid][i] != INVALID_IND {
+            return false;
+        }
+        let nei = self.neighbors[tid][i];
+        let a = self.edge(tid, i)[0];
+        let apex = self.triangles[tid][i];
+        let far = self.triangles[nei][(self.vertex_index(nei, a) + 1) % 3];
+        if apex == GHOST_VERTEX || far == GHOST_VERTEX {
+            return false;
+        }
+        self.in_circle(nei, self.point(apex))
+    }
+
+    /// Flip the edge `i` of `tid`, its opposite vertex stays the first vertex
+    /// of both triangles.
+    fn flip(&mut self, tid: usize, i: usize) -> usize {
+        let nei = self.neighbors[tid][i];
+        let a = self.triangles[tid][i];
+        let [b, c] = self.edge(tid, i);
+        let j = (self.vertex_index(nei, b) + 1) % 3;
+        let d = self.triangles[nei][j];
+        let (n_ca, s_ca) = (
+            self.neighbors[tid][(i + 1) % 3],
+            self.edge_segments[tid][(i + 1) % 3],
+        );
+        let (n_ab, s_ab) = (
+            self.neighbors[tid][(i + 2) % 3],
+            self.edge_segments[tid][(i + 2) % 3],
+        );
+        let (n_bd, s_bd) = (
+            self.neighbors[nei][(j + 1) % 3],
+            self.edge_segments[nei][(j + 1) % 3],
+        );
+        let (n_dc, s_dc) = (
+            self.neighbors[nei][(j + 2) % 3],
+            self.edge_segments[nei][(j + 2) % 3],
+        );
+        // flipping an edge between ghost triangles makes one of them solid
+        if a != GHOST_VERTEX && d != GHOST_VERTEX && (b == GHOST_VERTEX || c == GHOST_VERTEX) {
+            self.n_solid_triangles += 1;
+        }
+        self.triangles[tid] = [a, b, d];
+        self.neighbors[tid] = [n_bd, nei, n_ab];
+        self.edge_segments[tid] = [s_bd, INVALID_IND, s_ab];
+        self.triangles[nei] = [a, d, c];
+        self.neighbors[nei] = [n_dc, n_ca, tid];
+        self.edge_segments[nei] = [s_dc, s_ca, INVALID_IND];
+        for (outer, edge, new) in [(n_bd, [d, b], tid), (n_ca, [a, c], nei)] {
+            let k = (0..3).find(|&k| self.edge(outer, k) == edge).unwrap();
+            self.neighbors[outer][k] = new;
+        }
+        for (v, t) in [(a, tid), (b, tid), (d, tid), (c, nei)] {
+            if v != GHOST_VERTEX {
+                self.vertex_triangles[v] = t;
+            }
+        }
+        nei
+    }
+
+    /// Flip the edges of the stack until all of them are locally Delaunay.
+    fn legalize(&mut self, stack: &mut Vec<(usize, [usize; 2])>) {
+        while let Some((t, edge)) = stack.pop() {
+            // the edge moves to the other triangle when its triangle is flipped
+            let found = if self.is_alive(t) {
+                (0..3).find(|&i| self.edge(t, i) == edge).map(|i| (t, i))
+            } else {
+                None
+            };
+            let Some((t, i)) = found.or_else(|| self.find_edge(edge[0], edge[1])) else {
+                continue;
+            };
+            if !self.is_illegal(t, i) {
+                continue;
+            }
+            let nei = self.flip(t, i);
+            stack.extend([(t, self.edge(t, 0)), (t, self.edge(t, 2))]);
+            stack.extend([(nei, self.edge(nei, 0)), (nei, self.edge(nei, 1))]);
+        }
+    }
+
+    /// Triangulate the star shaped polygon left by the removal of an interior
+    /// vertex with the constrained Delaunay triangulation of its edges, which
+    /// handles the collinear runs of the polygon. Returns `None` if the
+    /// triangles do not cover the polygon.
+    fn fill_star_hole(&self, polygon: &[usize]) -> Option<Vec<[usize; 3]>> {
+        let n = polygon.len();
+        let points = Vec::from_iter(polygon.iter().flat_map(|&v| self.point(v).iter().copied()));
+        let segments = Vec::from_iter((0..n).flat_map(|i| [i, (i + 1) % n]));
+        let triangles = triangulate(&points, &segments, Global);
+        if triangles.len() != (n - 2) * 3 {
+            return None;
+        }
+        let result = Vec::from_iter(
+            triangles
+                .chunks(3)
+                .map(|tri| [tri[0], tri[1], tri[2]].map(|i| polygon[i])),
+        );
+        if result.iter().all(|&[a, b, c]| self.orient(a, b, c) > 0.0) {
+            Some(result)
+        } else {
+            None
+        }
+    }
+
+    /// Triangulate the region left by the removal of a hull vertex, `chain` is
+    /// its link from the next to the previous vertex on the hull.
+    fn fill_hull_notch(&self, chain: &[usize]) -> Vec<[usize; 3]> {
+        let mut result = Vec::new();
+        let mut hull = Vec::with_capacity(chain.len());
+        for &c in chain {
+            while hull.len() >= 2
+                && self.orient(hull[hull.len() - 2], hull[hull.len() - 1], c) > 0.0
+            {
+                result.push([hull[hull.len() - 2], hull[hull.len() - 1], c]);
+                hull.pop();
+            }
+            hull.push(c);
+        }
+        result.extend(hull.windows(2).map(|w| [w[0], w[1], GHOST_VERTEX]));
+        result
+    }
+
+    /// Follow the segment from `a` towards `b` up to `b` or the first vertex on
+    /// it, returns this vertex and the edges crossed, or `None` if a segment is
+    /// crossed.
+    fn trace(&self, a: usize, b: usize) -> Option<(usize, Vec<[usize; 2]>)> {
+        let pa = self.point(a);
+        let pb = self.point(b);
+        let ahead = |v: usize| {
+            let pv = self.point(v);
+            (pv[0] - pa[0]) * (pb[0] - pa[0]) + (pv[1] - pa[1]) * (pb[1] - pa[1]) > 0.0
+        };
+        for t in self.star(a) {
+            if self.is_ghost(t) {
+                continue;
+            }
+            let k = self.vertex_index(t, a);
+            let [x, y] = self.edge(t, k);
+            if x == b || y == b {
+                return Some((b, Vec::new()));
+            }
+            let (ox, oy) = (self.orient(a, b, x), self.orient(a, b, y));
+            if ox == 0.0 && ahead(x) {
+                return Some((x, Vec::new()));
+            }
+            if oy == 0.0 && ahead(y) {
+                return Some((y, Vec::new()));
+            }
+            if !(ox < 0.0 && oy > 0.0) {
+                continue;
+            }
+            let mut crossed = Vec::new();
+            let (mut left, mut right, mut t) = (y, x, t);
+            loop {
+                let i = (0..3)
+                    .find(|&i| self.triangles[t][i] != left && self.triangles[t][i] != right)
+                    .unwrap();
+                if self.edge_segments[t][i] != INVALID_IND {
+                    return None;
+                }
+                crossed.push([left, right]);
+                t = self.neighbors[t][i];
+                let far = self.triangles[t][(self.vertex_index(t, left) + 2) % 3];
+                if far == b {
+                    return Some((b, crossed));
+                }
+                let ori = self.orient(a, b, far);
+                if ori == 0.0 {
+                    return Some((far, crossed));
+                } else if ori > 0.0 {
+                    left = far;
+                } else {
+                    right = far;
+                }
+            }
+        }
+        None
+    }
+
+    /// Split the segment from `a` to `b` at the vertices on it, returns `None`
+    /// if it crosses or overlaps another segment.
+    fn segment_pieces(&self, a: usize, b: usize) -> Option<Vec<[usize; 2]>> {
+        let mut pieces = Vec::new();
+        let mut u = a;
+        while u != b {
+            let (w, crossed) = self.trace(u, b)?;
+            if crossed.is_empty() {
+                let (t, i) = self.find_edge(u, w)?;
+                if self.edge_segments[t][i] != INVALID_IND {
+                    return None;
+                }
+            }
+            pieces.push([u, w]);
+            u = w;
+        }
+        Some(pieces)
+    }
+
+    /// Insert the edge from `a` to `b` as a piece of the segment `seg` by
+    /// flipping the edges it crosses, as Sloan does, then restore the
+    /// constrained Delaunay property.
+    fn insert_piece(&mut self, a: usize, b: usize, seg: usize) {
+        let (_, crossed) = self.trace(a, b).unwrap();
+        let separates = |tri: &Self, p: usize, q: usize, u: usize, v: usize| {
+            let (ou, ov) = (tri.orient(p, q, u), tri.orient(p, q, v));
+            (ou > 0.0 && ov < 0.0) || (ou < 0.0 && ov > 0.0)
+        };
+        let mut queue = VecDeque::from(crossed);
+        let mut new_edges = Vec::new();
+        while let Some([u, v]) = queue.pop_front() {
+            let (t, i) = self.find_edge(u, v).unwrap();
+            let p = self.triangles[t][i];
+            let nei = self.neighbors[t][i];
+            let q = self.triangles[nei][(self.vertex_index(nei, u) + 1) % 3];
+            if !separates(self, p, q, u, v) {
+                // the quadrilateral is not convex
+                queue.push_back([u, v]);
+                continue;
+            }
+            self.flip(t, i);
+            if separates(self, a, b, p, q) && separates(self, p, q, a, b) {
+                queue.push_back([p, q]);
+            } else {
+                new_edges.push([p, q]);
+            }
+        }
+        self.set_segment(a, b, seg);
+        let mut stack = Vec::from_iter(new_edges.into_iter().filter_map(|[p, q]| {
+            let (t, i) = self.find_edge(p, q)?;
+            Some((t, self.edge(t, i)))
+        }));
+        self.legalize(&mut stack);
+    }
+}
+
+#[test]
+fn test_triangulation_2d() {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+    let check = |tri: &Triangulation2D| {
+        for t in 0..tri.triangles.len() {
+            if !tri.is_alive(t) {
+                continue;
+            }
+            for i in 0..3 {
+                let nei = tri.neighbors[t][i];
+                let [a, b] = tri.edge(t, i);
+                let j = (0..3).find(|&j| tri.edge(nei, j) == [b, a]).unwrap();
+                assert_eq!(tri.neighbors[nei][j], t);
+                assert_eq!(tri.edge_segments[nei][j], tri.edge_segments[t][i]);
+                assert!(!tri.is_illegal(t, i));
+            }
+            if !tri.is_ghost(t) {
+                let [a, b, c] = tri.triangles[t];
+                assert!(tri.orient(a, b, c) > 0.0);
+            }
+            for i in 0..3 {
+                // the edges of a segment lie on it
+                if let Some(seg) = tri.segment(t, i) {
+                    let [a, b] = tri.segments[seg].unwrap();
+                    for v in tri.edge(t, i) {
+                        assert_eq!(tri.orient(a, b, v), 0.0);
+                    }
+                }
+            }
+        }
+        for v in tri.vertices() {
+            assert!(tri.triangles[tri.vertex_triangles[v]].contains(&v));
+        }
+    };
+
+    let mut rng = SmallRng::seed_from_u64(7);
+    let mut tri = Triangulation2D::new();
+    // collinear points first, then points on a grid to get degeneracies
+    for i in 0..4 {
+        tri.insert_point([i as f64, 0.0]);
+    }
+    assert_eq!(tri.n_triangles(), 0);
+    assert_eq!(tri.insert_segment(0, 3), Some(0));
+    for _ in 0..200 {
+        tri.insert_point([rng.gen_range(0..10) as f64, rng.gen_range(-5..5) as f64]);
+    }
+    check(&tri);
+    assert_eq!(tri.locate(&[2.0, 0.0]), Location::Vertex(2));
+    assert_eq!(tri.locate(&[20.0, 0.0]), Location::Outside);
+    assert!(matches!(tri.locate(&[0.5, 0.0]), Location::Edge(..)));
+    // the first segment is split at the collinear points
+    let constrained = |tri: &Triangulation2D, a: usize, b: usize| {
+        let (t, i) = tri.find_edge(a, b).unwrap();
+        tri.segment(t, i)
+    };
+    assert_eq!(constrained(&tri, 1, 2), Some(0));
+
+    let n_points = tri.vertices().count();
+    let mut n_segments = 1;
+    for _ in 0..100 {
+        let a = rng.gen_range(0..n_points);
+        let b = rng.gen_range(0..n_points);
+        n_segments += tri.insert_segment(a, b).is_some() as usize;
+    }
+    check(&tri);
+    assert!(n_segments > 5);
+
+    // the segment through the removed vertex is joined
+    assert!(tri.remove_point(1));
+    check(&tri);
+    assert_eq!(constrained(&tri, 0, 2), Some(0));
+    for _ in 0..150 {
+        let v = rng.gen_range(0..n_points);
+        tri.remove_point(v);
+        check(&tri);
+        let p = [rng.gen_range(-20..20) as f64, rng.gen_range(-20..20) as f64];
+        tri.insert_point(p);
+        check(&tri);
+    }
+    for v in 0..tri.removed.len() {
+        tri.remove_point(v);
+    }
+    assert_eq!(tri.n_triangles(), 0);
+
+    // the link of the center of a grid has collinear runs, and the segment
+    // through the center is joined across the hole
+    let mut tri = Triangulation2D::new();
+    for i in 0..5 {
+        for j in 0..5 {
+            tri.insert_point([i as f64, j as f64]);
+        }
+    }
+    assert_eq!(tri.insert_segment(2, 22), Some(0));
+    assert!(tri.remove_point(12));
+    check(&tri);
+    assert_eq!(tri.n_triangles(), 30);
+    assert_eq!(constrained(&tri, 7, 17), Some(0));
+    for v in [11, 13, 6, 8, 16, 18] {
+        assert!(tri.remove_point(v));
+        check(&tri);
+    }
+}
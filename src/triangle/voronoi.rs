@@ -0,0 +1,537 @@
+use std::alloc::Allocator;
+
+use crate::{
+    disjoint_set::DisjointSet,
+    predicates::{self, double_to_sign, sign_reverse, GenericNum, Orientation},
+};
+
+use super::{triangulate, triangulate_mesh, TriMesh};
+
+#[inline(always)]
+fn point(points: &[f64], idx: usize) -> [f64; 2] {
+    [points[idx << 1], points[(idx << 1) + 1]]
+}
+
+/// A bounded Voronoi diagram. The cell of each site is a list of convex
+/// polygons with their vertices in counterclockwise order.
+pub struct VoronoiDiagram<A: Allocator + Copy> {
+    /// coordinates of the vertices of the polygons
+    vertices: Vec<f64, A>,
+    /// the first vertex of each polygon, followed by the number of vertices
+    polygons: Vec<usize, A>,
+    /// the first polygon of each cell, followed by the number of polygons
+    cells: Vec<usize, A>,
+}
+
+impl<A: Allocator + Copy> VoronoiDiagram<A> {
+    fn new(bump: A) -> Self {
+        let mut polygons = Vec::new_in(bump);
+        polygons.push(0);
+        let mut cells = Vec::new_in(bump);
+        cells.push(0);
+        Self {
+            vertices: Vec::new_in(bump),
+            polygons,
+            cells,
+        }
+    }
+
+    #[inline(always)]
+    pub fn n_cells(&self) -> usize {
+        self.cells.len() - 1
+    }
+
+    /// The polygons of the cell of `site`, as flat coordinates.
+    pub fn cell(&self, site: usize) -> impl Iterator<Item = &[f64]> + '_ {
+        (self.cells[site]..self.cells[site + 1])
+            .map(|k| &self.vertices[(self.polygons[k] << 1)..(self.polygons[k + 1] << 1)])
+    }
+
+    /// The area of the cell of `site`.
+    pub fn area(&self, site: usize) -> f64 {
+        self.cell(site)
+            .map(|polygon| {
+                let n = polygon.len() >> 1;
+                (0..n)
+                    .map(|i| {
+                        let [x0, y0] = point(polygon, i);
+                        let [x1, y1] = point(polygon, (i + 1) % n);
+                        x0 * y1 - x1 * y0
+                    })
+                    .sum::<f64>()
+                    * 0.5
+            })
+            .sum()
+    }
+
+    fn push_polygon(&mut self, polygon: &[[f64; 2]]) {
+        self.vertices.extend(polygon.iter().flatten());
+        self.polygons.push(self.vertices.len() >> 1);
+    }
+
+    fn close_cell(&mut self) {
+        self.cells.push(self.polygons.len() - 1);
+    }
+}
+
+/// The circumcenter of the triangle, rounded. The denominator is computed by
+/// the adaptive `orient2d`, so that it has the sign of the exact one, but the
+/// center is not exact and may be far off for a nearly flat triangle.
+fn circumcenter<A: Allocator + Copy>(points: &[f64], tri: [usize; 3], bump: A) -> [f64; 2] {
+    let [pa, pb, pc] = tri.map(|v| point(points, v));
+    let (bx, by) = (pb[0] - pa[0], pb[1] - pa[1]);
+    let (cx, cy) = (pc[0] - pa[0], pc[1] - pa[1]);
+    let b2 = bx * bx + by * by;
+    let c2 = cx * cx + cy * cy;
+    let d = 2.0 * predicates::orient2d(&pa, &pb, &pc, bump);
+    [
+        pa[0] + (cy * b2 - by * c2) / d,
+        pa[1] + (bx * c2 - cx * b2) / d,
+    ]
+}
+
+#[inline(always)]
+fn diff<T: GenericNum>(p: &[T; 2], q: &[T; 2]) -> [T; 2] {
+    [&q[0] - &p[0], &q[1] - &p[1]]
+}
+
+#[inline(always)]
+fn dot<T: GenericNum>(u: &[T; 2], v: &[T; 2]) -> T {
+    &u[0] * &v[0] + &u[1] * &v[1]
+}
+
+crate::predicate! {
+    /// The sign of the squared distance from `p` to `s` minus the one to `u`,
+    /// positive on the side of `u` of their bisector.
+    fn bisector_side(px, py, sx, sy, ux, uy) {
+        let p = [px, py];
+        let (ps, pu) = (diff(&p, &[sx, sy]), diff(&p, &[ux, uy]));
+        dot(&ps, &ps) - dot(&pu, &pu)
+    }
+}
+
+crate::predicate! {
+    /// The sign of the squared distance from `x` to `s` minus the one to `u`,
+    /// where `x` is the crossing of the line through `p` and `q` with the
+    /// bisector of `s` and `t`, scaled by the dot product of `q - p` and
+    /// `t - s`.
+    fn crossing_side(px, py, qx, qy, sx, sy, tx, ty, ux, uy) {
+        let [p, q, s, t, u] = [[px, py], [qx, qy], [sx, sy], [tx, ty], [ux, uy]];
+        let d = diff(&p, &q);
+        let [ps, pt, pu] = [&s, &t, &u].map(|x| diff(&p, x));
+        let (ss, tt, uu) = (dot(&ps, &ps), dot(&pt, &pt), dot(&pu, &pu));
+        dot(&d, &diff(&s, &t)) * (&ss - &uu) + (tt - &ss) * dot(&d, &diff(&s, &u))
+    }
+}
+
+crate::predicate! {
+    /// The sign of the dot product of `q - p` and `t - s`.
+    fn crossing_direction(px, py, qx, qy, sx, sy, tx, ty) {
+        dot(&diff(&[px, py], &[qx, qy]), &diff(&[sx, sy], &[tx, ty]))
+    }
+}
+
+/// A vertex of a cell clipped to a convex polygon. It is kept symbolic, so
+/// that its side of a bisector is decided exactly from the sites, and it is
+/// rounded once the cell is complete.
+#[derive(Clone, Copy)]
+enum CellVertex {
+    /// a corner of the clipping polygon
+    Corner([f64; 2]),
+    /// the crossing of the line through two corners with the bisector of the
+    /// site and another one
+    Crossing([f64; 2], [f64; 2], usize, usize),
+    /// the center of the circle through the site and two other ones
+    Center(usize, usize, usize),
+}
+
+/// The line through an edge of a clipped cell.
+#[derive(Clone, Copy)]
+enum Support {
+    /// the line through two corners of the clipping polygon
+    Line([f64; 2], [f64; 2]),
+    /// the bisector of the site and another one
+    Bisector(usize),
+}
+
+/// The side of `vertex` of the cell of `site` with respect to the bisector of
+/// `site` and `other`, positive on the side of `other`.
+fn side<A: Allocator + Copy>(
+    points: &[f64],
+    vertex: CellVertex,
+    site: usize,
+    other: usize,
+    bump: A,
+) -> Orientation {
+    let [ps, pu] = [site, other].map(|v| point(points, v));
+    match vertex {
+        CellVertex::Corner(p) => bisector_side(p[0], p[1], ps[0], ps[1], pu[0], pu[1], bump),
+        CellVertex::Crossing(p, q, _, t) => {
+            let pt = point(points, t);
+            let side = crossing_side(
+                p[0], p[1], q[0], q[1], ps[0], ps[1], pt[0], pt[1], pu[0], pu[1], bump,
+            );
+            match crossing_direction(p[0], p[1], q[0], q[1], ps[0], ps[1], pt[0], pt[1], bump) {
+                Orientation::Negative => sign_reverse(side),
+                _ => side,
+            }
+        }
+        CellVertex::Center(_, t, w) => {
+            // the center is on the side of `other` iff `other` is inside the
+            // circle
+            let [pt, pw] = [t, w].map(|v| point(points, v));
+            let inside = double_to_sign(predicates::incircle(&ps, &pt, &pw, &pu, bump));
+            if predicates::orient2d(&ps, &pt, &pw, bump) < 0.0 {
+                sign_reverse(inside)
+            } else {
+                inside
+            }
+        }
+    }
+}
+
+/// Keep the part of `cell`, a convex polygon in the cell of `site`, on the
+/// side of `site` of its bisector with `other`. The support of each vertex is
+/// the line through the edge starting from it.
+fn clip<A: Allocator + Copy>(
+    points: &[f64],
+    cell: &[(CellVertex, Support)],
+    site: usize,
+    other: usize,
+    result: &mut Vec<(CellVertex, Support), A>,
+    bump: A,
+) {
+    result.clear();
+    let Some(&(first, _)) = cell.first() else {
+        return;
+    };
+    let first = side(points, first, site, other, bump);
+    let mut side_v = first;
+    for (i, &(v, support)) in cell.iter().enumerate() {
+        let side_w = match cell.get(i + 1) {
+            Some(&(w, _)) => side(points, w, site, other, bump),
+            None => first,
+        };
+        // the crossing is only computed for a strict change of side, where
+        // the support and the bisector are not parallel
+        let crossing = || match support {
+            Support::Line(p, q) => CellVertex::Crossing(p, q, site, other),
+            Support::Bisector(t) => CellVertex::Center(site, t, other),
+        };
+        match (side_v, side_w) {
+            (Orientation::Positive, Orientation::Negative) => result.push((crossing(), support)),
+            (Orientation::Negative, Orientation::Positive) => {
+                result.push((v, support));
+                result.push((crossing(), Support::Bisector(other)));
+            }
+            (Orientation::Positive, _) => {}
+            (_, Orientation::Positive) => result.push((v, Support::Bisector(other))),
+            _ => result.push((v, support)),
+        }
+        side_v = side_w;
+    }
+}
+
+/// The targets of `pairs` grouped by source, with the offsets of the groups.
+fn adjacency<A: Allocator + Copy>(
+    n: usize,
+    mut pairs: Vec<[usize; 2], A>,
+    bump: A,
+) -> (Vec<usize, A>, Vec<usize, A>) {
+    pairs.sort_unstable();
+    pairs.dedup();
+    let mut offsets = std::vec::from_elem_in(0, n + 1, bump);
+    for [a, _] in &pairs {
+        offsets[a + 1] += 1;
+    }
+    for i in 0..n {
+        offsets[i + 1] += offsets[i];
+    }
+    let mut targets = Vec::with_capacity_in(pairs.len(), bump);
+    targets.extend(pairs.iter().map(|&[_, b]| b));
+    (targets, offsets)
+}
+
+/// Clips convex polygons to the Voronoi cells of the sites, with the Delaunay
+/// triangulation of the sites.
+struct Cells<'a, A: Allocator + Copy> {
+    points: &'a [f64],
+    mesh: TriMesh<A>,
+    /// the first of the duplicates of each site, which gets the cell
+    owners: Vec<usize, A>,
+    /// the triangulated duplicate of each first duplicate
+    vertices: Vec<usize, A>,
+    /// the sites of the adjacent cells of each first duplicate
+    neighbors: Vec<usize, A>,
+    neighbor_offsets: Vec<usize, A>,
+    /// the triangles around each vertex of the mesh
+    incident: Vec<usize, A>,
+    incident_offsets: Vec<usize, A>,
+    /// the circumcenter of each triangle, the same for the triangles whose
+    /// vertices are cocircular
+    centers: Vec<[f64; 2], A>,
+    cell: Vec<(CellVertex, Support), A>,
+    result: Vec<(CellVertex, Support), A>,
+    bump: A,
+}
+
+impl<'a, A: Allocator + Copy> Cells<'a, A> {
+    fn new(points: &'a [f64], bump: A) -> Self {
+        let n_points = points.len() >> 1;
+        let mesh = triangulate_mesh(points, &[], bump);
+        let mut in_mesh = std::vec::from_elem_in(false, n_points, bump);
+        for &v in mesh.triangles() {
+            in_mesh[v] = true;
+        }
+
+        // only one of the duplicates of a site is triangulated, the cell goes
+        // to the first of them
+        let mut sorted = Vec::with_capacity_in(n_points, bump);
+        sorted.extend(0..n_points);
+        sorted.sort_by(|&i, &j| point(points, i).partial_cmp(&point(points, j)).unwrap());
+        let mut owners = std::vec::from_elem_in(0, n_points, bump);
+        let mut vertices = std::vec::from_elem_in(0, n_points, bump);
+        let mut edges = Vec::with_capacity_in(mesh.triangles().len() * 2, bump);
+        let mut last = None;
+        for group in sorted.chunk_by(|&i, &j| point(points, i) == point(points, j)) {
+            for &v in group {
+                owners[v] = group[0];
+            }
+            vertices[group[0]] = group
+                .iter()
+                .copied()
+                .find(|&v| in_mesh[v])
+                .unwrap_or(group[0]);
+            // collinear sites are adjacent to the next ones in sorted order
+            if let Some(last) = last.replace(group[0]).filter(|_| mesh.n_triangles() == 0) {
+                edges.extend([[last, group[0]], [group[0], last]]);
+            }
+        }
+        for tri in mesh.triangles().chunks(3) {
+            for i in 0..3 {
+                let (a, b) = (owners[tri[i]], owners[tri[(i + 1) % 3]]);
+                edges.extend([[a, b], [b, a]]);
+            }
+        }
+        let (neighbors, neighbor_offsets) = adjacency(n_points, edges, bump);
+        let mut pairs = Vec::with_capacity_in(mesh.triangles().len(), bump);
+        pairs.extend((0..mesh.n_triangles()).flat_map(|t| mesh.triangle(t).map(|v| [v, t])));
+        let (incident, incident_offsets) = adjacency(n_points, pairs, bump);
+
+        // the triangles sharing an edge whose opposite vertices are
+        // cocircular share their circumcenter
+        let mut groups = DisjointSet::new(mesh.n_triangles());
+        for t in 0..mesh.n_triangles() {
+            let tri = mesh.triangle(t);
+            let [pa, pb, pc] = tri.map(|v| point(points, v));
+            for k in 0..3 {
+                let Some(nei) = mesh.neighbor(t, k).filter(|&nei| nei > t) else {
+                    continue;
+                };
+                let opposite = mesh
+                    .triangle(nei)
+                    .into_iter()
+                    .find(|v| !tri.contains(v))
+                    .unwrap();
+                if predicates::incircle(&pa, &pb, &pc, &point(points, opposite), bump) == 0.0 {
+                    groups.merge(t, nei);
+                }
+            }
+        }
+        let mut centers = std::vec::from_elem_in([0.0; 2], mesh.n_triangles(), bump);
+        for group in groups.output().into_values() {
+            let center = circumcenter(points, mesh.triangle(group[0]), bump);
+            for t in group {
+                centers[t] = center;
+            }
+        }
+
+        Self {
+            points,
+            mesh,
+            owners,
+            vertices,
+            neighbors,
+            neighbor_offsets,
+            incident,
+            incident_offsets,
+            centers,
+            cell: Vec::new_in(bump),
+            result: Vec::new_in(bump),
+            bump,
+        }
+    }
+
+    /// The sites of the cells adjacent to the one of `site`.
+    #[inline(always)]
+    fn neighbors(&self, site: usize) -> &[usize] {
+        &self.neighbors[self.neighbor_offsets[site]..self.neighbor_offsets[site + 1]]
+    }
+
+    /// The circumcenter of the triangle `[s, t, u]` of the cell of `s`, where
+    /// the cells of `s` and `t` are adjacent. It is the one of a Delaunay
+    /// triangle around the edge from `s` to `t`.
+    fn center(&self, [s, t, u]: [usize; 3]) -> [f64; 2] {
+        let (vs, vt) = (self.vertices[s], self.vertices[t]);
+        let pu = point(self.points, u);
+        self.incident[self.incident_offsets[vs]..self.incident_offsets[vs + 1]]
+            .iter()
+            .find(|&&tid| {
+                let tri = self.mesh.triangle(tid);
+                let [pa, pb, pc] = tri.map(|v| point(self.points, v));
+                tri.contains(&vt) && predicates::incircle(&pa, &pb, &pc, &pu, self.bump) == 0.0
+            })
+            .map_or_else(
+                || circumcenter(self.points, [s, t, u], self.bump),
+                |&tid| self.centers[tid],
+            )
+    }
+
+    /// The rounded coordinates of `vertex`, the same in all the cells
+    /// sharing it.
+    fn coordinates(&self, vertex: CellVertex) -> [f64; 2] {
+        match vertex {
+            CellVertex::Corner(p) => p,
+            CellVertex::Crossing(p, q, s, t) => {
+                let (p, q) = if p <= q { (p, q) } else { (q, p) };
+                let [ps, pt] = [s.min(t), s.max(t)].map(|v| point(self.points, v));
+                let d = [q[0] - p[0], q[1] - p[1]];
+                let squared_dist = |x: [f64; 2]| (x[0] - p[0]).powi(2) + (x[1] - p[1]).powi(2);
+                let t = (squared_dist(pt) - squared_dist(ps))
+                    / (2.0 * (d[0] * (pt[0] - ps[0]) + d[1] * (pt[1] - ps[1])));
+                [p[0] + d[0] * t, p[1] + d[1] * t]
+            }
+            CellVertex::Center(s, t, u) => self.center([s, t, u]),
+        }
+    }
+
+    /// Clip the convex polygon `corners`, in counterclockwise order, to the
+    /// cell of `site`, a first duplicate, and round it into `polygon`.
+    /// Returns false if the cell misses the polygon.
+    fn clip(&mut self, site: usize, corners: &[[f64; 2]], polygon: &mut Vec<[f64; 2], A>) -> bool {
+        self.cell.clear();
+        self.cell.extend(corners.iter().enumerate().map(|(i, &p)| {
+            let q = corners[(i + 1) % corners.len()];
+            (CellVertex::Corner(p), Support::Line(p, q))
+        }));
+        for k in self.neighbor_offsets[site]..self.neighbor_offsets[site + 1] {
+            let other = self.neighbors[k];
+            clip(
+                self.points,
+                &self.cell,
+                site,
+                other,
+                &mut self.result,
+                self.bump,
+            );
+            std::mem::swap(&mut self.cell, &mut self.result);
+        }
+
+        polygon.clear();
+        for &(vertex, _) in &self.cell {
+            let p = self.coordinates(vertex);
+            if polygon.last() != Some(&p) {
+                polygon.push(p);
+            }
+        }
+        while polygon.len() > 1 && polygon.first() == polygon.last() {
+            polygon.pop();
+        }
+        !self.cell.is_empty()
+    }
+}
+
+/// The Voronoi diagram of `points` clipped to the box `[min_x, min_y, max_x,
+/// max_y]`. Each cell is a single convex polygon, whose vertices inside the
+/// box are the circumcenters of the Delaunay triangles around the site. Only
+/// the first of the duplicates of a site has a non-empty cell.
+///
+/// The cells are the box clipped by the bisectors of the site and its
+/// Delaunay neighbors, where the side of each vertex is decided by exact
+/// predicates on the sites and the box corners. The vertices are then
+/// rounded in the same way in all the cells sharing them, in particular the
+/// Delaunay triangles with cocircular vertices share one circumcenter, so the
+/// cells tile the box up to the rounding of the vertices.
+pub fn voronoi<A: Allocator + Copy>(points: &[f64], bbox: &[f64; 4], bump: A) -> VoronoiDiagram<A> {
+    let mut cells = Cells::new(points, bump);
+    let corners = [
+        [bbox[0], bbox[1]],
+        [bbox[2], bbox[1]],
+        [bbox[2], bbox[3]],
+        [bbox[0], bbox[3]],
+    ];
+    let mut diagram = VoronoiDiagram::new(bump);
+    let mut polygon = Vec::new_in(bump);
+    for site in 0..points.len() >> 1 {
+        if cells.owners[site] == site {
+            cells.clip(site, &corners, &mut polygon);
+            if polygon.len() > 2 {
+                diagram.push_polygon(&polygon);
+            }
+        }
+        diagram.close_cell();
+    }
+    diagram
+}
+
+/// The Voronoi diagram of `points` clipped to the domain of the constrained
+/// triangulation of `points` and `segments`, as given by [`triangulate`].
+/// The cell of each site is made of its intersections with the triangles of
+/// the domain, computed as in [`voronoi`].
+pub fn voronoi_in_domain<A: Allocator + Copy>(
+    points: &[f64],
+    segments: &[usize],
+    bump: A,
+) -> VoronoiDiagram<A> {
+    let n_points = points.len() >> 1;
+    let mut cells = Cells::new(points, bump);
+
+    // the sites whose cells meet a triangle are connected through adjacent
+    // cells meeting it, starting from its vertices
+    let mut pieces = Vec::new_in(bump);
+    let mut stamps = std::vec::from_elem_in(usize::MAX, n_points, bump);
+    let mut queue = Vec::new_in(bump);
+    let mut piece = Vec::new_in(bump);
+    for (tid, tri) in triangulate(points, segments, bump).chunks(3).enumerate() {
+        queue.clear();
+        for &v in tri {
+            let site = cells.owners[v];
+            if stamps[site] != tid {
+                stamps[site] = tid;
+                queue.push(site);
+            }
+        }
+        let corners = [tri[0], tri[1], tri[2]].map(|v| point(points, v));
+        while let Some(site) = queue.pop() {
+            if !cells.clip(site, &corners, &mut piece) {
+                continue;
+            }
+            let area = (0..piece.len())
+                .map(|i| {
+                    let (pa, pb) = (piece[i], piece[(i + 1) % piece.len()]);
+                    pa[0] * pb[1] - pb[0] * pa[1]
+                })
+                .sum::<f64>();
+            if piece.len() > 2 && area > 0.0 {
+                pieces.push((site, piece.clone()));
+            }
+            for &other in cells.neighbors(site) {
+                if stamps[other] != tid {
+                    stamps[other] = tid;
+                    queue.push(other);
+                }
+            }
+        }
+    }
+
+    pieces.sort_by_key(|(site, _)| *site);
+    let mut diagram = VoronoiDiagram::new(bump);
+    let mut pieces = pieces.into_iter().peekable();
+    for site in 0..n_points {
+        while let Some((_, piece)) = pieces.next_if(|(s, _)| *s == site) {
+            diagram.push_polygon(&piece);
+        }
+        diagram.close_cell();
+    }
+    diagram
+}
@@ -1,4 +1,9 @@
-use gpf::polygonlization::{make_mesh_for_triangles, make_polyhedral_mesh};
+use std::collections::HashMap;
+
+use gpf::polygonlization::{
+    conforming_tetrahedralize, make_mesh_for_triangles, make_polyhedral_mesh,
+};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 use serde::Deserialize;
 
 #[allow(non_snake_case)]
@@ -129,3 +134,197 @@ fn test_pig_model() {
     let (new_points, new_triangles) = make_mesh_for_triangles(&points, &triangles, &tri_in_shells);
     write_obj(&new_points, &new_triangles, "126.obj");
 }
+
+#[test]
+fn test_conforming_tetrahedralize() {
+    // two crossing cubes in a box, the triangles of the cubes face outwards
+    let mut points = Vec::new();
+    let mut triangles = Vec::new();
+    for (origin, size) in [([0.0, 0.0, 0.0], 2.0), ([1.0, 0.5, 0.25], 2.0)] {
+        let start = points.len() / 3;
+        for i in 0..8 {
+            points.extend([
+                origin[0] + size * (i & 1) as f64,
+                origin[1] + size * ((i >> 1) & 1) as f64,
+                origin[2] + size * ((i >> 2) & 1) as f64,
+            ]);
+        }
+        for tri in [
+            [0, 2, 3],
+            [0, 3, 1],
+            [4, 5, 7],
+            [4, 7, 6],
+            [0, 1, 5],
+            [0, 5, 4],
+            [2, 6, 7],
+            [2, 7, 3],
+            [0, 4, 6],
+            [0, 6, 2],
+            [1, 3, 7],
+            [1, 7, 5],
+        ] {
+            triangles.extend(tri.map(|i| start + i));
+        }
+    }
+    for i in 0..8 {
+        points.extend([0, 1, 2].map(|axis| if (i >> axis) & 1 == 0 { -1.0 } else { 4.0 }));
+    }
+
+    let (new_points, tets, inside) = conforming_tetrahedralize(&points, &triangles);
+    assert_eq!(new_points[..points.len()], points[..]);
+    let p = |i: usize| {
+        [
+            new_points[i * 3],
+            new_points[i * 3 + 1],
+            new_points[i * 3 + 2],
+        ]
+    };
+    let mut volume = 0.0;
+    let mut inside_volume = 0.0;
+    let mut faces = HashMap::new();
+    for (tet, &is_inside) in tets.chunks(4).zip(&inside) {
+        let [pa, pb, pc, pd] = [p(tet[0]), p(tet[1]), p(tet[2]), p(tet[3])];
+        let [u, v, w] = [pb, pc, pd].map(|q| [q[0] - pa[0], q[1] - pa[1], q[2] - pa[2]]);
+        let tet_volume = (u[0] * (v[1] * w[2] - v[2] * w[1]) - u[1] * (v[0] * w[2] - v[2] * w[0])
+            + u[2] * (v[0] * w[1] - v[1] * w[0]))
+            / 6.0;
+        assert!(tet_volume > 0.0);
+        volume += tet_volume;
+        if is_inside {
+            inside_volume += tet_volume;
+        }
+        for i in 0..4 {
+            let mut face = [tet[(i + 1) % 4], tet[(i + 2) % 4], tet[(i + 3) % 4]];
+            face.sort_unstable();
+            *faces.entry(face).or_insert(0) += 1;
+        }
+    }
+    assert!((volume - 125.0).abs() < 1e-9);
+    assert!((inside_volume - (16.0 - 1.0 * 1.5 * 1.75)).abs() < 1e-9);
+    // the faces are shared by two tetrahedra, except on the box
+    for (face, count) in faces {
+        let on_box = (0..3).any(|axis| {
+            [-1.0, 4.0]
+                .into_iter()
+                .any(|x| face.iter().all(|&vid| p(vid)[axis] == x))
+        });
+        assert_eq!(count, if on_box { 1 } else { 2 });
+    }
+}
+
+#[test]
+fn test_conforming_tetrahedralize_implicit() {
+    // two tilted tetrahedra crossing each other in a box, the intersections
+    // are implicit points
+    let mut points = Vec::new();
+    let mut triangles = Vec::new();
+    for corners in [
+        [
+            [0.1, 0.2, 0.3],
+            [2.9, 0.7, 0.4],
+            [1.3, 2.8, 0.6],
+            [1.1, 1.2, 2.7],
+        ],
+        [
+            [0.4, 1.9, 2.2],
+            [2.6, 2.3, 1.9],
+            [1.7, 0.3, 1.1],
+            [0.9, 0.8, 0.1],
+        ],
+    ] {
+        let start = points.len() / 3;
+        points.extend(corners.iter().flatten());
+        for tri in [[0, 2, 1], [0, 1, 3], [1, 2, 3], [2, 0, 3]] {
+            triangles.extend(tri.map(|i| start + i));
+        }
+    }
+    for i in 0..8 {
+        points.extend([0, 1, 2].map(|axis| if (i >> axis) & 1 == 0 { -1.0 } else { 4.0 }));
+    }
+
+    let (new_points, tets, inside) = conforming_tetrahedralize(&points, &triangles);
+    assert!(new_points.len() > points.len());
+    let p = |i: usize| {
+        [
+            new_points[i * 3],
+            new_points[i * 3 + 1],
+            new_points[i * 3 + 2],
+        ]
+    };
+    let mut volume = 0.0;
+    let mut faces = HashMap::new();
+    for tet in tets.chunks(4) {
+        let [pa, pb, pc, pd] = [p(tet[0]), p(tet[1]), p(tet[2]), p(tet[3])];
+        let [u, v, w] = [pb, pc, pd].map(|q| [q[0] - pa[0], q[1] - pa[1], q[2] - pa[2]]);
+        volume += (u[0] * (v[1] * w[2] - v[2] * w[1]) - u[1] * (v[0] * w[2] - v[2] * w[0])
+            + u[2] * (v[0] * w[1] - v[1] * w[0]))
+            / 6.0;
+        for i in 0..4 {
+            let mut face = [tet[(i + 1) % 4], tet[(i + 2) % 4], tet[(i + 3) % 4]];
+            face.sort_unstable();
+            *faces.entry(face).or_insert(0) += 1;
+        }
+    }
+    assert!((volume - 125.0f64).abs() < 1e-9);
+    assert!(inside.contains(&true));
+    // no tetrahedron is dropped, the box is filled and the faces are shared
+    // by two tetrahedra, except on the box
+    for (face, count) in faces {
+        let on_box = (0..3).any(|axis| {
+            [-1.0, 4.0]
+                .into_iter()
+                .any(|x| face.iter().all(|&vid| p(vid)[axis] == x))
+        });
+        assert_eq!(count, if on_box { 1 } else { 2 });
+    }
+}
+
+#[test]
+fn test_conforming_tetrahedralize_degenerate() {
+    // constraints between the points of a lattice and of the half lattice,
+    // which pass through vertices and along edges and faces of the Delaunay
+    // tetrahedra, the degenerate cases of tracing their sides, which never
+    // fail to find the next tetrahedron
+    for seed in 0..30 {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let n = rng.gen_range(3..5);
+        let mut points = Vec::from_iter(
+            (0..(n * n * n))
+                .flat_map(|i| [(i % n) as f64, ((i / n) % n) as f64, (i / n / n) as f64]),
+        );
+        for _ in 0..rng.gen_range(0..10) {
+            let p = [0, 1, 2].map(|_| rng.gen_range(0..(2 * n - 1)) as f64 * 0.5);
+            if !points.chunks(3).any(|q| q == p) {
+                points.extend(p);
+            }
+        }
+        let n_points = points.len() / 3;
+        let p = |points: &[f64], i: usize| [points[i * 3], points[i * 3 + 1], points[i * 3 + 2]];
+        let mut triangles = Vec::new();
+        while triangles.len() < 30 {
+            let tri = [0, 1, 2].map(|_| rng.gen_range(0..n_points));
+            let [pa, pb, pc] = tri.map(|i| p(&points, i));
+            let [u, v] = [pb, pc].map(|q| [q[0] - pa[0], q[1] - pa[1], q[2] - pa[2]]);
+            let normal = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            if normal != [0.0; 3] {
+                triangles.extend(tri);
+            }
+        }
+
+        let (new_points, tets, _) = conforming_tetrahedralize(&points, &triangles);
+        let mut volume = 0.0;
+        for tet in tets.chunks(4) {
+            let [pa, pb, pc, pd] = [0, 1, 2, 3].map(|i| p(&new_points, tet[i]));
+            let [u, v, w] = [pb, pc, pd].map(|q| [q[0] - pa[0], q[1] - pa[1], q[2] - pa[2]]);
+            volume += (u[0] * (v[1] * w[2] - v[2] * w[1]) - u[1] * (v[0] * w[2] - v[2] * w[0])
+                + u[2] * (v[0] * w[1] - v[1] * w[0]))
+                / 6.0;
+        }
+        let side = (n - 1) as f64;
+        assert!((volume - side * side * side).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,213 @@
+#![feature(allocator_api)]
+
+use bumpalo::Bump;
+use gpf::predicates::{
+    self, double_to_sign, orient3d::orient3d, ExplicitPoint3D, ImplicitPointLPI, ImplicitPointTPI,
+    Orientation, Point3D,
+};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+gpf::predicate! {
+    fn orient2d_generated(ax, ay, bx, by, cx, cy) {
+        let acx = ax - &cx;
+        let bcy = by - &cy;
+        let acy = ay - cy;
+        let bcx = bx - cx;
+        acx * bcy - acy * bcx
+    }
+}
+
+gpf::predicate! {
+    fn orient3d_lee_generated(
+        bx, by, bz, cx, cy, cz, dx, dy, dz;
+        p: P => (lx, ly, lz, ld)
+    ) {
+        let pdx = lx - &ld * &dx;
+        let pdy = ly - &ld * &dy;
+        let pdz = lz - ld * &dz;
+        let bdx = bx - &dx;
+        let bdy = by - &dy;
+        let bdz = bz - &dz;
+        let cdx = cx - dx;
+        let cdy = cy - dy;
+        let cdz = cz - dz;
+        let m1 = &bdy * &cdz - &bdz * &cdy;
+        let m2 = &bdz * &cdx - &bdx * &cdz;
+        let m3 = bdx * cdy - bdy * cdx;
+        pdx * m1 + pdy * m2 + pdz * m3
+    }
+}
+
+#[test]
+fn test_generated_predicate() {
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(7);
+    for _ in 0..1000 {
+        let pa = [rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)];
+        let pb = [rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)];
+        // nearly collinear points exercise the interval and exact stages
+        let t = rng.gen_range(0.0..1.0);
+        let pc = [pa[0] + t * (pb[0] - pa[0]), pa[1] + t * (pb[1] - pa[1])];
+        for pc in [[rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)], pc] {
+            assert_eq!(
+                orient2d_generated(pa[0], pa[1], pb[0], pb[1], pc[0], pc[1], &bump),
+                double_to_sign(predicates::orient2d(&pa, &pb, &pc, &bump))
+            );
+        }
+    }
+    assert_eq!(
+        orient2d_generated(0.0, 0.0, 1.0, 1.0, 2.0, 2.0, &bump),
+        Orientation::Zero
+    );
+}
+
+#[test]
+fn test_generated_implicit_predicate() {
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(11);
+    let mut random_point = || ExplicitPoint3D {
+        data: [
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        ],
+    };
+    for _ in 0..200 {
+        let lpi = ImplicitPointLPI::new(
+            random_point(),
+            random_point(),
+            random_point(),
+            random_point(),
+            random_point(),
+        );
+        let (b, c, d) = (random_point(), random_point(), random_point());
+        let expected = orient3d(
+            &Point3D::LPI(lpi.clone()),
+            &Point3D::Explicit(b.clone()),
+            &Point3D::Explicit(c.clone()),
+            &Point3D::Explicit(d.clone()),
+            &bump,
+        );
+        // `orient3d` on `Point3D` is negative when the last point is above the plane
+        let result = orient3d_lee_generated(
+            c[0], c[1], c[2], b[0], b[1], b[2], d[0], d[1], d[2], &lpi, &bump,
+        );
+        assert_eq!(result, expected);
+    }
+    // implicit points on the plane z = 0, nearly coplanar with the others,
+    // decided by the later stages
+    for eps in [1e-6, 1e-12, 0.0] {
+        for _ in 0..100 {
+            let mut random_point = |z: f64| ExplicitPoint3D {
+                data: [rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), z],
+            };
+            let (p, q) = (random_point(-1.0), random_point(1.0));
+            let (r, s, t) = (random_point(0.0), random_point(0.0), random_point(0.0));
+            let lpi = ImplicitPointLPI::new(p, q, r, s, t);
+            let (b, c, d) = (random_point(eps), random_point(eps), random_point(eps));
+            let expected = orient3d(
+                &Point3D::LPI(lpi.clone()),
+                &Point3D::Explicit(b.clone()),
+                &Point3D::Explicit(c.clone()),
+                &Point3D::Explicit(d.clone()),
+                &bump,
+            );
+            let result = orient3d_lee_generated(
+                c[0], c[1], c[2], b[0], b[1], b[2], d[0], d[1], d[2], &lpi, &bump,
+            );
+            assert_eq!(result, expected);
+        }
+    }
+}
+
+gpf::predicate! {
+    fn not_homogeneous(a, b) {
+        &a * &a - b
+    }
+}
+
+#[test]
+#[should_panic(expected = "homogeneous")]
+fn test_generated_not_homogeneous() {
+    not_homogeneous(1.0, 2.0, &Bump::new());
+}
+
+#[test]
+fn test_implicit_point_sign() {
+    // the intersections of vertical lines with the plane z = 0 are exact, and
+    // nearly coplanar with points at z = eps, so that the interval filter
+    // decides, with the product of the denominators of two implicit points
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(13);
+    let point = |x: f64, y: f64, z: f64| ExplicitPoint3D { data: [x, y, z] };
+    for eps in [1e-6, 1e-10, 1e-12, 1e-14] {
+        for _ in 0..100 {
+            let mut random_xy = || [rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)];
+            let [a, b, l1, l2] = [random_xy(), random_xy(), random_xy(), random_xy()];
+            let [r, s, t] = [random_xy(), random_xy(), random_xy()].map(|p| point(p[0], p[1], 0.0));
+            let (a, b) = (point(a[0], a[1], eps), point(b[0], b[1], -eps));
+            let lpi = |p: [f64; 2], plane: [&ExplicitPoint3D; 3]| {
+                let [r, s, t] = plane.map(|p| p.clone());
+                ImplicitPointLPI::new(point(p[0], p[1], -1.0), point(p[0], p[1], 1.0), r, s, t)
+            };
+            let expected = orient3d(
+                &Point3D::Explicit(a.clone()),
+                &Point3D::Explicit(b.clone()),
+                &Point3D::Explicit(point(l1[0], l1[1], 0.0)),
+                &Point3D::Explicit(point(l2[0], l2[1], 0.0)),
+                &bump,
+            );
+            // both orientations of the plane
+            for plane in [[&r, &s, &t], [&s, &r, &t]] {
+                let result = orient3d(
+                    &Point3D::Explicit(a.clone()),
+                    &Point3D::Explicit(b.clone()),
+                    &Point3D::LPI(lpi(l1, plane)),
+                    &Point3D::LPI(lpi(l2, [&r, &s, &t])),
+                    &bump,
+                );
+                assert_eq!(result, expected);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_implicit_point_repeated() {
+    // the exact coordinates of intersection points span many exponents, the
+    // predicates on them must not underflow and miss a repeated point
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(7);
+    let mut random_point = || ExplicitPoint3D {
+        data: [
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+            rng.gen_range(-1.0..1.0),
+        ],
+    };
+    for _ in 0..100 {
+        let mut points = Vec::from_iter((0..19).map(|_| random_point()));
+        let mut next = || points.pop().unwrap();
+        let tpi = ImplicitPointTPI::new(
+            next(),
+            next(),
+            next(),
+            next(),
+            next(),
+            next(),
+            next(),
+            next(),
+            next(),
+        );
+        let l1 = ImplicitPointLPI::new(next(), next(), next(), next(), next());
+        let l2 = ImplicitPointLPI::new(next(), next(), next(), next(), next());
+        let [t, l1, l2] = [Point3D::TPI(tpi), Point3D::LPI(l1), Point3D::LPI(l2)];
+        for [a, b, c, d] in [
+            [&t, &l1, &l2, &l1],
+            [&l1, &l2, &t, &l1],
+            [&l1, &t, &l2, &l1],
+        ] {
+            assert_eq!(orient3d(a, b, c, d, &bump), Orientation::Zero);
+        }
+    }
+}
@@ -1,8 +1,18 @@
 #![feature(test)]
 
 use bumpalo::Bump;
-use gpf::triangle::{tetrahedralize, triangulate};
+use gpf::triangle::{
+    alpha_complex, convex_hull, polygon_boolean, reconstruct_surface, tetrahedralize,
+    tetrahedralize_refine, tetrahedralize_weighted, triangulate, triangulate_mesh,
+    triangulate_planar_polygon, triangulate_polygon_soup, triangulate_polygon_soup_parallel,
+    triangulate_polygon_soup_with_fill_rule, triangulate_refine, triangulate_regions,
+    triangulate_with_fill_rule, triangulate_with_intersections, voronoi, voronoi_in_domain,
+    AlphaClass, BooleanOp, FillRule, IncrementalHull, LineOfSight, PolygonError, PolygonLoops,
+    RegionSeed, TetLocation, TetMesh, TetMeshData, VoronoiDiagram,
+};
 use rand::{distributions::Uniform, rngs::SmallRng, Rng, SeedableRng};
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufRead, BufReader, Write};
 use std::time::Instant;
@@ -61,6 +71,509 @@ fn test_triangulate() {
     // write_obj(&points, &triangles, "test.obj");
 }
 
+#[test]
+fn test_triangulate_refine() {
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let mut points = vec![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+    // nearly collinear points make thin triangles
+    for _ in 0..20 {
+        points.push(rng.gen_range(0.1..0.9));
+        points.push(0.5 + rng.gen_range(-1e-3..1e-3));
+    }
+    let segments = [0, 1, 1, 2, 2, 3, 3, 0];
+    let bump = Bump::new();
+    let (new_points, triangles, _) =
+        triangulate_refine(&points, &segments, &[], &[], 20.0, 0.01, &bump);
+    assert!(!new_points.is_empty());
+    points.extend(new_points);
+
+    let mut total_area = 0.0;
+    for tri in triangles.chunks(3) {
+        let p = |i: usize| [points[tri[i] * 2], points[tri[i] * 2 + 1]];
+        let [pa, pb, pc] = [p(0), p(1), p(2)];
+        let area = ((pb[0] - pa[0]) * (pc[1] - pa[1]) - (pb[1] - pa[1]) * (pc[0] - pa[0])) * 0.5;
+        assert!(area > 0.0 && area <= 0.01);
+        total_area += area;
+        for (o, a, b) in [(pa, pb, pc), (pb, pc, pa), (pc, pa, pb)] {
+            let u = [a[0] - o[0], a[1] - o[1]];
+            let v = [b[0] - o[0], b[1] - o[1]];
+            let cos = (u[0] * v[0] + u[1] * v[1]) / (u[0].hypot(u[1]) * v[0].hypot(v[1]));
+            assert!(cos.acos().to_degrees() > 20.0 - 1e-6);
+        }
+    }
+    assert!((total_area - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_triangulate_regions() {
+    // an island inside a hole inside a square
+    let mut points = Vec::new();
+    for (lo, hi) in [(0.0, 4.0), (1.0, 3.0), (1.5, 2.5)] {
+        points.extend([lo, lo, hi, lo, hi, hi, lo, hi]);
+    }
+    let segments = Vec::from_iter(
+        (0..3).flat_map(|k| (0..4).flat_map(move |i| [k * 4 + i, k * 4 + (i + 1) % 4])),
+    );
+    let regions = [
+        RegionSeed {
+            point: [0.5, 0.5],
+            attribute: 1.0,
+            max_area: 0.1,
+        },
+        RegionSeed {
+            point: [2.0, 2.0],
+            attribute: 2.0,
+            max_area: 0.5,
+        },
+    ];
+    let area = |points: &[f64], tri: &[usize]| {
+        let p = |i: usize| [points[tri[i] * 2], points[tri[i] * 2 + 1]];
+        let [pa, pb, pc] = [p(0), p(1), p(2)];
+        ((pb[0] - pa[0]) * (pc[1] - pa[1]) - (pb[1] - pa[1]) * (pc[0] - pa[0])) * 0.5
+    };
+
+    let bump = Bump::new();
+    let (triangles, attributes) =
+        triangulate_regions(&points, &segments, &[1.2, 1.2], &regions, &bump);
+    let mut region_areas = [0.0; 3];
+    for (tri, &attribute) in triangles.chunks(3).zip(attributes.iter()) {
+        region_areas[attribute as usize] += area(&points, tri);
+    }
+    assert_eq!(region_areas, [0.0, 12.0, 1.0]);
+
+    let (new_points, triangles, attributes) = triangulate_refine(
+        &points,
+        &segments,
+        &[1.2, 1.2],
+        &regions,
+        0.0,
+        f64::INFINITY,
+        &bump,
+    );
+    points.extend(new_points);
+    for (tri, &attribute) in triangles.chunks(3).zip(attributes.iter()) {
+        assert!(area(&points, tri) <= regions[attribute as usize - 1].max_area);
+    }
+}
+
+#[test]
+fn test_triangulate_duplicates() {
+    // the vertex 4 duplicates the vertex 2, only the first of them is
+    // triangulated and the segments ending at 4 end at 2
+    let points = [0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 1.0];
+    let bump = Bump::new();
+    let mut triangles = triangulate(&points, &[0, 1, 1, 4, 4, 3, 3, 0], &bump);
+    assert_eq!(triangles.len(), 6);
+    triangles.sort_unstable();
+    triangles.dedup();
+    assert_eq!(triangles.to_vec(), [0, 1, 2, 3]);
+    // coincident points have no triangles
+    assert!(triangulate(&[1.0, 2.0, 1.0, 2.0, 1.0, 2.0], &[], &bump).is_empty());
+}
+
+#[test]
+fn test_triangulate_with_fill_rule() {
+    // two nested counterclockwise squares around a clockwise one, and a
+    // clockwise square aside, the winding numbers are 1, 2, 1 and -1
+    let mut points = Vec::new();
+    for (lo, hi) in [(0.0, 4.0), (1.0, 3.0), (1.5, 2.5)] {
+        points.extend([lo, lo, hi, lo, hi, hi, lo, hi]);
+    }
+    points.extend([5.0, 0.0, 6.0, 0.0, 6.0, 1.0, 5.0, 1.0]);
+    let segments = Vec::from_iter((0..4).flat_map(|k| {
+        (0..4).flat_map(move |i| {
+            let (a, b) = (k * 4 + i, k * 4 + (i + 1) % 4);
+            if k < 2 {
+                [a, b]
+            } else {
+                [b, a]
+            }
+        })
+    }));
+    let area = |tri: &[usize]| {
+        let p = |i: usize| [points[tri[i] * 2], points[tri[i] * 2 + 1]];
+        let [pa, pb, pc] = [p(0), p(1), p(2)];
+        ((pb[0] - pa[0]) * (pc[1] - pa[1]) - (pb[1] - pa[1]) * (pc[0] - pa[0])) * 0.5
+    };
+
+    let points_3d = Vec::from_iter(points.chunks(2).flat_map(|p| [p[0], p[1], 1.0]));
+    let edges = vec![segments.clone()];
+    let axes = [0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+
+    let bump = Bump::new();
+    for (fill_rule, expected) in [
+        (FillRule::EvenOdd, 14.0),
+        (FillRule::NonZero, 17.0),
+        (FillRule::Positive, 16.0),
+    ] {
+        let triangles = triangulate_with_fill_rule(&points, &segments, fill_rule, &bump);
+        assert_eq!(triangles.chunks(3).map(area).sum::<f64>(), expected);
+        // the same polygon in the plane z = 1 of a polygon soup
+        let (soup_triangles, _) =
+            triangulate_polygon_soup_with_fill_rule(&points_3d, &edges, &axes, fill_rule);
+        assert_eq!(soup_triangles, triangles.to_vec());
+    }
+    // the polygon soup keeps the inside of the segment marks by default
+    let (soup_triangles, _) = triangulate_polygon_soup(&points_3d, &edges, &axes);
+    assert_eq!(
+        soup_triangles,
+        triangulate(&points, &segments, &bump).to_vec()
+    );
+}
+
+#[test]
+fn test_triangulate_mesh() {
+    // a square with a square hole, and a point above it outside the domain
+    let mut points = Vec::new();
+    for (lo, hi) in [(0.0, 4.0), (1.0, 3.0)] {
+        points.extend([lo, lo, hi, lo, hi, hi, lo, hi]);
+    }
+    points.extend([2.0, 5.0]);
+    let segments = [0, 1, 1, 2, 2, 3, 3, 0, 4, 7, 7, 6, 6, 5, 5, 4];
+
+    let bump = Bump::new();
+    let mesh = triangulate_mesh(&points, &segments, &bump);
+    assert_eq!(
+        mesh.triangles(),
+        &triangulate(&points, &segments, &bump)[..]
+    );
+    assert_eq!(mesh.hull(), &[0, 1, 2, 8, 3]);
+
+    let mut n_constrained = 0;
+    for t in 0..mesh.n_triangles() {
+        let tri = mesh.triangle(t);
+        for i in 0..3 {
+            let (a, b) = (tri[(i + 1) % 3], tri[(i + 2) % 3]);
+            // the segments bound the domain
+            assert_eq!(mesh.neighbor(t, i).is_none(), mesh.is_constrained(t, i));
+            if let Some(seg) = mesh.segment(t, i) {
+                let ends = &segments[seg * 2..seg * 2 + 2];
+                assert!(ends == [a, b] || ends == [b, a]);
+                n_constrained += 1;
+            }
+            if let Some(nei) = mesh.neighbor(t, i) {
+                let k = (0..3).find(|&k| mesh.neighbor(nei, k) == Some(t)).unwrap();
+                let other = mesh.triangle(nei);
+                assert_eq!([other[(k + 1) % 3], other[(k + 2) % 3]], [b, a]);
+            }
+        }
+    }
+    assert_eq!(n_constrained, 8);
+}
+
+#[test]
+fn test_triangulate_with_intersections() {
+    // a square with its crossing diagonals, a segment overlapping its bottom
+    // side and a segment crossing the diagonals between its left and right sides
+    let points = [
+        0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 0.0, 4.0, 1.0, 0.0, 3.0, 0.0, 0.0, 1.0, 4.0, 2.0,
+    ];
+    let segments = [0, 1, 1, 2, 2, 3, 3, 0, 0, 2, 1, 3, 4, 5, 6, 7];
+    let bump = Bump::new();
+    let (new_points, triangles, sub_segments) =
+        triangulate_with_intersections(&points, &segments, &bump).unwrap();
+    assert_eq!(new_points.len(), 6);
+    assert_eq!(sub_segments.len(), 18 * 3);
+
+    let all_points = [&points[..], &new_points[..]].concat();
+    let p = |i: usize| [all_points[i * 2], all_points[i * 2 + 1]];
+    let mut lengths = [0.0; 8];
+    for seg in sub_segments.chunks(3) {
+        let [u, v, parent] = [seg[0], seg[1], seg[2]];
+        // the sub-segments lie on their input segment and cross no edge
+        let orient = |a: [f64; 2], b: [f64; 2], c: [f64; 2]| {
+            (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+        };
+        for tri in triangles.chunks(3) {
+            for i in 0..3 {
+                let [pc, pd] = [p(tri[i]), p(tri[(i + 1) % 3])];
+                let [pu, pv] = [p(u), p(v)];
+                assert!(
+                    orient(pu, pv, pc) * orient(pu, pv, pd) >= -1e-12
+                        || orient(pc, pd, pu) * orient(pc, pd, pv) >= -1e-12
+                );
+            }
+        }
+        let [pa, pb] = [p(segments[parent * 2]), p(segments[parent * 2 + 1])];
+        for q in [p(u), p(v)] {
+            let area = (pb[0] - pa[0]) * (q[1] - pa[1]) - (pb[1] - pa[1]) * (q[0] - pa[0]);
+            assert!(area.abs() < 1e-12);
+        }
+        let [pu, pv] = [p(u), p(v)];
+        lengths[parent] += (pv[0] - pu[0]).hypot(pv[1] - pu[1]);
+    }
+    for (parent, length) in lengths.into_iter().enumerate() {
+        let [pa, pb] = [p(segments[parent * 2]), p(segments[parent * 2 + 1])];
+        assert!((length - (pb[0] - pa[0]).hypot(pb[1] - pa[1])).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_polygon_boolean() {
+    let bump = Bump::new();
+    // the signed area and the number of loops of the result
+    let measure = |points: &[f64], a: &[usize], b: &[usize], op: BooleanOp| {
+        let PolygonLoops {
+            points: new_points,
+            loops,
+            offsets,
+        } = polygon_boolean(points, a, b, op, FillRule::Positive, &bump).unwrap();
+        let all_points = [points, &new_points[..]].concat();
+        let p = |i: usize| [all_points[i * 2], all_points[i * 2 + 1]];
+        let mut area = 0.0;
+        for range in offsets.windows(2) {
+            let polygon = &loops[range[0]..range[1]];
+            for (i, &u) in polygon.iter().enumerate() {
+                let [pu, pv] = [p(u), p(polygon[(i + 1) % polygon.len()])];
+                area += (pu[0] * pv[1] - pv[0] * pu[1]) * 0.5;
+            }
+        }
+        (area, offsets.len() - 1)
+    };
+
+    // two overlapping squares
+    let points = [
+        0.0, 0.0, 2.0, 0.0, 2.0, 2.0, 0.0, 2.0, 1.0, 1.0, 3.0, 1.0, 3.0, 3.0, 1.0, 3.0,
+    ];
+    let a = [0, 1, 1, 2, 2, 3, 3, 0];
+    let b = [4, 5, 5, 6, 6, 7, 7, 4];
+    for (op, expected, n_loops) in [
+        (BooleanOp::Union, 7.0, 1),
+        (BooleanOp::Intersection, 1.0, 1),
+        (BooleanOp::Difference, 3.0, 1),
+        (BooleanOp::Xor, 6.0, 2),
+    ] {
+        let (area, n) = measure(&points, &a, &b, op);
+        assert!((area - expected).abs() < 1e-12);
+        assert_eq!(n, n_loops);
+    }
+
+    // a square with a clockwise hole and a square overlapping both
+    let points = [
+        0.0, 0.0, 4.0, 0.0, 4.0, 4.0, 0.0, 4.0, 1.0, 1.0, 3.0, 1.0, 3.0, 3.0, 1.0, 3.0, 2.0, 2.0,
+        5.0, 2.0, 5.0, 5.0, 2.0, 5.0,
+    ];
+    let a = [0, 1, 1, 2, 2, 3, 3, 0, 4, 7, 7, 6, 6, 5, 5, 4];
+    let b = [8, 9, 9, 10, 10, 11, 11, 8];
+    for (op, expected, n_loops) in [
+        (BooleanOp::Union, 18.0, 2),
+        (BooleanOp::Intersection, 3.0, 1),
+        (BooleanOp::Difference, 9.0, 1),
+        (BooleanOp::Xor, 15.0, 3),
+    ] {
+        let (area, n) = measure(&points, &a, &b, op);
+        assert!((area - expected).abs() < 1e-12);
+        assert_eq!(n, n_loops);
+    }
+}
+
+#[test]
+fn test_triangulate_planar_polygon() {
+    let bump = Bump::new();
+    // a square with a square hole in the plane x + y + z = 1, seen from below
+    let lift = |x: f64, y: f64| [x, y, 1.0 - x - y];
+    let mut points = Vec::new();
+    for [x, y] in [
+        [0.0, 0.0],
+        [0.0, 4.0],
+        [4.0, 4.0],
+        [4.0, 0.0],
+        [1.0, 1.0],
+        [1.0, 3.0],
+        [3.0, 3.0],
+        [3.0, 1.0],
+    ] {
+        points.extend(lift(x, y));
+    }
+    let p = |points: &[f64], i: usize| [points[i * 3], points[i * 3 + 1], points[i * 3 + 2]];
+    let loops = [0, 1, 2, 3, 4, 5, 6, 7];
+    let offsets = [0, 4, 8];
+    let triangles = triangulate_planar_polygon(&points, &loops, &offsets, 1e-12, &bump).unwrap();
+    assert_eq!(triangles.len(), 8 * 3);
+    // the triangles face the same side as the outer loop, towards -(1, 1, 1)
+    let mut area = 0.0;
+    for tri in triangles.chunks(3) {
+        let [pa, pb, pc] = [p(&points, tri[0]), p(&points, tri[1]), p(&points, tri[2])];
+        let u = [pb[0] - pa[0], pb[1] - pa[1], pb[2] - pa[2]];
+        let v = [pc[0] - pa[0], pc[1] - pa[1], pc[2] - pa[2]];
+        let normal = [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ];
+        assert!(normal.iter().all(|&x| x < 0.0));
+        area += -normal[2] * 0.5;
+    }
+    assert!((area - 12.0).abs() < 1e-12);
+
+    // a vertex lifted off the plane
+    let mut bent = points.clone();
+    bent[6 * 3 + 2] += 1e-3;
+    assert_eq!(
+        triangulate_planar_polygon(&bent, &loops, &offsets, 1e-6, &bump).unwrap_err(),
+        PolygonError::NonPlanar
+    );
+    assert!(triangulate_planar_polygon(&bent, &loops, &offsets, 1e-2, &bump).is_ok());
+
+    // a hole crossing the outer boundary and a bow tie
+    let mut crossing = points.clone();
+    crossing.extend(lift(5.0, 2.0));
+    let loops = [0, 1, 2, 3, 4, 5, 8, 7];
+    assert_eq!(
+        triangulate_planar_polygon(&crossing, &loops, &offsets, 1e-12, &bump).unwrap_err(),
+        PolygonError::SelfIntersecting
+    );
+    assert_eq!(
+        triangulate_planar_polygon(&crossing, &[0, 2, 1, 8], &[0, 4], 1e-12, &bump).unwrap_err(),
+        PolygonError::SelfIntersecting
+    );
+    assert_eq!(
+        triangulate_planar_polygon(&points, &[0, 4, 6], &[0, 3], 1e-12, &bump).unwrap_err(),
+        PolygonError::Degenerate
+    );
+
+    // offsets which do not split the loops
+    for offsets in [&[][..], &[0], &[0, 4, 2], &[0, 4, 9]] {
+        assert_eq!(
+            triangulate_planar_polygon(&points, &loops, offsets, 1e-12, &bump).unwrap_err(),
+            PolygonError::InvalidOffsets
+        );
+    }
+}
+
+#[test]
+fn test_triangulate_polygon_soup_parallel() {
+    // random star shaped polygons in horizontal planes
+    let mut rng = SmallRng::seed_from_u64(7);
+    let mut points = Vec::new();
+    let mut edges = Vec::new();
+    let mut axes = Vec::new();
+    for face in 0..3000 {
+        let n = rng.gen_range(3..12);
+        let start = points.len() / 3;
+        for i in 0..n {
+            let angle = std::f64::consts::TAU * (i as f64 + rng.gen_range(0.0..0.5)) / n as f64;
+            let radius = rng.gen_range(0.5..1.0);
+            points.extend([radius * angle.cos(), radius * angle.sin(), face as f64]);
+        }
+        edges.push(Vec::from_iter(
+            (0..n).flat_map(|i| [start + i, start + (i + 1) % n]),
+        ));
+        axes.extend([0.0, 0.0, face as f64, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+    }
+    let (triangles, parents) = triangulate_polygon_soup(&points, &edges, &axes);
+    assert_eq!(
+        triangles.len(),
+        edges.iter().map(|e| e.len() / 2 - 2).sum::<usize>() * 3
+    );
+    assert_eq!(
+        triangulate_polygon_soup_parallel(&points, &edges, &axes),
+        (triangles, parents)
+    );
+}
+
+#[test]
+fn test_voronoi() {
+    let bump = Bump::new();
+    let inside = |polygon: &[f64], p: [f64; 2]| {
+        let n = polygon.len() / 2;
+        (0..n).all(|i| {
+            let (ax, ay) = (polygon[i * 2], polygon[i * 2 + 1]);
+            let (bx, by) = (polygon[(i + 1) % n * 2], polygon[(i + 1) % n * 2 + 1]);
+            (bx - ax) * (p[1] - ay) - (by - ay) * (p[0] - ax) >= -1e-12
+        })
+    };
+
+    // random sites, the cell of the nearest site contains the query points
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let mut points: Vec<f64> = Vec::from_iter((0..400).map(|_| rng.gen_range(0.0..1.0)));
+    points.extend_from_within(0..2);
+    let diagram = voronoi(&points, &[0.0, 0.0, 1.0, 1.0], &bump);
+    assert_eq!(diagram.n_cells(), 201);
+    assert_eq!(diagram.cell(200).count(), 0);
+    let total = (0..201).map(|i| diagram.area(i)).sum::<f64>();
+    assert!((total - 1.0).abs() < 1e-9);
+    for _ in 0..1000 {
+        let q = [rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)];
+        let nearest = (0..200)
+            .min_by(|&i, &j| {
+                let d = |k: usize| (points[k * 2] - q[0]).hypot(points[k * 2 + 1] - q[1]);
+                d(i).partial_cmp(&d(j)).unwrap()
+            })
+            .unwrap();
+        assert!(inside(diagram.cell(nearest).next().unwrap(), q));
+    }
+
+    // a grid has many cocircular sites
+    let points = Vec::from_iter((0..25).flat_map(|i| [(i % 5) as f64, (i / 5) as f64]));
+    let diagram = voronoi(&points, &[-0.5, -0.5, 4.5, 4.5], &bump);
+    for i in 0..25 {
+        assert!((diagram.area(i) - 1.0).abs() < 1e-12);
+    }
+
+    // cocircular sites around (1/3, 1/3), whose triangles have different
+    // rounded circumcenters, share one vertex
+    let sites = [-2.0, -1.0, -1.0, -2.0, 3.0, 0.0, 0.0, 3.0];
+    let near_center = |diagram: &VoronoiDiagram<&Bump>| {
+        let mut vertices = Vec::from_iter((0..4).flat_map(|i| {
+            diagram
+                .cell(i)
+                .flat_map(|polygon| polygon.chunks(2).map(|p| [p[0], p[1]]))
+                .filter(|p| (p[0] - 1.0 / 3.0).hypot(p[1] - 1.0 / 3.0) < 1e-9)
+                .collect::<Vec<_>>()
+        }));
+        vertices.dedup();
+        vertices.len()
+    };
+    let diagram = voronoi(&sites, &[-5.0, -5.0, 5.0, 5.0], &bump);
+    let total = (0..4).map(|i| diagram.area(i)).sum::<f64>();
+    assert!((total - 100.0).abs() < 1e-12);
+    assert_eq!(near_center(&diagram), 1);
+    let diagram = voronoi_in_domain(&sites, &[0, 1, 1, 2, 2, 3, 3, 0], &bump);
+    let total = (0..4).map(|i| diagram.area(i)).sum::<f64>();
+    assert!((total - 12.0).abs() < 1e-12);
+    assert_eq!(near_center(&diagram), 1);
+
+    // nearly cocircular sites, the cells stay close to the ones of the grid
+    let points = Vec::from_iter(
+        points
+            .iter()
+            .map(|&x| x + rng.gen_range(-1.0..1.0) * f64::EPSILON * 4.0),
+    );
+    let diagram = voronoi(&points, &[-0.5, -0.5, 4.5, 4.5], &bump);
+    for i in 0..25 {
+        assert!((diagram.area(i) - 1.0).abs() < 1e-9);
+        assert!(inside(
+            diagram.cell(i).next().unwrap(),
+            [points[i * 2], points[i * 2 + 1]]
+        ));
+    }
+    let total = (0..25).map(|i| diagram.area(i)).sum::<f64>();
+    assert!((total - 25.0).abs() < 1e-9);
+
+    // collinear sites
+    let points = [0.0, 0.0, 3.0, 0.0, 1.0, 0.0];
+    let diagram = voronoi(&points, &[-1.0, -1.0, 4.0, 1.0], &bump);
+    for (i, area) in [3.0, 4.0, 3.0].into_iter().enumerate() {
+        assert!((diagram.area(i) - area).abs() < 1e-12);
+    }
+
+    // a square with a square hole
+    let mut points = Vec::new();
+    for (lo, hi) in [(0.0, 4.0), (1.0, 3.0)] {
+        points.extend([lo, lo, hi, lo, hi, hi, lo, hi]);
+    }
+    points.extend(Vec::from_iter((0..100).map(|_| rng.gen_range(0.0..4.0))));
+    let segments = [0, 1, 1, 2, 2, 3, 3, 0, 4, 7, 7, 6, 6, 5, 5, 4];
+    let diagram = voronoi_in_domain(&points, &segments, &bump);
+    let total = (0..diagram.n_cells()).map(|i| diagram.area(i)).sum::<f64>();
+    assert!((total - 12.0).abs() < 1e-9);
+    let cells = voronoi(&points, &[0.0, 0.0, 4.0, 4.0], &bump);
+    for i in 0..diagram.n_cells() {
+        assert!(diagram.area(i) <= cells.area(i) + 1e-12);
+    }
+}
+
 #[allow(dead_code)]
 fn read_points(name: &str) -> Vec<f64> {
     let f = File::open(name).unwrap();
@@ -92,3 +605,739 @@ fn test_tetrahedralize() {
     let tets = tetrahedralize(&points);
     assert!(tets.tets.len() > 0);
 }
+
+#[test]
+fn test_tetrahedralize_duplicates() {
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let n_points = 500;
+    let mut points = Vec::from_iter((0..(n_points * 3)).map(|_| rng.gen_range(0.0..1.0)));
+    // the insertion order is random, the points inserted after a duplicate
+    // are vertices too
+    for _ in 0..100 {
+        let vid = rng.gen_range(0..n_points);
+        points.extend_from_within((vid * 3)..(vid * 3 + 3));
+    }
+    let mesh = tetrahedralize(&points);
+
+    let point = |vid: usize| [points[vid * 3], points[vid * 3 + 1], points[vid * 3 + 2]];
+    let volume = |data: &[usize; 4]| {
+        let [pa, pb, pc, pd] = data.map(point);
+        let [u, v, w] = [pb, pc, pd].map(|p| [p[0] - pa[0], p[1] - pa[1], p[2] - pa[2]]);
+        (u[0] * (v[1] * w[2] - v[2] * w[1]) - u[1] * (v[0] * w[2] - v[2] * w[0])
+            + u[2] * (v[0] * w[1] - v[1] * w[0]))
+            / 6.0
+    };
+    let mut used = vec![false; points.len() / 3];
+    let mut total = 0.0;
+    for tid in 0..mesh.tets.len() {
+        if mesh.is_hull_tet(tid) {
+            continue;
+        }
+        let data = &mesh.tets[tid].data;
+        assert!(volume(data) > 0.0);
+        total += volume(data);
+        for &vid in data {
+            used[vid] = true;
+        }
+    }
+    // one vertex for each distinct point
+    let mut distinct = HashMap::new();
+    for (vid, &is_used) in used.iter().enumerate() {
+        assert_eq!(is_used, mesh.p2t[vid] != usize::MAX);
+        let key = point(vid).map(f64::to_bits);
+        *distinct.entry(key).or_insert(0) += is_used as usize;
+    }
+    assert_eq!(distinct.len(), n_points);
+    assert!(distinct.values().all(|&count| count == 1));
+
+    let unique = tetrahedralize(&points[..(n_points * 3)]);
+    let hull_volume: f64 = (0..unique.tets.len())
+        .filter(|&tid| !unique.is_hull_tet(tid))
+        .map(|tid| volume(&unique.tets[tid].data))
+        .sum();
+    assert!((total - hull_volume).abs() < 1e-9);
+}
+
+#[test]
+fn test_tetrahedralize_nearly_flat() {
+    let bump = Bump::new();
+    // a 4x4 grid and a point barely above its middle, every tetrahedron
+    // joins a triangle of the grid to that point
+    let mut points = Vec::from_iter((0..16).flat_map(|i| [(i % 4) as f64, (i / 4) as f64, 0.0]));
+    points.extend([1.5, 1.5, 1e-300]);
+    let apex = 16;
+    let mesh = tetrahedralize(&points);
+    let positive = gpf::predicates::orient3d(
+        &[0.0, 0.0, 0.0],
+        &[1.0, 0.0, 0.0],
+        &[0.0, 1.0, 0.0],
+        &[0.0, 0.0, 1.0],
+        &bump,
+    );
+    let tets = Vec::from_iter((0..mesh.tets.len()).filter(|&tid| !mesh.is_hull_tet(tid)));
+    // 2 * 16 - 12 - 2 triangles with 12 points on the boundary of the grid
+    assert_eq!(tets.len(), 18);
+    for tid in tets {
+        let data = mesh.tets[tid].data;
+        assert!(data.contains(&apex));
+        let [pa, pb, pc, pd] = data.map(|vid| mesh.point(vid));
+        let ori = gpf::predicates::orient3d(pa, pb, pc, pd, &bump);
+        assert_eq!(ori.signum(), positive.signum());
+    }
+    assert!(mesh.p2t.iter().all(|&tid| tid != usize::MAX));
+
+    // coplanar and collinear points give no tetrahedra
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let mut coplanar = Vec::new();
+    let mut collinear = Vec::new();
+    for _ in 0..100 {
+        let [x, y] = [0, 1].map(|_| rng.gen_range(-8..8) as f64);
+        coplanar.extend([x, y, x * 0.5 + y * 0.25]);
+        collinear.extend([x, x * 0.5, x * 0.25]);
+    }
+    for points in [coplanar, collinear] {
+        let mesh = tetrahedralize(&points);
+        assert!(mesh.tets.is_empty());
+        assert!(mesh.p2t.iter().all(|&tid| tid == usize::MAX));
+    }
+}
+
+#[test]
+fn test_tetrahedralize_refine() {
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let mut points = Vec::from_iter((0..600).map(|_| rng.gen_range(0.0..1.0)));
+    for i in 0..8 {
+        points.extend([0, 1, 2].map(|axis| ((i >> axis) & 1) as f64));
+    }
+    let (new_points, tets) = tetrahedralize_refine(&points, 1.5, 1e-3, 10.0, 100000);
+    assert!(!new_points.is_empty());
+    points.extend_from_slice(&new_points);
+
+    let point = |vid: usize| [points[vid * 3], points[vid * 3 + 1], points[vid * 3 + 2]];
+    let sub = |p: [f64; 3], q: [f64; 3]| [q[0] - p[0], q[1] - p[1], q[2] - p[2]];
+    let dot = |u: [f64; 3], v: [f64; 3]| u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+    let cross = |u: [f64; 3], v: [f64; 3]| {
+        [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ]
+    };
+    let mut total = 0.0;
+    for tet in tets.chunks(4) {
+        let [pa, pb, pc, pd] = [tet[0], tet[1], tet[2], tet[3]].map(point);
+        let [u, v, w] = [pb, pc, pd].map(|p| sub(pa, p));
+        let det = dot(u, cross(v, w));
+        assert!(det > 0.0);
+        assert!(det / 6.0 <= 1e-3);
+        total += det / 6.0;
+
+        let mut center = [0.0; 3];
+        for (e, n) in [(u, cross(v, w)), (v, cross(w, u)), (w, cross(u, v))] {
+            for i in 0..3 {
+                center[i] += n[i] * dot(e, e) / (2.0 * det);
+            }
+        }
+        let min_edge = [(pa, pb), (pa, pc), (pa, pd), (pb, pc), (pb, pd), (pc, pd)]
+            .map(|(p, q)| dot(sub(p, q), sub(p, q)))
+            .into_iter()
+            .fold(f64::INFINITY, f64::min);
+        assert!(dot(center, center) <= 1.5 * 1.5 * min_edge * (1.0 + 1e-9));
+    }
+    assert!((total - 1.0).abs() < 1e-9);
+
+    // the split points of the faces of an octahedron are rounded off its
+    // faces, those rounded inside are inserted, and the slivers next to them
+    // do not split the hull again
+    let mut points = vec![
+        1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, -1.0,
+    ];
+    while points.len() < 300 {
+        let p = [0, 1, 2].map(|_| rng.gen_range(-1.0..1.0f64));
+        if p.iter().map(|x| x.abs()).sum::<f64>() < 1.0 {
+            points.extend(p);
+        }
+    }
+    let (new_points, tets) = tetrahedralize_refine(&points, 2.0, 1e-2, 10.0, 100000);
+    assert!(new_points.len() / 3 < 10000);
+    assert!(new_points.chunks(3).any(|p| {
+        let norm = p.iter().map(|x| x.abs()).sum::<f64>();
+        norm != 1.0 && (norm - 1.0).abs() < 1e-12
+    }));
+    points.extend_from_slice(&new_points);
+    let point = |vid: usize| [points[vid * 3], points[vid * 3 + 1], points[vid * 3 + 2]];
+    let total = tets
+        .chunks(4)
+        .map(|tet| {
+            let [pa, pb, pc, pd] = [tet[0], tet[1], tet[2], tet[3]].map(point);
+            let [u, v, w] = [pb, pc, pd].map(|p| sub(pa, p));
+            dot(u, cross(v, w)) / 6.0
+        })
+        .sum::<f64>();
+    assert!((total - 4.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_tetrahedralize_weighted() {
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let n_points = 2000;
+    let points = Vec::from_iter((0..(n_points * 3)).map(|_| rng.gen_range(0.0..1.0)));
+    let weights = Vec::from_iter((0..n_points).map(|_| rng.gen_range(0.0..0.05)));
+    let mesh = tetrahedralize_weighted(&points, &weights);
+
+    let volume = |data: &[usize; 4]| {
+        let [pa, pb, pc, pd] = data.map(|vid| &points[(vid * 3)..(vid * 3 + 3)]);
+        let [u, v, w] = [pb, pc, pd].map(|p| [p[0] - pa[0], p[1] - pa[1], p[2] - pa[2]]);
+        (u[0] * (v[1] * w[2] - v[2] * w[1]) - u[1] * (v[0] * w[2] - v[2] * w[0])
+            + u[2] * (v[0] * w[1] - v[1] * w[0]))
+            / 6.0
+    };
+    let power = |c: [f64; 3], vid: usize| {
+        let p = &points[(vid * 3)..(vid * 3 + 3)];
+        let d = [p[0] - c[0], p[1] - c[1], p[2] - c[2]];
+        d[0] * d[0] + d[1] * d[1] + d[2] * d[2] - weights[vid]
+    };
+    let mut used = vec![false; n_points];
+    let mut total = 0.0;
+    for tid in 0..mesh.tets.len() {
+        if mesh.is_hull_tet(tid) {
+            continue;
+        }
+        let data = &mesh.tets[tid].data;
+        assert!(volume(data) > 0.0);
+        total += volume(data);
+        for &vid in data {
+            used[vid] = true;
+        }
+        // no vertex of the neighbors is closer to the orthocenter in power
+        let center = mesh.orthocenter(tid);
+        let radius = power(center, data[0]);
+        for nei in &mesh.tets[tid].nei {
+            if mesh.is_hull_tet(nei.tet) {
+                continue;
+            }
+            for &vid in &mesh.tets[nei.tet].data {
+                assert!(power(center, vid) >= radius - 1e-9);
+            }
+        }
+    }
+    let redundant = (0..n_points).filter(|&i| !used[i]).count();
+    assert!(redundant > 0);
+    for (i, &is_used) in used.iter().enumerate() {
+        assert_eq!(is_used, mesh.p2t[i] != usize::MAX);
+    }
+
+    // the regular triangulation covers the convex hull like the Delaunay one
+    let delaunay = tetrahedralize(&points);
+    let hull_volume: f64 = (0..delaunay.tets.len())
+        .filter(|&tid| !delaunay.is_hull_tet(tid))
+        .map(|tid| volume(&delaunay.tets[tid].data))
+        .sum();
+    assert!((total - hull_volume).abs() < 1e-9);
+}
+
+#[test]
+fn test_tetmesh_locate() {
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let mut points = Vec::from_iter((0..3000).map(|_| rng.gen_range(0.0..1.0)));
+    for i in 0..8 {
+        points.extend([0, 1, 2].map(|axis| ((i >> axis) & 1) as f64));
+    }
+    let mesh = tetrahedralize(&points);
+    let field = |p: &[f64]| 1.0 + 2.0 * p[0] - 3.0 * p[1] + 0.5 * p[2];
+
+    // linear fields are interpolated exactly, walking from the previous tet
+    let mut hint = None;
+    for _ in 0..1000 {
+        let p = [0, 1, 2].map(|_| rng.gen_range(0.0..1.0));
+        let found = mesh.locate(&p, hint, &bump).unwrap();
+        assert_eq!(found.location, TetLocation::Tet);
+        assert!(!mesh.is_hull_tet(found.tet));
+        assert!(found.barycentric.iter().all(|&b| b >= 0.0));
+        let data = &mesh.tets[found.tet].data;
+        let value: f64 = (0..4)
+            .map(|i| found.barycentric[i] * field(&points[(data[i] * 3)..]))
+            .sum();
+        assert!((value - field(&p)).abs() < 1e-9);
+        hint = Some(found.tet);
+    }
+
+    let found = mesh.locate(&points[30..33], None, &bump).unwrap();
+    assert_eq!(found.location, TetLocation::Vertex(10));
+    let i = mesh.tets[found.tet].index(10).unwrap();
+    assert_eq!(found.barycentric[i], 1.0);
+
+    // the faces of the cube are made of hull faces
+    let found = mesh.locate(&[0.3, 0.6, 0.0], None, &bump).unwrap();
+    assert!(matches!(
+        found.location,
+        TetLocation::Face(_) | TetLocation::Edge(_)
+    ));
+    assert!(found.barycentric.contains(&0.0));
+
+    let found = mesh.locate(&[0.5, 0.5, 1.5], None, &bump).unwrap();
+    assert_eq!(found.location, TetLocation::Outside);
+    assert!(found.barycentric.iter().any(|&b| b < 0.0));
+}
+
+/// Check that the neighbors of the tetrahedra match, that they are positively
+/// oriented and Delaunay, and return their volume.
+fn check_tet_mesh(mesh: &TetMesh) -> f64 {
+    let point = |vid: usize| {
+        let p = mesh.point(vid);
+        [p[0], p[1], p[2]]
+    };
+    let sub = |p: [f64; 3], q: [f64; 3]| [q[0] - p[0], q[1] - p[1], q[2] - p[2]];
+    let dot = |u: [f64; 3], v: [f64; 3]| u[0] * v[0] + u[1] * v[1] + u[2] * v[2];
+    let mut total = 0.0;
+    for (tid, tet) in mesh.tets.iter().enumerate() {
+        for nei in &tet.nei {
+            assert_eq!(mesh.tets[nei.tet].nei[nei.ver & 3].tet, tid);
+        }
+        if mesh.is_hull_tet(tid) {
+            continue;
+        }
+        let [pa, pb, pc, pd] = tet.data.map(point);
+        let [u, v, w] = [pb, pc, pd].map(|p| sub(pa, p));
+        let det = u[0] * (v[1] * w[2] - v[2] * w[1]) - u[1] * (v[0] * w[2] - v[2] * w[0])
+            + u[2] * (v[0] * w[1] - v[1] * w[0]);
+        assert!(det > 0.0);
+        total += det / 6.0;
+        let center = mesh.orthocenter(tid);
+        let radius = dot(sub(center, pa), sub(center, pa));
+        for nei in &tet.nei {
+            for &vid in &mesh.tets[nei.tet].data {
+                if vid != mesh.n_points {
+                    let d = sub(center, point(vid));
+                    assert!(dot(d, d) >= radius * (1.0 - 1e-9));
+                }
+            }
+        }
+    }
+    for vid in mesh.vertices() {
+        if mesh.p2t[vid] != usize::MAX {
+            assert!(mesh.tets[mesh.p2t[vid]].data.contains(&vid));
+        }
+    }
+    total
+}
+
+#[test]
+fn test_tetmesh_remove_vertex() {
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let n_points = 1000;
+    let points = Vec::from_iter((0..(n_points * 3)).map(|_| rng.gen_range(0.0..1.0)));
+    let mut mesh = tetrahedralize(&points);
+    for vid in (0..n_points).step_by(2) {
+        assert!(mesh.remove_vertex(vid, &bump));
+    }
+    assert!(!mesh.remove_vertex(0, &bump));
+    let volume = check_tet_mesh(&mesh);
+
+    // the remaining points have the same tetrahedralization
+    let rest = Vec::from_iter(
+        (1..n_points)
+            .step_by(2)
+            .flat_map(|vid| [0, 1, 2].map(|i| points[vid * 3 + i])),
+    );
+    let rest_mesh = tetrahedralize(&rest);
+    let n_tets = |mesh: &TetMesh| {
+        (0..mesh.tets.len())
+            .filter(|&tid| !mesh.is_hull_tet(tid))
+            .count()
+    };
+    assert_eq!(n_tets(&mesh), n_tets(&rest_mesh));
+    assert!((volume - check_tet_mesh(&rest_mesh)).abs() < 1e-9);
+}
+
+#[test]
+fn test_tetmesh_relocate_vertex() {
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let n_points = 1000;
+    let points = Vec::from_iter((0..(n_points * 3)).map(|_| rng.gen_range(0.0..1.0)));
+    let mut mesh = tetrahedralize(&points);
+    for _ in 0..3 {
+        for vid in 0..n_points {
+            let p = [0, 1, 2].map(|i| mesh.points[vid * 3 + i] + rng.gen_range(-0.05..0.05));
+            assert!(mesh.relocate_vertex(vid, &p, &bump));
+            assert_eq!(mesh.point(vid), &p);
+        }
+    }
+    // moving onto another vertex fails
+    let p = [0, 1, 2].map(|i| mesh.points[3 + i]);
+    assert!(!mesh.relocate_vertex(0, &p, &bump));
+    check_tet_mesh(&mesh);
+
+    let moved = mesh.points.to_vec();
+    let moved_mesh = tetrahedralize(&moved);
+    assert!((check_tet_mesh(&mesh) - check_tet_mesh(&moved_mesh)).abs() < 1e-9);
+    assert_eq!(mesh.tets.len(), moved_mesh.tets.len());
+}
+
+#[test]
+fn test_tetmesh_insert_point() {
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let n_points = 500;
+    let points = Vec::from_iter((0..(n_points * 3)).map(|_| rng.gen_range(0.0..1.0)));
+    let mut mesh = tetrahedralize(&points);
+    // the new points grow the hull and follow the ghost vertex
+    let mut all_points = points.clone();
+    let mut hint = None;
+    for i in 0..n_points {
+        let p = [0, 1, 2].map(|_| rng.gen_range(-0.5..1.5));
+        let pid = mesh.insert_point(&p, hint, &bump).unwrap();
+        assert_eq!(pid, n_points + 1 + i);
+        assert_eq!(mesh.point(pid), &p);
+        all_points.extend(p);
+        hint = Some(mesh.p2t[pid]);
+    }
+    assert!(mesh.insert_point(&all_points[..3], None, &bump).is_none());
+    assert_eq!(mesh.vertices().count(), n_points * 2);
+    assert!(mesh
+        .vertices()
+        .enumerate()
+        .all(|(i, vid)| mesh.output_index(vid) == i));
+    // the input points are still borrowed, the inserted ones are apart
+    assert!(matches!(mesh.points, Cow::Borrowed(_)));
+    assert_eq!(mesh.n_points, n_points);
+    assert_eq!(mesh.new_points, all_points[(n_points * 3)..]);
+    let volume = check_tet_mesh(&mesh);
+
+    let all_mesh = tetrahedralize(&all_points);
+    assert!((volume - check_tet_mesh(&all_mesh)).abs() < 1e-9);
+    assert_eq!(mesh.tets.len(), all_mesh.tets.len());
+}
+
+#[test]
+fn test_tet_mesh_io() {
+    let bump = Bump::new();
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let n_points = 200;
+    let points = Vec::from_iter((0..(n_points * 3)).map(|_| rng.gen_range(0.0..1.0)));
+    let mut mesh = tetrahedralize(&points);
+    let pid = mesh.insert_point(&[2.0, 0.5, 0.5], None, &bump).unwrap();
+    let marks = Vec::from_iter(0..mesh.tets.len());
+    let data = TetMeshData::new(&mesh, Some(&marks)).unwrap();
+    let n_tets = (0..mesh.tets.len())
+        .filter(|&tid| !mesh.is_hull_tet(tid))
+        .count();
+    assert_eq!(data.points.len(), (n_points + 1) * 3);
+    assert_eq!(data.points[(pid - 1) * 3..], [2.0, 0.5, 0.5]);
+    assert_eq!(data.tets.len(), n_tets * 4);
+    let data_marks = data.marks.as_ref().unwrap();
+    assert!(data_marks.iter().all(|&tid| !mesh.is_hull_tet(tid)));
+
+    // the boundary faces are the faces of the hull tetrahedra
+    let faces = data.boundary_faces();
+    assert_eq!(faces.len() / 3, mesh.tets.len() - n_tets);
+
+    let mut medit = Vec::new();
+    data.write_medit(&mut medit).unwrap();
+    assert_eq!(TetMeshData::read_medit(&medit[..]).unwrap(), data);
+
+    let (mut node, mut ele, mut face) = (Vec::new(), Vec::new(), Vec::new());
+    data.write_tetgen(&mut node, &mut ele, &mut face).unwrap();
+    assert_eq!(TetMeshData::read_tetgen(&node[..], &ele[..]).unwrap(), data);
+    let face = String::from_utf8(face).unwrap();
+    assert_eq!(face.lines().count(), faces.len() / 3 + 1);
+
+    let mut vtk = Vec::new();
+    data.write_vtk(&mut vtk).unwrap();
+    assert!(String::from_utf8_lossy(&vtk).contains("SCALARS mark unsigned_long 1"));
+    assert_eq!(TetMeshData::read_vtk(&vtk[..]).unwrap(), data);
+
+    // the marks must match the tetrahedra, before reading and writing
+    assert!(TetMeshData::new(&mesh, Some(&marks[1..])).is_err());
+    let mut invalid = data.clone();
+    invalid.marks.as_mut().unwrap().pop();
+    assert!(invalid.write_medit(Vec::new()).is_err());
+    assert!(invalid
+        .write_tetgen(Vec::new(), Vec::new(), Vec::new())
+        .is_err());
+    assert!(invalid.write_vtk(Vec::new()).is_err());
+    let mut invalid = data.clone();
+    invalid.tets[0] = n_points + 1;
+    assert!(invalid.write_medit(Vec::new()).is_err());
+
+    // the marks are optional
+    let data = TetMeshData::new(&mesh, None).unwrap();
+    let mut vtk = Vec::new();
+    data.write_vtk(&mut vtk).unwrap();
+    assert_eq!(TetMeshData::read_vtk(&vtk[..]).unwrap(), data);
+    let (mut node, mut ele, mut face) = (Vec::new(), Vec::new(), Vec::new());
+    data.write_tetgen(&mut node, &mut ele, &mut face).unwrap();
+    assert_eq!(TetMeshData::read_tetgen(&node[..], &ele[..]).unwrap(), data);
+    assert!(TetMeshData::read_medit(&b"Dimension 3 Vertices 1 0 0 0"[..]).is_err());
+
+    // the marks are integers, and the counts are not trusted
+    let node = b"4 3 0 0\n0 0 0 0\n1 1 0 0\n2 0 1 0\n3 0 0 1\n";
+    let ele = |mark: &str| format!("1 4 1\n0 0 1 2 3 {}\n", mark).into_bytes();
+    let data = TetMeshData::read_tetgen(&node[..], &ele("2.0")[..]).unwrap();
+    assert_eq!(data.marks, Some(vec![2]));
+    for mark in ["2.5", "-1", "1e300"] {
+        assert!(TetMeshData::read_tetgen(&node[..], &ele(mark)[..]).is_err());
+    }
+    let huge = format!("Dimension 3 Vertices {} 0 0 0 0", usize::MAX);
+    assert!(TetMeshData::read_medit(huge.as_bytes()).is_err());
+    let huge = format!("Dimension 3 Edges {} 1 2 0", usize::MAX);
+    assert!(TetMeshData::read_medit(huge.as_bytes()).is_err());
+    let huge = format!("a\nb\nASCII\nPOINTS {} double 0 0 0", usize::MAX);
+    assert!(TetMeshData::read_vtk(huge.as_bytes()).is_err());
+}
+
+/// Check that every directed edge of the oriented triangles is matched by the
+/// opposite edge.
+fn check_closed_surface(triangles: &[usize]) {
+    let mut edges = HashMap::new();
+    for tri in triangles.chunks(3) {
+        for i in 0..3 {
+            *edges.entry((tri[i], tri[(i + 1) % 3])).or_insert(0) += 1;
+        }
+    }
+    for (&(a, b), &count) in &edges {
+        assert_eq!(edges.get(&(b, a)), Some(&count));
+    }
+}
+
+#[test]
+fn test_alpha_shape() {
+    // the corners of a cube are cospherical, all the tetrahedra have the same
+    // circumradius
+    let cube = [
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0,
+        0.0, 1.0, 1.0, 1.0, 1.0, 1.0,
+    ];
+    let complex = alpha_complex(&cube);
+    let radius = complex.optimal_alpha();
+    assert!((radius - 3.0f64.sqrt() / 2.0).abs() < 1e-15);
+    assert_eq!(complex.boundary(radius).len(), 12 * 3);
+    assert!(complex.boundary(radius.next_down()).is_empty());
+    // the edges of the cube are in the complex from half their length
+    let eid = complex
+        .edges
+        .iter()
+        .position(|e| e.vertices == [0, 1])
+        .unwrap();
+    assert_eq!(complex.edges[eid].radius, Some(0.5));
+    assert_eq!(
+        complex.classify(0.5f64.next_down()).edges[eid],
+        AlphaClass::Exterior
+    );
+    assert_eq!(complex.classify(0.5).edges[eid], AlphaClass::Singular);
+    let shape = complex.classify(radius);
+    assert_eq!(shape.edges[eid], AlphaClass::Regular);
+    assert_eq!(shape.vertices[0], AlphaClass::Regular);
+    assert_eq!(complex.classify(0.0).vertices[0], AlphaClass::Singular);
+
+    // two clusters of points
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let n_points = 1000;
+    let points = Vec::from_iter((0..n_points).flat_map(|i| {
+        let offset = if i % 2 == 0 { 0.0 } else { 3.0 };
+        let mut p = [0, 1, 2].map(|_| rng.gen_range(0.0..1.0));
+        p[0] += offset;
+        p
+    }));
+    let complex = alpha_complex(&points);
+    let alpha = complex.optimal_alpha();
+    assert!(alpha > 1.0);
+    assert_eq!(complex.n_components(alpha), 1);
+    assert!(complex.n_components(0.3) >= 2);
+    check_closed_surface(&complex.boundary(alpha));
+    check_closed_surface(&complex.boundary(0.3));
+    // all the points are on the solid
+    let shape = complex.classify(alpha);
+    assert!(shape
+        .vertices
+        .iter()
+        .all(|&c| c == AlphaClass::Regular || c == AlphaClass::Interior));
+    // with an infinite alpha, the shape is the convex hull
+    let n_hull = (0..complex.mesh.tets.len())
+        .filter(|&tid| complex.mesh.is_hull_tet(tid))
+        .count();
+    let hull = complex.boundary(f64::INFINITY);
+    assert_eq!(hull.len(), n_hull * 3);
+    check_closed_surface(&hull);
+
+    // the faces of the simplices in the complex are in it
+    let shape = complex.classify(0.1);
+    for (face, &class) in complex.faces.iter().zip(&shape.faces) {
+        let tets = face.tets.map(|tid| shape.tets[tid]);
+        assert_eq!(
+            class == AlphaClass::Regular || class == AlphaClass::Interior,
+            tets.contains(&AlphaClass::Interior)
+        );
+        if class != AlphaClass::Exterior {
+            for eid in face.edges {
+                assert_ne!(shape.edges[eid], AlphaClass::Exterior);
+                assert_ne!(shape.edges[eid], AlphaClass::Singular);
+            }
+        }
+    }
+}
+
+/// The volume enclosed by a closed surface oriented outwards.
+fn volume(points: &[f64], triangles: &[usize]) -> f64 {
+    triangles
+        .chunks(3)
+        .map(|tri| {
+            let [a, b, c] = [0, 1, 2].map(|i| &points[tri[i] * 3..tri[i] * 3 + 3]);
+            (a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+                + a[2] * (b[0] * c[1] - b[1] * c[0]))
+                / 6.0
+        })
+        .sum()
+}
+
+#[test]
+fn test_surface_reconstruction() {
+    // a torus, whose hole is not on the convex hull
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let (major, minor) = (1.0, 0.4);
+    let n_points = 4000;
+    let mut points = Vec::with_capacity(n_points * 3);
+    let mut normals = Vec::with_capacity(n_points * 3);
+    for _ in 0..n_points {
+        let u: f64 = rng.gen_range(0.0..std::f64::consts::TAU);
+        let v: f64 = rng.gen_range(0.0..std::f64::consts::TAU);
+        let n = [u.cos() * v.cos(), u.sin() * v.cos(), v.sin()];
+        let r = major + minor * v.cos();
+        points.extend([r * u.cos(), r * u.sin(), minor * v.sin()]);
+        normals.extend(n);
+    }
+    let sight = LineOfSight::Normals {
+        normals: &normals,
+        ray_length: minor,
+    };
+    let triangles = reconstruct_surface(&points, sight, 1.0);
+    check_closed_surface(&triangles);
+    let expected = 2.0 * std::f64::consts::PI.powi(2) * major * minor * minor;
+    let vol = volume(&points, &triangles);
+    assert!((vol - expected).abs() < expected * 0.05);
+
+    // a sphere seen from sensors around it
+    let n_points = 2000;
+    let points = Vec::from_iter((0..n_points).flat_map(|_| loop {
+        let p = [0, 1, 2].map(|_| rng.gen_range(-1.0..1.0f64));
+        let norm = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+        if norm > 0.1 && norm < 1.0 {
+            break p.map(|x| x / norm);
+        }
+    }));
+    let sensors = Vec::from_iter(points.iter().map(|x| x * 3.0));
+    let triangles = reconstruct_surface(&points, LineOfSight::Sensors(&sensors), 1.0);
+    check_closed_surface(&triangles);
+    let expected = 4.0 / 3.0 * std::f64::consts::PI;
+    let vol = volume(&points, &triangles);
+    assert!((vol - expected).abs() < expected * 0.05);
+    // all the points are on the surface
+    assert_eq!(triangles.len() / 3, 2 * n_points - 4);
+}
+
+#[test]
+fn test_convex_hull() {
+    // the corners of a cube with points inside and on its faces
+    let mut rng = SmallRng::seed_from_u64(5489);
+    let mut points = vec![
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0,
+        0.0, 1.0, 1.0, 1.0, 1.0, 1.0,
+    ];
+    for i in 0..1000 {
+        let mut p = [0, 1, 2].map(|_| rng.gen_range(0.0..1.0));
+        if i % 10 == 0 {
+            p[i % 3] = 1.0;
+        }
+        points.extend(p);
+    }
+    let hull = convex_hull(&points);
+    assert_eq!(hull.dimension, 3);
+    check_closed_surface(&hull.triangles);
+    assert!((volume(&points, &hull.triangles) - 1.0).abs() < 1e-12);
+    let bump = Bump::new();
+    for tri in hull.triangles.chunks(3) {
+        let [pa, pb, pc] = [0, 1, 2].map(|i| &points[tri[i] * 3..tri[i] * 3 + 3]);
+        for p in points.chunks(3) {
+            assert!(gpf::predicates::orient3d(pa, pb, pc, p, &bump) >= 0.0);
+        }
+    }
+    // the points on the faces are vertices of the hull
+    assert_eq!(hull.vertices.len(), 8 + 100);
+
+    // coplanar points, with a tilted square as hull
+    let mut points = vec![0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 2.0];
+    for _ in 0..100 {
+        let [x, y] = [0, 1].map(|_| rng.gen_range(0.0..1.0));
+        points.extend([x, y, x + y]);
+    }
+    points.extend([0.5, 0.0, 0.5, 0.0, 0.0, 0.0]);
+    let hull = convex_hull(&points);
+    assert_eq!(hull.dimension, 2);
+    // the point on an edge is kept, and the duplicate of a corner is not
+    assert_eq!(hull.vertices, vec![0, 1, 2, 3, 104]);
+    assert_eq!(hull.triangles.len(), 2 * 3 * 3);
+    check_closed_surface(&hull.triangles);
+
+    // collinear and single points
+    let points = [
+        2.0, 2.0, 2.0, 0.0, 0.0, 0.0, 3.0, 3.0, 3.0, 1.0, 1.0, 1.0, 3.0, 3.0, 3.0,
+    ];
+    let hull = convex_hull(&points);
+    assert_eq!(hull.dimension, 1);
+    assert_eq!(hull.vertices, vec![1, 2]);
+    let hull = convex_hull(&[1.0, 2.0, 3.0, 1.0, 2.0, 3.0]);
+    assert_eq!(hull.dimension, 0);
+    assert_eq!(hull.vertices, vec![0]);
+    assert_eq!(convex_hull(&[]), Default::default());
+
+    // a point barely off the plane of the others
+    let points = [
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.5, 0.5, 1e-300,
+    ];
+    let hull = convex_hull(&points);
+    assert_eq!(hull.dimension, 3);
+    assert_eq!(hull.triangles.len(), 6 * 3);
+    check_closed_surface(&hull.triangles);
+
+    // the incremental hull from collinear points to a sphere
+    let mut hull = IncrementalHull::new(&[0.0, 0.0, -1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(hull.hull().dimension, 1);
+    assert_eq!(hull.insert(&[0.0, 0.0, 0.0]), 2);
+    assert_eq!(hull.insert(&[1.0, 0.0, 0.0]), 3);
+    assert_eq!(hull.hull().dimension, 2);
+    let mut points = vec![0.0, 0.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+    for i in 0..500 {
+        let p = loop {
+            let p = [0, 1, 2].map(|_| rng.gen_range(-1.0..1.0f64));
+            let norm = (p[0] * p[0] + p[1] * p[1] + p[2] * p[2]).sqrt();
+            if norm > 0.1 && norm < 1.0 {
+                // some points inside the sphere
+                break p.map(|x| if i % 5 == 0 { x * 0.5 } else { x / norm });
+            }
+        };
+        assert_eq!(hull.insert(&p), points.len() / 3);
+        points.extend(p);
+        if i == 0 {
+            assert_eq!(hull.hull().dimension, 3);
+        }
+    }
+    let incremental = hull.hull();
+    let batch = convex_hull(&points);
+    check_closed_surface(&incremental.triangles);
+    assert_eq!(incremental.vertices, batch.vertices);
+    // the points inside the sphere are not on the hull
+    assert!(incremental
+        .vertices
+        .iter()
+        .all(|&v| v != 2 && (v < 4 || (v - 4) % 5 != 0)));
+    assert!(
+        (volume(&points, &incremental.triangles) - volume(&points, &batch.triangles)).abs() < 1e-12
+    );
+    // a duplicate does not change the hull
+    assert_eq!(hull.insert(&[1.0, 0.0, 0.0]), points.len() / 3);
+    assert_eq!(hull.n_points(), points.len() / 3 + 1);
+    assert_eq!(hull.hull(), incremental);
+}